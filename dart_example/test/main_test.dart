@@ -123,18 +123,63 @@ void main() {
     expect(await accounts.scalarI32(val: 123), equals(123));
     expect(await accounts.scalarU32(val: 123), equals(123));
     expect(await accounts.scalarI64(val: 10), equals(10));
-    expect(await accounts.scalarU64(val: 10), equals(Uint64.parse('10')));
-    expect(await accounts.scalarI128(val: 10), equals(Int128.parse('10')));
-    expect(await accounts.scalarU128(val: 10), equals(Uint128.parse('10')));
+    expect(await accounts.scalarU64(val: Uint64.parse('10')),
+        equals(Uint64.parse('10')));
+    expect(await accounts.scalarI128(val: Int128.parse('10')),
+        equals(Int128.parse('10')));
+    expect(await accounts.scalarU128(val: Uint128.parse('10')),
+        equals(Uint128.parse('10')));
     expect((await accounts.scalarF32(val: 21.1)).toStringAsFixed(1),
         equals('21.1'));
     expect(await accounts.scalarF64(val: 11.1), equals(11.1));
     expect(await accounts.scalarString(val: "hello world / ダミーテキスト"),
         equals("hello world / ダミーテキスト"));
     expect(await accounts.scalarBool(val: true), equals(true));
+    expect(await accounts.scalarChar(val: 'ダ'.runes.first), equals('ダ'));
+    expect(await accounts.scalarStr(val: "hello world / ダミーテキスト"),
+        equals("hello world / ダミーテキスト"));
     expect(() async => await accounts.scalarEmpty(), returnsNormally);
   });
 
+  test('checks that a scalar arg fits its Rust type before calling Rust',
+      () async {
+    final accounts = AccountsApi();
+    expect(() => accounts.scalarU8(val: 256), throwsRangeError);
+    expect(() => accounts.scalarI8(val: -129), throwsRangeError);
+    expect(() => accounts.scalarU32(val: -1), throwsRangeError);
+    expect(() => accounts.scalarF32(val: 1e39), throwsRangeError);
+    expect(() => accounts.scalarChar(val: 0xD800), throwsArgumentError);
+  });
+
+  test('can pass optional scalar args', () {
+    final accounts = AccountsApi();
+    expect(accounts.optionalScalars(),
+        equals('None None None None None None'));
+    expect(
+        accounts.optionalScalars(
+            one: 255,
+            two: Uint64.parse('18446744073709551615'),
+            three: 1.5,
+            four: 'a'.runes.first,
+            five: 'five',
+            six: Int128.parse('-10')),
+        equals(
+            'Some(255) Some(18446744073709551615) Some(1.5) Some(\'a\') Some("five") Some(-10)'));
+  });
+
+  test('can pass wrapped types and transparent newtypes as their inner type',
+      () async {
+    final accounts = AccountsApi();
+    expect(
+        await accounts.boxedContact(
+            contact:
+                Contact(id: 1, fullName: 'Alice Smith', status: Status.pending),
+            fullName: 'John Smith'),
+        equals(Contact(id: 1, fullName: 'John Smith', status: Status.pending)));
+    expect(await accounts.transparentId(id: 1, manager: 2), equals(3));
+    expect(await accounts.transparentId(id: 1), equals(1));
+  });
+
   test(
       'test that a function throws an ApiError instance when an error is returned',
       () async {
@@ -220,7 +265,8 @@ void main() {
 
   test('can handle a vec of integers', () async {
     final accounts = AccountsApi();
-    expect((await accounts.vecInt(values: [1, 2])), equals([1, 2]));
+    expect((await accounts.vecInt(values: Int64List.fromList([1, 2]))),
+        equals([1, 2]));
   });
 
   test('can handle a vec of unsigned integers', () async {
@@ -249,7 +295,9 @@ void main() {
 
   test('can handle a vec of floats', () async {
     final accounts = AccountsApi();
-    expect((await accounts.vecFloat(values: [1.0, 2.1])), equals([1.0, 2.1]));
+    expect(
+        (await accounts.vecFloat(values: Float64List.fromList([1.0, 2.1]))),
+        equals([1.0, 2.1]));
   });
 
   test('can handle a vec of vecs', () async {
@@ -342,6 +390,70 @@ void main() {
     expect((await accounts.optionalFloatArg()), equals(null));
   });
 
+  test('can pass maps and sets', () async {
+    final accounts = AccountsApi();
+    final contact =
+        Contact(id: 1, fullName: 'Alice Smith', status: Status.pending);
+    expect(await accounts.mapArg(values: {'alice': contact, 'nobody': null}),
+        equals({'alice': contact, 'nobody': null}));
+    expect(await accounts.setArg(values: {3, 1, 2}), equals({1, 2, 3}));
+  });
+
+  test('can pass and return tuples as records', () async {
+    final accounts = AccountsApi();
+    final (point, ids) = await accounts.tupleArg(point: (1, 'one'), contacts: [
+      (Contact(id: 1, fullName: 'Alice Smith', status: Status.pending), true),
+      (Contact(id: 2, fullName: 'John Smith', status: Status.active), false)
+    ]);
+    expect(point, equals('1 Some("one")'));
+    expect(ids, equals([1]));
+    expect((await accounts.tupleArg(point: (2, null), contacts: [])).$1,
+        equals('2 None'));
+  });
+
+  test('can call an infallible function', () async {
+    final accounts = AccountsApi();
+    expect(await accounts.infallibleArg(value: 2), equals(4));
+  });
+
+  test('can receive the message of an error which is not serializable',
+      () async {
+    final accounts = AccountsApi();
+    expect(await accounts.errorMessageArg(value: '2'), equals(2));
+    try {
+      await accounts.errorMessageArg(value: 'two');
+      fail('expected an error');
+    } on AccountsApiError catch (err) {
+      final error = err.e as MembraneErrorMessage;
+      expect(error.message, 'the value is not a number');
+      expect(error.causes, ['invalid digit found in string']);
+    }
+  });
+
+  test('can pass and return typed data', () async {
+    final accounts = AccountsApi();
+    final samples = await accounts.typedDataArg(
+        pixels: Uint8List.fromList([1, 2, 3]),
+        samples: Float32List.fromList([0.5, 1.5]));
+    expect(samples, isA<Float32List>());
+    expect(samples, equals([3.5, 4.5]));
+    expect(accounts.typedDataSyncArg(pixels: Uint8List.fromList([1, 2, 3])),
+        equals(Uint8List.fromList([3, 2, 1])));
+  });
+
+  test('can pass and return fixed-size arrays', () async {
+    final accounts = AccountsApi();
+    expect(
+        await accounts.arrayArg(key: Uint8List.fromList([1, 2, 3, 4])),
+        equals(Uint8List.fromList([4, 3, 2, 1])));
+    expect(
+        await accounts.arrayArg(
+            key: Uint8List.fromList([1, 2, 3, 4]), ids: [5, 6]),
+        equals(Uint8List.fromList([11, 3, 2, 1])));
+    expect(() => accounts.arrayArg(key: Uint8List.fromList([1, 2, 3])),
+        throwsA(isA<MembraneArrayLengthException>()));
+  });
+
   test('can pass a tuple arg containing a vec of structs', () async {
     final accounts = AccountsApi();
     expect(
@@ -1,4 +1,6 @@
 fn main() {
+  membrane::build::hide_unprefixed_symbols();
+
   #[cfg(feature = "c-example")]
   {
     let headers = std::path::Path::new("./c/");
@@ -6,6 +6,8 @@ use tokio_stream::Stream;
 // used for background threading examples
 use std::{thread, time::Duration};
 
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
 use crate::data::{self, MoreTypes};
 
 #[async_dart(namespace = "accounts")] // also demonstrate fully qualified return types
@@ -189,51 +191,39 @@ pub async fn scalar_empty() -> Result<(), String> {
 }
 
 #[async_dart(namespace = "accounts")]
-pub async fn scalar_i8(val: i64) -> Result<i8, String> {
-  use std::convert::TryInto;
-
+pub async fn scalar_i8(val: i8) -> Result<i8, String> {
   assert!(val == 123);
-  Ok(val.try_into().unwrap())
+  Ok(val)
 }
 
 #[async_dart(namespace = "accounts")]
-pub async fn scalar_u8(val: i64) -> Result<u8, String> {
-  use std::convert::TryInto;
-
+pub async fn scalar_u8(val: u8) -> Result<u8, String> {
   assert!(val == 123);
-  Ok(val.try_into().unwrap())
+  Ok(val)
 }
 
 #[async_dart(namespace = "accounts")]
-pub async fn scalar_i16(val: i64) -> Result<i16, String> {
-  use std::convert::TryInto;
-
+pub async fn scalar_i16(val: i16) -> Result<i16, String> {
   assert!(val == 123);
-  Ok(val.try_into().unwrap())
+  Ok(val)
 }
 
 #[async_dart(namespace = "accounts")]
-pub async fn scalar_u16(val: i64) -> Result<u16, String> {
-  use std::convert::TryInto;
-
+pub async fn scalar_u16(val: u16) -> Result<u16, String> {
   assert!(val == 123);
-  Ok(val.try_into().unwrap())
+  Ok(val)
 }
 
 #[async_dart(namespace = "accounts")]
-pub async fn scalar_i32(val: i64) -> Result<i32, String> {
-  use std::convert::TryInto;
-
+pub async fn scalar_i32(val: i32) -> Result<i32, String> {
   assert!(val == 123);
-  Ok(val.try_into().unwrap())
+  Ok(val)
 }
 
 #[async_dart(namespace = "accounts")]
-pub async fn scalar_u32(val: i64) -> Result<u32, String> {
-  use std::convert::TryInto;
-
+pub async fn scalar_u32(val: u32) -> Result<u32, String> {
   assert!(val == 123);
-  Ok(val.try_into().unwrap())
+  Ok(val)
 }
 
 #[async_dart(namespace = "accounts")]
@@ -245,27 +235,27 @@ pub async fn scalar_i64(mut val: i64) -> Result<i64, String> {
 }
 
 #[async_dart(namespace = "accounts")]
-pub async fn scalar_u64(val: i64) -> Result<u64, String> {
+pub async fn scalar_u64(val: u64) -> Result<u64, String> {
   assert!(val == 10);
-  Ok(val as u64)
+  Ok(val)
 }
 
 #[async_dart(namespace = "accounts")]
-pub async fn scalar_i128(val: i64) -> Result<i128, String> {
+pub async fn scalar_i128(val: i128) -> Result<i128, String> {
   assert!(val == 10);
-  Ok(val as i128)
+  Ok(val)
 }
 
 #[async_dart(namespace = "accounts")]
-pub async fn scalar_u128(val: i64) -> Result<u128, String> {
+pub async fn scalar_u128(val: u128) -> Result<u128, String> {
   assert!(val == 10);
-  Ok(val as u128)
+  Ok(val)
 }
 
 #[async_dart(namespace = "accounts")]
-pub async fn scalar_f32(val: f64) -> Result<f32, String> {
+pub async fn scalar_f32(val: f32) -> Result<f32, String> {
   assert!(val == 21.1);
-  Ok(21.1)
+  Ok(val)
 }
 
 #[async_dart(namespace = "accounts")]
@@ -286,6 +276,52 @@ pub async fn scalar_bool(val: bool) -> Result<bool, String> {
   Ok(val)
 }
 
+#[async_dart(namespace = "accounts")]
+pub async fn scalar_char(val: char) -> Result<String, String> {
+  assert!(val == 'ダ');
+  Ok(val.to_string())
+}
+
+#[async_dart(namespace = "accounts")]
+pub async fn scalar_str(val: &str) -> Result<String, String> {
+  assert!(val == "hello world / ダミーテキスト");
+  Ok(val.to_string())
+}
+
+#[sync_dart(namespace = "accounts")]
+pub fn optional_scalars(
+  one: Option<u8>,
+  two: Option<u64>,
+  three: Option<f32>,
+  four: Option<char>,
+  five: Option<&str>,
+  six: Option<i128>,
+) -> Result<String, String> {
+  Ok(format!(
+    "{:?} {:?} {:?} {:?} {:?} {:?}",
+    one, two, three, four, five, six
+  ))
+}
+
+#[async_dart(namespace = "accounts")]
+pub async fn boxed_contact(
+  contact: Box<data::Contact>,
+  full_name: std::sync::Arc<str>,
+) -> Result<Box<data::Contact>, String> {
+  Ok(Box::new(data::Contact {
+    full_name: full_name.to_string(),
+    ..*contact
+  }))
+}
+
+#[async_dart(namespace = "accounts")]
+pub async fn transparent_id(
+  id: data::UserId,
+  manager: Option<data::UserId>,
+) -> Result<data::UserId, String> {
+  Ok(data::UserId(id.0 + manager.map_or(0, |manager| manager.0)))
+}
+
 #[async_dart(namespace = "accounts")]
 pub async fn scalar_error() -> Result<bool, String> {
   Err("an error message".to_string())
@@ -452,6 +488,68 @@ pub async fn optional_float_arg(value: Option<f64>) -> Result<Option<f64>, Strin
   Ok(value)
 }
 
+#[async_dart(namespace = "accounts")]
+pub async fn map_arg(
+  values: HashMap<String, Option<data::Contact>>,
+) -> Result<BTreeMap<String, Option<data::Contact>>, String> {
+  Ok(values.into_iter().collect())
+}
+
+#[async_dart(namespace = "accounts")]
+pub async fn set_arg(values: BTreeSet<i64>) -> Result<HashSet<i64>, String> {
+  Ok(values.into_iter().collect())
+}
+
+#[async_dart(namespace = "accounts")]
+pub async fn tuple_arg(
+  point: (i64, Option<String>),
+  contacts: Vec<(data::Contact, bool)>,
+) -> Result<(String, Vec<i64>), String> {
+  Ok((
+    format!("{} {:?}", point.0, point.1),
+    contacts
+      .into_iter()
+      .filter_map(|(contact, active)| active.then_some(contact.id))
+      .collect(),
+  ))
+}
+
+#[async_dart(namespace = "accounts")]
+pub async fn infallible_arg(value: i64) -> i64 {
+  value * 2
+}
+
+#[async_dart(namespace = "accounts")]
+pub async fn error_message_arg(value: String) -> anyhow::Result<i64> {
+  use anyhow::Context;
+  value.parse().context("the value is not a number")
+}
+
+#[async_dart(namespace = "accounts")]
+pub async fn typed_data_arg(pixels: Vec<u8>, samples: Vec<f32>) -> Result<Vec<f32>, String> {
+  Ok(
+    samples
+      .into_iter()
+      .map(|x| x + pixels.len() as f32)
+      .collect(),
+  )
+}
+
+#[sync_dart(namespace = "accounts")]
+pub fn typed_data_sync_arg(pixels: Vec<u8>) -> Result<Box<[u8]>, String> {
+  Ok(pixels.into_iter().rev().collect())
+}
+
+#[async_dart(namespace = "accounts")]
+pub async fn array_arg(key: [u8; 4], ids: Option<[i64; 2]>) -> Result<[u8; 4], String> {
+  let mut key = key;
+  key.reverse();
+  if let Some(ids) = ids {
+    key[0] = ids.iter().sum::<i64>() as u8;
+  }
+  Ok(key)
+}
+
 #[async_dart(namespace = "accounts")]
 pub async fn filter_arg(filter: data::Filter) -> Result<data::Contacts, String> {
   println!("\n[Rust] Received filter: {:?}", filter);
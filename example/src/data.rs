@@ -97,6 +97,11 @@ pub struct Contacts {
 #[derive(Deserialize, Serialize)]
 pub struct SyncContacts(pub Vec<Contact>);
 
+// passed to and from Dart as an `int`
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct UserId(pub i64);
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct VecWrapper {
   data: Vec<f64>,
@@ -0,0 +1,72 @@
+//! Helpers for the `build.rs` of a library which uses Membrane.
+use std::env;
+use std::path::PathBuf;
+
+// allo-isolate exports these without a prefix. Dart reaches `store_dart_post_cobject` through the prefixed
+// `membrane_store_dart_post_cobject` wrapper and the buffer finalizers are only ever called by pointer.
+// They're listed by name because rustc's own version script names them and an exact match wins over a glob.
+const UNPREFIXED_SYMBOLS: &[&str] = &[
+  "store_dart_post_cobject",
+  "free_zero_copy_buffer_u8",
+  "free_zero_copy_buffer_i8",
+  "free_zero_copy_buffer_u16",
+  "free_zero_copy_buffer_i16",
+  "free_zero_copy_buffer_u32",
+  "free_zero_copy_buffer_i32",
+  "free_zero_copy_buffer_u64",
+  "free_zero_copy_buffer_i64",
+  "free_zero_copy_buffer_f32",
+  "free_zero_copy_buffer_f64",
+];
+
+///
+/// Keep allo-isolate's unprefixed symbols out of the exports of a cdylib built with `MEMBRANE_SYMBOL_PREFIX`
+/// so that two prefixed libraries loaded into one app have no symbol in common. Call it from `build.rs`:
+///
+/// ```ignore
+/// fn main() {
+///   membrane::build::hide_unprefixed_symbols();
+/// }
+/// ```
+///
+/// Without a prefix the Dart loader uses allo-isolate's `store_dart_post_cobject` so nothing is hidden. Only
+/// cdylibs are affected, a `staticlib` keeps all of its symbols so two of them can't share one binary.
+pub fn hide_unprefixed_symbols() {
+  println!("cargo:rerun-if-env-changed=MEMBRANE_SYMBOL_PREFIX");
+  if env::var("MEMBRANE_SYMBOL_PREFIX")
+    .unwrap_or_default()
+    .is_empty()
+  {
+    return;
+  }
+
+  match env::var("CARGO_CFG_TARGET_VENDOR").as_deref() {
+    Ok("apple") => {
+      for symbol in UNPREFIXED_SYMBOLS {
+        println!(
+          "cargo:rustc-cdylib-link-arg=-Wl,-unexported_symbol,_{}",
+          symbol
+        );
+      }
+    }
+    // the symbols a Windows DLL exports are chosen by rustc
+    _ if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("windows") => {}
+    _ => {
+      // a second version script is merged with the one rustc passes to the linker
+      let path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("membrane_symbols.map");
+      let script = format!(
+        "{{\n  local:\n{}\n}};\n",
+        UNPREFIXED_SYMBOLS
+          .iter()
+          .map(|symbol| format!("    {};", symbol))
+          .collect::<Vec<String>>()
+          .join("\n")
+      );
+      std::fs::write(&path, script).unwrap();
+      println!(
+        "cargo:rustc-cdylib-link-arg=-Wl,--version-script={}",
+        path.display()
+      );
+    }
+  }
+}
@@ -0,0 +1,744 @@
+//! Detects changes between two API schemas which would break an already deployed Dart client.
+//!
+//! Bincode is positional so the wire format only stays compatible when fields keep their order
+//! and type, enum variants keep their index, and function signatures are left untouched. Renames
+//! and additions which an older client can never observe are reported as compatible.
+use crate::schema::{FunctionSchema, Schema};
+use membrane_types::rust::tuple_elements;
+use serde_reflection::{ContainerFormat, Format, Named, VariantFormat};
+use std::collections::BTreeSet;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compatibility {
+  Compatible,
+  Breaking,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+  pub namespace: String,
+  pub compatibility: Compatibility,
+  pub description: String,
+}
+
+impl fmt::Display for Change {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let label = match self.compatibility {
+      Compatibility::Compatible => "compatible",
+      Compatibility::Breaking => "breaking",
+    };
+    write!(f, "[{}] {}: {}", label, self.namespace, self.description)
+  }
+}
+
+struct Changes<'a> {
+  namespace: &'a str,
+  list: Vec<Change>,
+}
+
+impl Changes<'_> {
+  fn compatible(&mut self, description: String) {
+    self.push(Compatibility::Compatible, description);
+  }
+
+  fn breaking(&mut self, description: String) {
+    self.push(Compatibility::Breaking, description);
+  }
+
+  fn push(&mut self, compatibility: Compatibility, description: String) {
+    self.list.push(Change {
+      namespace: self.namespace.to_string(),
+      compatibility,
+      description,
+    });
+  }
+}
+
+///
+/// Compare a namespace's `current` schema against its `baseline`, returning every difference found.
+pub fn compare(baseline: &Schema, current: &Schema) -> Vec<Change> {
+  let mut changes = Changes {
+    namespace: &current.namespace,
+    list: vec![],
+  };
+
+  compare_functions(&baseline.functions, &current.functions, &mut changes);
+
+  for (name, old) in baseline.types.iter() {
+    match current.types.get(name) {
+      Some(new) => compare_container(name, old, new, &mut changes),
+      None => changes.compatible(format!("type `{}` is no longer used", name)),
+    }
+  }
+
+  for name in current.types.keys() {
+    if !baseline.types.contains_key(name) {
+      changes.compatible(format!("type `{}` was added", name));
+    }
+  }
+
+  changes.list
+}
+
+///
+/// Compare a namespace which exists in the baseline but which has been removed entirely.
+pub fn removed(baseline: &Schema) -> Vec<Change> {
+  vec![Change {
+    namespace: baseline.namespace.clone(),
+    compatibility: Compatibility::Breaking,
+    description: "namespace was removed".to_string(),
+  }]
+}
+
+///
+/// Describe a namespace which did not exist in the baseline.
+pub fn added(current: &Schema) -> Vec<Change> {
+  vec![Change {
+    namespace: current.namespace.clone(),
+    compatibility: Compatibility::Compatible,
+    description: "namespace was added".to_string(),
+  }]
+}
+
+fn compare_functions(
+  baseline: &[FunctionSchema],
+  current: &[FunctionSchema],
+  changes: &mut Changes,
+) {
+  for old in baseline {
+    let new = match current.iter().find(|f| f.name == old.name) {
+      Some(new) => new,
+      None => {
+        changes.breaking(format!("function `{}` was removed", old.name));
+        continue;
+      }
+    };
+
+    let name = &old.name;
+    let old_types = old.args.iter().map(|x| &x.ty).collect::<Vec<_>>();
+    let new_types = new.args.iter().map(|x| &x.ty).collect::<Vec<_>>();
+    if old_types != new_types {
+      changes.breaking(format!(
+        "function `{}` arguments changed from ({}) to ({})",
+        name,
+        display_args(old),
+        display_args(new)
+      ));
+    } else if old.args != new.args {
+      changes.compatible(format!(
+        "function `{}` arguments were renamed from ({}) to ({})",
+        name,
+        display_args(old),
+        display_args(new)
+      ));
+    }
+
+    if old.return_type != new.return_type {
+      changes.breaking(format!(
+        "function `{}` return type changed from `{}` to `{}`",
+        name,
+        display_types(&old.return_type),
+        display_types(&new.return_type)
+      ));
+    }
+
+    if old.error_type != new.error_type {
+      changes.breaking(format!(
+        "function `{}` error type changed from `{}` to `{}`",
+        name,
+        display_types(&old.error_type),
+        display_types(&new.error_type)
+      ));
+    }
+
+    if old.is_stream != new.is_stream || old.is_sync != new.is_sync {
+      changes.breaking(format!(
+        "function `{}` changed from {} to {}",
+        name,
+        display_style(old),
+        display_style(new)
+      ));
+    }
+
+    if old.timeout != new.timeout {
+      changes.compatible(format!(
+        "function `{}` timeout changed from {:?} to {:?}",
+        name, old.timeout, new.timeout
+      ));
+    }
+  }
+
+  for new in current {
+    if !baseline.iter().any(|f| f.name == new.name) {
+      changes.compatible(format!("function `{}` was added", new.name));
+    }
+  }
+}
+
+fn compare_container(
+  name: &str,
+  old: &ContainerFormat,
+  new: &ContainerFormat,
+  changes: &mut Changes,
+) {
+  use ContainerFormat::*;
+
+  match (old, new) {
+    (UnitStruct, UnitStruct) => {}
+    (NewTypeStruct(old), NewTypeStruct(new)) => {
+      if old != new {
+        changes.breaking(format!(
+          "type `{}` changed from `{}` to `{}`",
+          name,
+          display_format(old),
+          display_format(new)
+        ));
+      }
+    }
+    (TupleStruct(old), TupleStruct(new)) => {
+      compare_tuple(&format!("type `{}`", name), old, new, changes)
+    }
+    (Struct(old), Struct(new)) => compare_fields(&format!("type `{}`", name), old, new, changes),
+    (Enum(old), Enum(new)) => {
+      let old_names = old.values().map(|x| &x.name).collect::<Vec<_>>();
+      let new_names = new.values().map(|x| &x.name).collect::<Vec<_>>();
+
+      // variants are encoded by their index so a reordered variant is decoded as another one
+      if old_names != new_names
+        && old_names.iter().collect::<BTreeSet<_>>() == new_names.iter().collect::<BTreeSet<_>>()
+      {
+        changes.breaking(format!(
+          "type `{}` variants were reordered from ({}) to ({})",
+          name,
+          display_names(&old_names),
+          display_names(&new_names)
+        ));
+        return;
+      }
+
+      for (index, old_variant) in old.iter() {
+        let new_variant = match new.get(index) {
+          Some(new_variant) => new_variant,
+          None => {
+            changes.breaking(format!(
+              "variant `{}::{}` was removed",
+              name, old_variant.name
+            ));
+            continue;
+          }
+        };
+
+        let subject = format!("variant `{}::{}`", name, old_variant.name);
+        if old_variant.value == new_variant.value
+          && !new_names.contains(&&old_variant.name)
+          && !old_names.contains(&&new_variant.name)
+        {
+          changes.compatible(format!("{} was renamed to `{}`", subject, new_variant.name));
+          continue;
+        }
+
+        if old_variant.name != new_variant.name {
+          changes.breaking(format!(
+            "{} was replaced by `{}` at index {}",
+            subject, new_variant.name, index
+          ));
+          continue;
+        }
+
+        compare_variant(&subject, &old_variant.value, &new_variant.value, changes);
+      }
+
+      for (index, new_variant) in new.iter() {
+        if !old.contains_key(index) {
+          changes.compatible(format!(
+            "variant `{}::{}` was added",
+            name, new_variant.name
+          ));
+        }
+      }
+    }
+    (old, new) => changes.breaking(format!(
+      "type `{}` changed from {} to {}",
+      name,
+      display_container(old),
+      display_container(new)
+    )),
+  }
+}
+
+fn compare_variant(subject: &str, old: &VariantFormat, new: &VariantFormat, changes: &mut Changes) {
+  use VariantFormat::*;
+
+  match (old, new) {
+    (Unit, Unit) => {}
+    (NewType(old), NewType(new)) => {
+      if old != new {
+        changes.breaking(format!(
+          "{} changed from `{}` to `{}`",
+          subject,
+          display_format(old),
+          display_format(new)
+        ));
+      }
+    }
+    (Tuple(old), Tuple(new)) => compare_tuple(subject, old, new, changes),
+    (Struct(old), Struct(new)) => compare_fields(subject, old, new, changes),
+    _ => changes.breaking(format!("{} changed shape", subject)),
+  }
+}
+
+fn compare_tuple(subject: &str, old: &[Format], new: &[Format], changes: &mut Changes) {
+  if old != new {
+    changes.breaking(format!(
+      "{} changed from ({}) to ({})",
+      subject,
+      old
+        .iter()
+        .map(display_format)
+        .collect::<Vec<_>>()
+        .join(", "),
+      new
+        .iter()
+        .map(display_format)
+        .collect::<Vec<_>>()
+        .join(", ")
+    ));
+  }
+}
+
+fn compare_fields(
+  subject: &str,
+  old: &[Named<Format>],
+  new: &[Named<Format>],
+  changes: &mut Changes,
+) {
+  let old_names = old.iter().map(|x| &x.name).collect::<Vec<_>>();
+  let new_names = new.iter().map(|x| &x.name).collect::<Vec<_>>();
+
+  if old_names != new_names
+    && old_names.iter().collect::<BTreeSet<_>>() == new_names.iter().collect::<BTreeSet<_>>()
+  {
+    changes.breaking(format!(
+      "{} fields were reordered from ({}) to ({})",
+      subject,
+      display_names(&old_names),
+      display_names(&new_names)
+    ));
+    return;
+  }
+
+  // a field which keeps its position and type under a new name is still read correctly
+  let renamed = old
+    .iter()
+    .zip(new.iter())
+    .filter(|(old_field, new_field)| {
+      !new_names.contains(&&old_field.name)
+        && !old_names.contains(&&new_field.name)
+        && old_field.value == new_field.value
+    })
+    .collect::<Vec<_>>();
+
+  for (old_field, new_field) in renamed.iter() {
+    changes.compatible(format!(
+      "{} field `{}` was renamed to `{}`",
+      subject, old_field.name, new_field.name
+    ));
+  }
+
+  for field in old
+    .iter()
+    .filter(|x| !new_names.contains(&&x.name) && !renamed.iter().any(|(old, _)| old.name == x.name))
+  {
+    changes.breaking(format!("{} field `{}` was removed", subject, field.name));
+  }
+
+  for field in new
+    .iter()
+    .filter(|x| !old_names.contains(&&x.name) && !renamed.iter().any(|(_, new)| new.name == x.name))
+  {
+    changes.breaking(format!("{} field `{}` was added", subject, field.name));
+  }
+
+  for old_field in old {
+    if let Some(new_field) = new.iter().find(|x| x.name == old_field.name) {
+      if old_field.value != new_field.value {
+        changes.breaking(format!(
+          "{} field `{}` changed from `{}` to `{}`",
+          subject,
+          old_field.name,
+          display_format(&old_field.value),
+          display_format(&new_field.value)
+        ));
+      }
+    }
+  }
+}
+
+fn display_args(function: &FunctionSchema) -> String {
+  function
+    .args
+    .iter()
+    .map(|x| format!("{}: {}", x.name, display_types(&x.ty)))
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+fn display_types(types: &[String]) -> String {
+  match types {
+    [] => "()".to_string(),
+    [ty] => ty.clone(),
+    [map, key, rest @ ..] if map == "Map" => format!("Map<{}, {}>", key, display_types(rest)),
+    [array, len, rest @ ..] if array == "Array" => format!("[{}; {}]", display_types(rest), len),
+    [open, ..] if open == "(" => format!(
+      "({})",
+      tuple_elements(&types.iter().map(|x| x.as_str()).collect::<Vec<&str>>())
+        .iter()
+        .map(|x| display_types(&x.iter().map(|x| x.to_string()).collect::<Vec<String>>()))
+        .collect::<Vec<String>>()
+        .join(", ")
+    ),
+    [ty, rest @ ..] => format!("{}<{}>", ty, display_types(rest)),
+  }
+}
+
+fn display_style(function: &FunctionSchema) -> &'static str {
+  match (function.is_stream, function.is_sync) {
+    (true, _) => "a stream",
+    (false, true) => "sync",
+    (false, false) => "async",
+  }
+}
+
+fn display_names(names: &[&String]) -> String {
+  names
+    .iter()
+    .map(|x| x.as_str())
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+fn display_container(format: &ContainerFormat) -> &'static str {
+  match format {
+    ContainerFormat::UnitStruct => "a unit struct",
+    ContainerFormat::NewTypeStruct(_) => "a newtype struct",
+    ContainerFormat::TupleStruct(_) => "a tuple struct",
+    ContainerFormat::Struct(_) => "a struct",
+    ContainerFormat::Enum(_) => "an enum",
+  }
+}
+
+fn display_format(format: &Format) -> String {
+  match format {
+    Format::TypeName(name) => name.clone(),
+    Format::Option(inner) => format!("Option<{}>", display_format(inner)),
+    Format::Seq(inner) => format!("Vec<{}>", display_format(inner)),
+    Format::Map { key, value } => {
+      format!("Map<{}, {}>", display_format(key), display_format(value))
+    }
+    Format::Tuple(formats) => format!(
+      "({})",
+      formats
+        .iter()
+        .map(display_format)
+        .collect::<Vec<_>>()
+        .join(", ")
+    ),
+    Format::TupleArray { content, size } => format!("[{}; {}]", display_format(content), size),
+    other => format!("{:?}", other),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{compare, Compatibility};
+  use crate::schema::{ArgSchema, FunctionSchema, Schema, SCHEMA_VERSION};
+  use serde_reflection::{ContainerFormat, Format, Named, Registry, VariantFormat};
+  use std::collections::BTreeMap;
+
+  fn schema(functions: Vec<FunctionSchema>, types: Vec<(&str, ContainerFormat)>) -> Schema {
+    Schema {
+      version: SCHEMA_VERSION,
+      namespace: "accounts".to_string(),
+      functions,
+      types: types
+        .into_iter()
+        .map(|(name, format)| (name.to_string(), format))
+        .collect::<Registry>(),
+    }
+  }
+
+  fn function(args: Vec<(&str, &str)>, return_type: &str) -> FunctionSchema {
+    FunctionSchema {
+      name: "contact".to_string(),
+      args: args
+        .into_iter()
+        .map(|(name, ty)| ArgSchema {
+          name: name.to_string(),
+          ty: vec![ty.to_string()],
+        })
+        .collect(),
+      return_type: vec![return_type.to_string()],
+      error_type: vec!["String".to_string()],
+      is_stream: false,
+      is_sync: false,
+      timeout: None,
+    }
+  }
+
+  fn fields(fields: Vec<(&str, Format)>) -> ContainerFormat {
+    ContainerFormat::Struct(
+      fields
+        .into_iter()
+        .map(|(name, value)| Named {
+          name: name.to_string(),
+          value,
+        })
+        .collect(),
+    )
+  }
+
+  fn variants(variants: Vec<(u32, &str)>) -> ContainerFormat {
+    ContainerFormat::Enum(
+      variants
+        .into_iter()
+        .map(|(index, name)| {
+          (
+            index,
+            Named {
+              name: name.to_string(),
+              value: VariantFormat::Unit,
+            },
+          )
+        })
+        .collect::<BTreeMap<_, _>>(),
+    )
+  }
+
+  fn summary(baseline: &Schema, current: &Schema) -> Vec<(Compatibility, String)> {
+    compare(baseline, current)
+      .into_iter()
+      .map(|x| (x.compatibility, x.description))
+      .collect()
+  }
+
+  #[test]
+  fn test_identical_schemas_have_no_changes() {
+    let a = schema(
+      vec![function(vec![("id", "i64")], "Contact")],
+      vec![("Contact", fields(vec![("id", Format::I64)]))],
+    );
+    assert!(compare(&a, &a.clone()).is_empty());
+  }
+
+  #[test]
+  fn test_struct_field_changes() {
+    let baseline = schema(
+      vec![],
+      vec![(
+        "Contact",
+        fields(vec![("id", Format::I64), ("name", Format::Str)]),
+      )],
+    );
+
+    let reordered = schema(
+      vec![],
+      vec![(
+        "Contact",
+        fields(vec![("name", Format::Str), ("id", Format::I64)]),
+      )],
+    );
+    assert_eq!(
+      summary(&baseline, &reordered),
+      vec![(
+        Compatibility::Breaking,
+        "type `Contact` fields were reordered from (id, name) to (name, id)".to_string()
+      )]
+    );
+
+    let retyped = schema(
+      vec![],
+      vec![(
+        "Contact",
+        fields(vec![
+          ("id", Format::Str),
+          ("name", Format::Str),
+          ("age", Format::U8),
+        ]),
+      )],
+    );
+    assert_eq!(
+      summary(&baseline, &retyped),
+      vec![
+        (
+          Compatibility::Breaking,
+          "type `Contact` field `age` was added".to_string()
+        ),
+        (
+          Compatibility::Breaking,
+          "type `Contact` field `id` changed from `I64` to `Str`".to_string()
+        ),
+      ]
+    );
+
+    let renamed = schema(
+      vec![],
+      vec![(
+        "Contact",
+        fields(vec![("id", Format::I64), ("full_name", Format::Str)]),
+      )],
+    );
+    assert_eq!(
+      summary(&baseline, &renamed),
+      vec![(
+        Compatibility::Compatible,
+        "type `Contact` field `name` was renamed to `full_name`".to_string()
+      )]
+    );
+
+    // a new name with a new type can't be told apart from a replaced field
+    let replaced = schema(
+      vec![],
+      vec![(
+        "Contact",
+        fields(vec![("id", Format::I64), ("full_name", Format::U8)]),
+      )],
+    );
+    assert_eq!(
+      summary(&baseline, &replaced),
+      vec![
+        (
+          Compatibility::Breaking,
+          "type `Contact` field `name` was removed".to_string()
+        ),
+        (
+          Compatibility::Breaking,
+          "type `Contact` field `full_name` was added".to_string()
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_enum_variant_reorders() {
+    let baseline = schema(
+      vec![],
+      vec![("Status", variants(vec![(0, "Active"), (1, "Closed")]))],
+    );
+
+    let reordered = schema(
+      vec![],
+      vec![("Status", variants(vec![(0, "Closed"), (1, "Active")]))],
+    );
+    assert_eq!(
+      summary(&baseline, &reordered),
+      vec![(
+        Compatibility::Breaking,
+        "type `Status` variants were reordered from (Active, Closed) to (Closed, Active)"
+          .to_string()
+      )]
+    );
+
+    let renamed = schema(
+      vec![],
+      vec![("Status", variants(vec![(0, "Open"), (1, "Closed")]))],
+    );
+    assert_eq!(
+      summary(&baseline, &renamed),
+      vec![(
+        Compatibility::Compatible,
+        "variant `Status::Active` was renamed to `Open`".to_string()
+      )]
+    );
+
+    // an existing variant moved to another index is decoded as the wrong variant by old clients
+    let inserted = schema(
+      vec![],
+      vec![(
+        "Status",
+        variants(vec![(0, "Pending"), (1, "Active"), (2, "Closed")]),
+      )],
+    );
+    assert_eq!(
+      summary(&baseline, &inserted),
+      vec![
+        (
+          Compatibility::Breaking,
+          "variant `Status::Active` was replaced by `Pending` at index 0".to_string()
+        ),
+        (
+          Compatibility::Breaking,
+          "variant `Status::Closed` was replaced by `Active` at index 1".to_string()
+        ),
+        (
+          Compatibility::Compatible,
+          "variant `Status::Closed` was added".to_string()
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_enum_variant_changes() {
+    let baseline = schema(
+      vec![],
+      vec![("Status", variants(vec![(0, "Active"), (1, "Closed")]))],
+    );
+
+    let appended = schema(
+      vec![],
+      vec![(
+        "Status",
+        variants(vec![(0, "Active"), (1, "Closed"), (2, "Pending")]),
+      )],
+    );
+    assert_eq!(
+      summary(&baseline, &appended),
+      vec![(
+        Compatibility::Compatible,
+        "variant `Status::Pending` was added".to_string()
+      )]
+    );
+
+    let removed = schema(vec![], vec![("Status", variants(vec![(0, "Active")]))]);
+    assert_eq!(
+      summary(&baseline, &removed),
+      vec![(
+        Compatibility::Breaking,
+        "variant `Status::Closed` was removed".to_string()
+      )]
+    );
+  }
+
+  #[test]
+  fn test_function_changes() {
+    let baseline = schema(vec![function(vec![("id", "i64")], "Contact")], vec![]);
+
+    let renamed = schema(vec![function(vec![("user_id", "i64")], "Contact")], vec![]);
+    assert_eq!(summary(&baseline, &renamed)[0].0, Compatibility::Compatible);
+
+    let changed = schema(vec![function(vec![("id", "String")], "Account")], vec![]);
+    assert_eq!(
+      summary(&baseline, &changed),
+      vec![
+        (
+          Compatibility::Breaking,
+          "function `contact` arguments changed from (id: i64) to (id: String)".to_string()
+        ),
+        (
+          Compatibility::Breaking,
+          "function `contact` return type changed from `Contact` to `Account`".to_string()
+        ),
+      ]
+    );
+
+    let removed = schema(vec![], vec![]);
+    assert_eq!(
+      summary(&baseline, &removed),
+      vec![(
+        Compatibility::Breaking,
+        "function `contact` was removed".to_string()
+      )]
+    );
+  }
+}
@@ -242,7 +242,7 @@ mod emitter_impl {
     )
   }
 
-  #[no_mangle]
+  #[membrane_macro::prefixed_symbol]
   pub extern "C" fn membrane_drop_handle(data: *mut std::ffi::c_void) {
     unsafe {
       if data.is_null() {
@@ -0,0 +1,356 @@
+//! Checks that traced types survive the trip through bincode to Dart.
+//!
+//! The Dart code decodes exactly the format which `serde_reflection` records while deserializing a
+//! type. Serde attributes that need a self-describing format (`untagged`, `flatten`, and internally or
+//! adjacently tagged enums) can't be traced at all, while `skip_serializing_if` or a `Serialize` impl
+//! which doesn't match its `Deserialize` impl trace fine but write bytes that Dart would decode as
+//! garbage. These functions are called by the code which `#[async_dart]`, `#[sync_dart]`, and
+//! `#[dart_enum]` generate.
+use crate::{shared, DeferredSample};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_reflection::{
+  ContainerFormat, Error, Format, FormatHolder, Registry, Samples, Tracer, TracerConfig,
+  VariantFormat,
+};
+
+// recursive types are encoded until this depth before the variant is given up on
+const MAX_DEPTH: usize = 32;
+
+pub fn trace_type<'de, T>(tracer: &mut Tracer, samples: &'de Samples) -> Result<Format, String>
+where
+  T: Deserialize<'de>,
+{
+  tracer
+    .trace_type::<T>(samples)
+    .map(|(format, _values)| format)
+    .map_err(trace_error::<T>)
+}
+
+///
+/// Record a value from a `#[dart_sample]` function so that its type can be traced.
+pub fn trace_sample<T>(tracer: &mut Tracer, samples: &mut Samples, value: &T) -> Result<(), String>
+where
+  T: Serialize,
+{
+  tracer
+    .trace_value(samples, value)
+    .map(|_| ())
+    .map_err(|err| {
+      format!(
+        "The sample of `{}` could not be traced: {}",
+        std::any::type_name::<T>(),
+        err
+      )
+    })
+}
+
+///
+/// Serialize the traced values of `T`, along with the smallest value of each enum variant, and check
+/// that they are written exactly as they are read.
+pub fn check_round_trip<T>() -> Result<(), String>
+where
+  T: Serialize + DeserializeOwned,
+{
+  // the samples of the library which is being traced, any errors were reported when they were recorded
+  let mut samples = Samples::new();
+  let (mut tracer, _errors) = sampled_tracer(&crate::metadata::samples(), &mut samples);
+  let (format, values) = tracer.trace_type::<T>(&samples).map_err(trace_error::<T>)?;
+
+  // tracing the serialization of a value fails if its format differs from the deserialized format
+  let mut serialized = Samples::new();
+  for value in values.iter() {
+    tracer
+      .trace_value(&mut serialized, value)
+      .map_err(|_| round_trip_error::<T>())?;
+  }
+  // nested enums which were only partly explored are completed by `#[dart_enum]`, the variants seen
+  // so far are enough to check with
+  let registry = tracer.registry_unchecked();
+
+  for value in values.iter() {
+    let bytes = bincode::serialize(value).map_err(|_| round_trip_error::<T>())?;
+    let decoded = bincode::deserialize::<T>(&bytes).map_err(|_| round_trip_error::<T>())?;
+    if bincode::serialize(&decoded).ok() != Some(bytes) {
+      return Err(round_trip_error::<T>());
+    }
+  }
+
+  for bytes in smallest_encodings(&format, &registry) {
+    // a `Deserialize` impl is free to reject a value, only what it accepts has to round trip
+    if let Ok(value) = bincode::deserialize::<T>(&bytes) {
+      if bincode::serialize(&value).ok() != Some(bytes) {
+        return Err(round_trip_error::<T>());
+      }
+    }
+  }
+
+  Ok(())
+}
+
+///
+/// A tracer which has recorded the values of the `#[dart_sample]` functions. serde-reflection only
+/// uses a sample during deserialization when the tracer already knows the sample's format.
+pub(crate) fn sampled_tracer(
+  deferred: &[&DeferredSample],
+  samples: &mut Samples,
+) -> (Tracer, Vec<String>) {
+  let mut tracer = Tracer::new(TracerConfig::default().record_samples_for_structs(true));
+  let errors = deferred
+    .iter()
+    .filter_map(|sample| {
+      (sample.trace)(&mut tracer, samples).err().map(|err| {
+        format!(
+          "{} It is the sample returned{}.",
+          err,
+          crate::utils::display_code_location(&[sample.location])
+        )
+      })
+    })
+    .collect();
+
+  (tracer, errors)
+}
+
+///
+/// The part of a registry which is reachable from the traced functions, enums, and types. A sampled type is
+/// recorded in every registry, whether or not the namespace uses it.
+pub(crate) fn used_types(mut registry: Registry, roots: &[Format]) -> Registry {
+  let mut pending = vec![];
+  roots
+    .iter()
+    .for_each(|root| shared::format_references(root, &mut pending));
+
+  let mut used = Registry::new();
+  while let Some(name) = pending.pop() {
+    if let Some(format) = registry.remove(&name) {
+      pending.extend(shared::references(&format));
+      used.insert(name, format);
+    }
+  }
+
+  used
+}
+
+///
+/// The enums of a registry which are still missing variants. serde-reflection keeps each variant it has
+/// only deserialized by name under a provisional index past the real ones, until it has also been
+/// deserialized by index.
+pub(crate) fn incomplete_enums(registry: &Registry) -> Vec<String> {
+  registry
+    .iter()
+    .filter_map(|(name, format)| match format {
+      ContainerFormat::Enum(variants)
+        if variants
+          .keys()
+          .any(|index| *index as usize >= variants.len()) =>
+      {
+        Some(name.clone())
+      }
+      _ => None,
+    })
+    .collect()
+}
+
+///
+/// The checks of `Tracer::registry` for a registry which was taken with `Tracer::registry_unchecked`.
+pub(crate) fn check_registry(mut registry: Registry) -> serde_reflection::Result<Registry> {
+  for (name, format) in registry.iter_mut() {
+    format
+      .normalize()
+      .map_err(|_| Error::UnknownFormatInContainer(name.clone()))?;
+  }
+
+  match incomplete_enums(&registry) {
+    names if names.is_empty() => Ok(registry),
+    names => Err(Error::MissingVariants(names)),
+  }
+}
+
+fn trace_error<T>(err: Error) -> String {
+  match err {
+    Error::NotSupported(method) => format!(
+      "`{}` can't be used from Dart because deserializing it calls `{}`, which needs a self-describing format. Bincode only encodes data and not its structure so `#[serde(untagged)]`, `#[serde(flatten)]`, and internally or adjacently tagged enums (`#[serde(tag = \"...\")]`) aren't supported. Remove the attribute or use a separate type for Dart.",
+      std::any::type_name::<T>(),
+      method
+    ),
+    Error::Custom(_) | Error::DeserializationError(_) => format!(
+      "`{}` could not be traced because its `Deserialize` impl rejected a value: {}. Return a valid value from a function marked with `#[dart_sample]` so that it can be traced.",
+      std::any::type_name::<T>(),
+      err
+    ),
+    err => format!(
+      "`{}` could not be traced: {}{}",
+      std::any::type_name::<T>(),
+      err,
+      err.explanation()
+    ),
+  }
+}
+
+fn round_trip_error<T>() -> String {
+  format!(
+    "`{}` doesn't serialize to the same bincode that it deserializes from, so Dart would decode garbage. This is usually caused by `#[serde(skip_serializing_if = \"...\")]` or `#[serde(skip_serializing)]` on a field, or by a custom `Serialize` impl which doesn't match its `Deserialize` impl.",
+    std::any::type_name::<T>()
+  )
+}
+
+///
+/// The bincode of the smallest value of a format, once with the first variant of every enum and then
+/// once for each other variant of each enum.
+fn smallest_encodings(format: &Format, registry: &Registry) -> Vec<Vec<u8>> {
+  let mut choices = vec![None];
+  for (name, container) in registry.iter() {
+    if let ContainerFormat::Enum(variants) = container {
+      choices.extend(variants.keys().map(|index| Some((name.as_str(), *index))));
+    }
+  }
+
+  choices
+    .into_iter()
+    .filter_map(|choice| {
+      let mut bytes = vec![];
+      encode(format, registry, choice, 0, &mut bytes).then_some(bytes)
+    })
+    .collect()
+}
+
+fn encode(
+  format: &Format,
+  registry: &Registry,
+  choice: Option<(&str, u32)>,
+  depth: usize,
+  bytes: &mut Vec<u8>,
+) -> bool {
+  if depth > MAX_DEPTH {
+    return false;
+  }
+
+  let width = match format {
+    Format::Variable(_) => return false,
+    Format::TypeName(name) => {
+      return match registry.get(name) {
+        Some(container) => encode_container(name, container, registry, choice, depth + 1, bytes),
+        None => false,
+      }
+    }
+    Format::Tuple(formats) => {
+      return formats
+        .iter()
+        .all(|x| encode(x, registry, choice, depth + 1, bytes))
+    }
+    Format::TupleArray { content, size } => {
+      return (0..*size).all(|_| encode(content, registry, choice, depth + 1, bytes))
+    }
+    Format::Unit => 0,
+    // `None`, `false`, and the one byte UTF-8 encoding of `\0`
+    Format::Bool | Format::I8 | Format::U8 | Format::Char | Format::Option(_) => 1,
+    Format::I16 | Format::U16 => 2,
+    Format::I32 | Format::U32 | Format::F32 => 4,
+    Format::I64 | Format::U64 | Format::F64 => 8,
+    Format::I128 | Format::U128 => 16,
+    // a zero length
+    Format::Str | Format::Bytes | Format::Seq(_) | Format::Map { .. } => 8,
+  };
+
+  bytes.resize(bytes.len() + width, 0);
+  true
+}
+
+fn encode_container(
+  name: &str,
+  container: &ContainerFormat,
+  registry: &Registry,
+  choice: Option<(&str, u32)>,
+  depth: usize,
+  bytes: &mut Vec<u8>,
+) -> bool {
+  let all = |formats: Vec<&Format>, bytes: &mut Vec<u8>| {
+    formats
+      .into_iter()
+      .all(|x| encode(x, registry, choice, depth, bytes))
+  };
+
+  match container {
+    ContainerFormat::UnitStruct => true,
+    ContainerFormat::NewTypeStruct(format) => encode(format, registry, choice, depth, bytes),
+    ContainerFormat::TupleStruct(formats) => all(formats.iter().collect(), bytes),
+    ContainerFormat::Struct(fields) => all(fields.iter().map(|x| &x.value).collect(), bytes),
+    ContainerFormat::Enum(variants) => variants
+      .iter()
+      .filter(|(index, _)| match choice {
+        Some((chosen, variant)) if chosen == name => **index == variant,
+        _ => true,
+      })
+      .any(|(index, variant)| {
+        let mut encoded = index.to_le_bytes().to_vec();
+        let complete = match &variant.value {
+          VariantFormat::Variable(_) => false,
+          VariantFormat::Unit => true,
+          VariantFormat::NewType(format) => encode(format, registry, choice, depth, &mut encoded),
+          VariantFormat::Tuple(formats) => all(formats.iter().collect(), &mut encoded),
+          VariantFormat::Struct(fields) => {
+            all(fields.iter().map(|x| &x.value).collect(), &mut encoded)
+          }
+        };
+        if complete {
+          bytes.extend(encoded);
+        }
+        complete
+      }),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::check_round_trip;
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Serialize, Deserialize)]
+  struct Contact {
+    id: i64,
+    name: Option<String>,
+    status: Status,
+  }
+
+  #[derive(Serialize, Deserialize)]
+  enum Status {
+    Pending,
+    Active { since: i64 },
+  }
+
+  #[derive(Serialize, Deserialize)]
+  struct SkippedContact {
+    id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+  }
+
+  #[derive(Serialize, Deserialize)]
+  enum SkippedStatus {
+    Pending,
+    Active {
+      #[serde(skip_serializing_if = "Vec::is_empty")]
+      tags: Vec<String>,
+    },
+  }
+
+  #[derive(Serialize, Deserialize)]
+  #[serde(tag = "type")]
+  enum Tagged {
+    Pending,
+  }
+
+  #[test]
+  fn test_check_round_trip() {
+    assert_eq!(check_round_trip::<Contact>(), Ok(()));
+    assert_eq!(check_round_trip::<Vec<Contact>>(), Ok(()));
+
+    assert!(check_round_trip::<SkippedContact>()
+      .unwrap_err()
+      .contains("doesn't serialize to the same bincode"));
+    assert!(check_round_trip::<SkippedStatus>().is_err());
+    assert!(check_round_trip::<Tagged>()
+      .unwrap_err()
+      .contains("needs a self-describing format"));
+  }
+}
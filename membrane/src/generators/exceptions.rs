@@ -30,6 +30,30 @@ class MembraneRustPanicException extends MembraneException {
 class MembraneUnknownResponseVariantException extends MembraneException {
   const MembraneUnknownResponseVariantException([String? message]) : super(message);
 }
+
+class MembraneLibraryLoadException extends MembraneException {
+  /// The library paths which were attempted, in order.
+  final List<String> attempted;
+
+  const MembraneLibraryLoadException([String? message, this.attempted = const []]) : super(message);
+}
+
+class MembraneArrayLengthException extends MembraneException {
+  /// The length of the Rust array.
+  final int expected;
+
+  /// The length of the list which was passed.
+  final int actual;
+
+  const MembraneArrayLengthException([String? message, this.expected = 0, this.actual = 0]) : super(message);
+}
+
+class MembraneVersionMismatchException extends MembraneException {
+  /// The namespaces whose schema differs between the Dart package and the native library.
+  final List<String> namespaces;
+
+  const MembraneVersionMismatchException([String? message, this.namespaces = const []]) : super(message);
+}
 "#
   .to_string()
 }
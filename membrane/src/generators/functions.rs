@@ -1,5 +1,6 @@
 use crate::{ContainerFormat, Function, Membrane, Registry, VariantFormat};
-use membrane_types::{dart::dart_type, heck::ToUpperCamelCase};
+use membrane_types::dart::{dart_type, typed_data};
+use membrane_types::{heck::ToUpperCamelCase, rust::tuple_elements};
 use std::io::Write;
 
 ///
@@ -19,6 +20,7 @@ pub(crate) struct Web {
 pub(crate) struct C {
   output: String,
   fun: Function,
+  symbol_prefix: String,
 }
 
 ///
@@ -91,6 +93,7 @@ impl Builder for C {
     Self {
       output: "".to_string(),
       fun: input.clone(),
+      symbol_prefix: "".to_string(),
     }
   }
 
@@ -98,10 +101,13 @@ impl Builder for C {
     self.output.as_bytes()
   }
 
-  fn build(&mut self, _config: &Membrane) -> C {
+  fn build(&mut self, config: &Membrane) -> C {
+    self.symbol_prefix = config.symbol_prefix.clone();
+
     C {
       output: self.begin().signature().output.clone(),
       fun: self.fun.clone(),
+      symbol_prefix: self.symbol_prefix.clone(),
     }
   }
 }
@@ -139,9 +145,9 @@ impl Function {
         "Future"
       },
       return_type = if self.is_sync {
-        dart_type(self.return_type)
+        self.dart_return_type()
       } else {
-        format!("<{}>", dart_type(self.return_type))
+        format!("<{}>", self.dart_return_type())
       },
       fn_name = self.fn_name,
       fn_params = if self.dart_outer_params.is_empty() {
@@ -163,6 +169,65 @@ impl Function {
     "\n  }\n".to_string()
   }
 
+  fn dart_return_type(&self) -> String {
+    match typed_data(self.return_type) {
+      Some(list) => list.to_string(),
+      None => dart_type(self.return_type),
+    }
+  }
+
+  ///
+  /// Deserialize the return value, typed data is usually passed on its own but it's deserialized when it
+  /// comes from an emitter or from a type which serializes as a `Vec`.
+  fn return_deserializer(&self, enum_tracer_registry: &Registry, config: &Membrane) -> String {
+    let de = self.deserializer(self.return_type, enum_tracer_registry, config);
+    match typed_data(self.return_type) {
+      Some(list) => format!("{}.fromList({})", list, de.trim_end_matches(';')),
+      None => de,
+    }
+  }
+
+  ///
+  /// Throw the error that Rust returned, an infallible function only sends an error when generated Dart and
+  /// Rust code are mismatched.
+  fn throw_error(&self, enum_tracer_registry: &Registry, config: &Membrane) -> String {
+    match self.error_type {
+      [] => format!(
+        "throw MembraneUnknownResponseVariantException('Received an error from `{fn_name}` which is infallible, is generated Dart code and Rust code mismatched?');",
+        fn_name = self.fn_name
+      ),
+      error_type => format!(
+        "throw {class_name}ApiError({error_de});",
+        class_name = self.namespace.to_upper_camel_case(),
+        error_de = self.deserializer(error_type, enum_tracer_registry, config)
+      ),
+    }
+  }
+
+  ///
+  /// Return typed data which Rust sent without serializing it.
+  fn typed_return(&self) -> String {
+    match (self.is_sync, typed_data(self.return_type)) {
+      // the message kind and the length of the `Vec` come before its elements, which are copied because the
+      // buffer is freed once the sync call returns
+      (true, Some(list)) => format!(
+        r#"
+      if (data[8] == MembraneMsgKind.ok) {{
+        return data.asTypedList(length + 8).sublist(17).buffer.as{list}();
+      }}"#,
+        list = list
+      ),
+      (false, Some(list)) => format!(
+        r#"
+      if (input is! TypedData) {{
+        return (input as List).first as {list};
+      }}"#,
+        list = list
+      ),
+      (_, None) => String::new(),
+    }
+  }
+
   #[allow(clippy::only_used_in_recursion)]
   fn deserializer(
     &self,
@@ -208,6 +273,75 @@ impl Function {
         );
         &de
       }
+      ["Map", key, ..] => {
+        de = format!(
+          "() {{
+            final length = deserializer.deserializeLength();
+            final map = <{key_type}, {value_type}>{{}};
+            for (var i = 0; i < length; i++) {{
+              final key = {key};
+              final value = {value};
+              map[key] = value;
+            }}
+            return map;
+          }}()",
+          key_type = dart_type(&[key]),
+          value_type = dart_type(&ty[2..]),
+          key = self.deserializer(&[key], enum_tracer_registry, config),
+          value = self.deserializer(&ty[2..], enum_tracer_registry, config)
+        );
+        &de
+      }
+      ["Set", ..] => {
+        de = format!(
+          "() {{
+            final length = deserializer.deserializeLength();
+            final set = <{value_type}>{{}};
+            for (var i = 0; i < length; i++) {{
+              final value = {value};
+              set.add(value);
+            }}
+            return set;
+          }}()",
+          value_type = dart_type(&ty[1..]),
+          value = self.deserializer(&ty[1..], enum_tracer_registry, config)
+        );
+        &de
+      }
+      // bincode reads an array like a tuple, without its length
+      ["Array", len, ..] => {
+        let elements = format!(
+          "List.generate({len}, (_i) {{
+            return {};
+          }})",
+          self
+            .deserializer(&ty[2..], enum_tracer_registry, config)
+            .trim_end_matches(';'),
+          len = len
+        );
+        de = match ty[2..] {
+          ["u8"] => format!("Uint8List.fromList({})", elements),
+          _ => elements,
+        };
+        &de
+      }
+      ["(", ..] => {
+        // the fields of a record literal are evaluated in order
+        let fields = tuple_elements(ty)
+          .iter()
+          .map(|x| {
+            self
+              .deserializer(x, enum_tracer_registry, config)
+              .trim_end_matches(';')
+              .to_string()
+          })
+          .collect::<Vec<String>>();
+        de = match &fields[..] {
+          [field] => format!("({},)", field),
+          fields => format!("({})", fields.join(", ")),
+        };
+        &de
+      }
       ["Option", ..] => {
         de = format!(
           "() {{
@@ -333,20 +467,20 @@ impl Callable for Ffi {
     try {{
       if (!_loggingDisabled) {{
         _log.{fine_logger}('Deserializing data from {fn_name}');
-      }}
+      }}{typed_return}
       final deserializer = BincodeDeserializer(data.asTypedList(length + 8).sublist(8));
       if (deserializer.deserializeUint8() == MembraneMsgKind.ok) {{
         return {return_de};
       }}
-      throw {class_name}ApiError({error_de});
+      {throw_error}
     }} finally {{
       if (_taskResult.kind == MembraneResponseKind.data && _bindings.membrane_free_membrane_vec(length + 8, _taskResult.data) < 1) {{
         throw MembraneMemoryFreeFailedException('Resource freeing call to C failed');
       }}
     }}"#,
-        return_de = self.fun.deserializer(self.fun.return_type, enum_tracer_registry, config),
-        error_de = self.fun.deserializer(self.fun.error_type, enum_tracer_registry, config),
-        class_name = self.fun.namespace.to_upper_camel_case(),
+        return_de = self.fun.return_deserializer(enum_tracer_registry, config),
+        typed_return = self.fun.typed_return(),
+        throw_error = self.fun.throw_error(enum_tracer_registry, config),
         fn_name = self.fun.fn_name,
         fine_logger = config.dart_config.logger.fine_log_fn
       )
@@ -357,21 +491,21 @@ impl Callable for Ffi {
       yield* _port{timeout}.map((input) {{
         if (!_loggingDisabled) {{
           _log.{fine_logger}('Deserializing data from {fn_name}');
-        }}
+        }}{typed_return}
         final deserializer = BincodeDeserializer(input as Uint8List);
         if (deserializer.deserializeUint8() == MembraneMsgKind.ok) {{
           return {return_de};
         }}
-        throw {class_name}ApiError({error_de});
+        {throw_error}
       }});
     }} finally {{
       if (_taskResult.kind == MembraneResponseKind.data && _bindings.membrane_cancel_membrane_task(_taskResult.data) < 1) {{
         throw MembraneCancellationFailedException('Cancellation call to C failed');
       }}
     }}"#,
-        return_de = self.fun.deserializer(self.fun.return_type, enum_tracer_registry, config),
-        error_de = self.fun.deserializer(self.fun.error_type, enum_tracer_registry, config),
-        class_name = self.fun.namespace.to_upper_camel_case(),
+        return_de = self.fun.return_deserializer(enum_tracer_registry, config),
+        typed_return = self.fun.typed_return(),
+        throw_error = self.fun.throw_error(enum_tracer_registry, config),
         fn_name = self.fun.fn_name,
         timeout = if let Some(val) = self.fun.timeout {
           // check the async_dart option configured timeout
@@ -390,19 +524,20 @@ impl Callable for Ffi {
       if (!_loggingDisabled) {{
         _log.{fine_logger}('Deserializing data from {fn_name}');
       }}
-      final deserializer = BincodeDeserializer(await _port.first{timeout} as Uint8List);
+      final input = await _port.first{timeout};{typed_return}
+      final deserializer = BincodeDeserializer(input as Uint8List);
       if (deserializer.deserializeUint8() == MembraneMsgKind.ok) {{
         return {return_de};
       }}
-      throw {class_name}ApiError({error_de});
+      {throw_error}
     }} finally {{
       if (_taskResult.kind == MembraneResponseKind.data && _bindings.membrane_cancel_membrane_task(_taskResult.data) < 1) {{
         throw MembraneCancellationFailedException('Cancellation call to C failed');
       }}
     }}"#,
-        return_de = self.fun.deserializer(self.fun.return_type, enum_tracer_registry, config),
-        error_de = self.fun.deserializer(self.fun.error_type, enum_tracer_registry, config),
-        class_name = self.fun.namespace.to_upper_camel_case(),
+        return_de = self.fun.return_deserializer(enum_tracer_registry, config),
+        typed_return = self.fun.typed_return(),
+        throw_error = self.fun.throw_error(enum_tracer_registry, config),
         fn_name = self.fun.fn_name,
         timeout = if let Some(val) = self.fun.timeout {
           // if #[async_dart(timeout = false)] is set then it will be represented
@@ -466,7 +601,8 @@ impl Callable for C {
 
   fn signature(&mut self) -> &mut Self {
     self.output += format!(
-      "MembraneResponse {extern_c_fn_name}({port}{extern_c_fn_types});",
+      "MembraneResponse {prefix}{extern_c_fn_name}({port}{extern_c_fn_types});",
+      prefix = self.symbol_prefix,
       extern_c_fn_name = self.fun.extern_c_fn_name,
       port = if self.fun.is_sync { "" } else { "int64_t port" },
       extern_c_fn_types = if self.fun.extern_c_fn_types.is_empty() {
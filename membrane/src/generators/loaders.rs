@@ -1,37 +1,136 @@
-pub fn create_ffi_loader(library: &str, dart_config: &crate::DartConfig) -> String {
+use std::collections::BTreeMap;
+
+pub fn create_ffi_loader(
+  library: &str,
+  search_paths: &[String],
+  schema_hashes: &BTreeMap<&str, Vec<(&str, String)>>,
+  signature_hashes: &BTreeMap<&str, Vec<(&str, String)>>,
+  symbol_prefix: &str,
+  dart_config: &crate::DartConfig,
+) -> String {
   format!(
     r#"// AUTO GENERATED FILE, DO NOT EDIT
 //
 // Generated by `membrane`
 import 'dart:ffi';
-import 'dart:io' show Platform;
+import 'dart:io' show File, FileSystemEntity, Platform;
 import 'package:ffi/ffi.dart';
 import '{logger_path}';
 
 import './ffi_bindings.dart' as ffi_bindings;
+import './membrane_exceptions.dart';
+
+/// Selects how the native library is located at runtime.
+enum MembraneLookup {{
+  /// Open the library file by path, the default on all platforms except iOS.
+  open,
+
+  /// Look up symbols in the running process, for executables which link the library statically.
+  process,
+
+  /// Look up symbols in the running executable, the default on iOS.
+  executable,
+}}
+
+class Membrane {{
+  static String? _path;
+  static MembraneLookup? _lookup;
+  static List<String> _searchPaths = const [{search_paths}];
+
+  /// Configures how the native library is found and then loads it immediately so that
+  /// a failure is reported here rather than from the first API call.
+  ///
+  /// * `path`, the full path to the library (or to the directory containing it).
+  ///   Takes precedence over the `MEMBRANE_LIB_PATH` environment variable.
+  /// * `lookup`, use `MembraneLookup.process` for statically linked executables.
+  /// * `searchPaths`, directories to try in order before falling back to the platform's default search.
+  static void initialize({{
+    String? path,
+    MembraneLookup? lookup,
+    List<String>? searchPaths,
+  }}) {{
+    if (bindingsLoaded) {{
+      throw StateError(
+          'Membrane.initialize() must be called before the native library is loaded.');
+    }}
+    _path = path;
+    _lookup = lookup;
+    if (searchPaths != null) {{
+      _searchPaths = searchPaths;
+    }}
+    for (final library in _schemaHashes.keys) {{
+      libraryBindings(library);
+    }}
+  }}
+}}
+
+String _fileName(String library) {{
+  if (Platform.isMacOS || Platform.isIOS) {{
+    return '$library.dylib';
+  }}
+  if (Platform.isWindows) {{
+    return '$library.dll';
+  }}
+  return '$library.so';
+}}
 
-DynamicLibrary _open() {{
-  if (Platform.isLinux) {{
-    {logger}.{info_logger}('Opening native library {lib}.so');
-    return DynamicLibrary.open('{lib}.so');
+// When several libraries are loaded a `path` naming one library's file is used to locate the
+// directory which holds the others.
+String _resolve(String path, String library) {{
+  if (FileSystemEntity.isDirectorySync(path)) {{
+    return path + Platform.pathSeparator + _fileName(library);
   }}
-  if (Platform.isAndroid) {{
-    {logger}.{info_logger}('Opening native library {lib}.so');
-    return DynamicLibrary.open('{lib}.so');
+  if (library == _defaultLibrary) {{
+    return path;
   }}
-  if (Platform.isIOS) {{
-    {logger}.{info_logger}('Creating dynamic library {lib}');
+  return File(path).parent.path + Platform.pathSeparator + _fileName(library);
+}}
+
+DynamicLibrary _open(String library) {{
+  final lookup = Membrane._lookup ??
+      (Platform.isIOS ? MembraneLookup.executable : MembraneLookup.open);
+
+  if (lookup == MembraneLookup.process) {{
+    {logger}.{info_logger}('Using the process symbols for native library $library');
+    return DynamicLibrary.process();
+  }}
+  if (lookup == MembraneLookup.executable) {{
+    {logger}.{info_logger}('Creating dynamic library $library');
     return DynamicLibrary.executable();
   }}
-  if (Platform.isMacOS) {{
-    {logger}.{info_logger}('Opening native library {lib}.dylib');
-    return DynamicLibrary.open('{lib}.dylib');
+  if (!(Platform.isLinux ||
+      Platform.isAndroid ||
+      Platform.isMacOS ||
+      Platform.isWindows)) {{
+    throw UnsupportedError('This platform is not supported.');
   }}
-  if (Platform.isWindows) {{
-    {logger}.{info_logger}('Opening native library {lib}.dll');
-    return DynamicLibrary.open('{lib}.dll');
+
+  final envPath = Platform.environment['MEMBRANE_LIB_PATH'];
+  final candidates = <String>[
+    if (Membrane._path != null)
+      _resolve(Membrane._path!, library)
+    else if (envPath != null && envPath.isNotEmpty)
+      _resolve(envPath, library)
+    else ...[
+      for (final dir in Membrane._searchPaths)
+        dir + Platform.pathSeparator + _fileName(library),
+      _fileName(library),
+    ],
+  ];
+
+  final failures = <String>[];
+  for (final candidate in candidates) {{
+    try {{
+      {logger}.{info_logger}('Opening native library $candidate');
+      return DynamicLibrary.open(candidate);
+    }} on ArgumentError catch (err) {{
+      failures.add('$candidate: ${{err.message}}');
+    }}
   }}
-  throw UnsupportedError('This platform is not supported.');
+
+  throw MembraneLibraryLoadException(
+      "Unable to load native library '$library'. Tried:\n  ${{failures.join('\n  ')}}",
+      candidates);
 }}
 
 typedef _StoreDartPostCobjectC = Void Function(
@@ -41,14 +140,20 @@ typedef _StoreDartPostCobjectDart = void Function(
   Pointer<NativeFunction<Int8 Function(Int64, Pointer<Dart_CObject>)>> ptr,
 );
 
-_load() {{
-  final dl = _open();
+ffi_bindings.NativeLibrary _load(String library) {{
+  final dl = _open(library);
   {logger}.{info_logger}('Initializing FFI bindings');
   final bindings = ffi_bindings.NativeLibrary(dl);
-  final storeDartPostCobject =
-      dl.lookupFunction<_StoreDartPostCobjectC, _StoreDartPostCobjectDart>(
-    'store_dart_post_cobject',
-  );
+  final storeDartPostCobject = () {{
+    try {{
+      return dl.lookupFunction<_StoreDartPostCobjectC, _StoreDartPostCobjectDart>(
+        '{store_dart_post_cobject}',
+      );
+    }} on ArgumentError catch (err) {{
+      throw MembraneLibraryLoadException(
+          "The native library '$library' was opened but is not a Membrane library: ${{err.message}}");
+    }}
+  }}();
 
   {logger}.{fine_logger}('Initializing Dart_PostCObject');
   storeDartPostCobject(NativeApi.postCObject);
@@ -56,13 +161,81 @@ _load() {{
   final ptr = bindings.membrane_metadata_version();
   final version = ptr.cast<Utf8>().toDartString();
   bindings.membrane_free_membrane_string(ptr);
-  final msg = "Successfully loaded '{lib}' which was built at version '$version'.";
+  final msg = "Successfully loaded '$library' which was built at version '$version'.";
   {logger}.{info_logger}(msg);
 
+  _checkSchemaHashes(library, bindings);
+
   bindingsLoaded = true;
   return bindings;
 }}
 
+// The schema hash of each namespace, by library, at the time this package was generated
+const _schemaHashes = <String, Map<String, String>>{{{schema_hashes}}};
+
+// The hash of the function signatures of each namespace, by library, which every library reports
+const _signatureHashes = <String, Map<String, String>>{{{signature_hashes}}};
+
+void _checkSchemaHashes(String library, ffi_bindings.NativeLibrary bindings) {{
+  final schemaHashes = _readHashes(library, bindings, () => bindings.membrane_metadata_schema_hashes());
+  if (schemaHashes != null && schemaHashes.isNotEmpty) {{
+    _compareHashes(library, _schemaHashes[library] ?? const {{}}, schemaHashes);
+    return;
+  }}
+
+  final signatureHashes = _readHashes(library, bindings, () => bindings.membrane_metadata_signature_hashes());
+  if (signatureHashes == null) {{
+    {logger}.{warning_logger}("'$library' does not export schema hashes, skipping the schema check");
+    return;
+  }}
+
+  {logger}.{warning_logger}(
+      "'$library' was built without MEMBRANE_SCHEMA_HASHES, only its function signatures are checked against this package");
+  _compareHashes(library, _signatureHashes[library] ?? const {{}}, signatureHashes);
+}}
+
+// `null` when the library doesn't export the hashes
+Map<String, String>? _readHashes(
+    String library, ffi_bindings.NativeLibrary bindings, Pointer<Char> Function() read) {{
+  final Pointer<Char> ptr;
+  try {{
+    ptr = read();
+  }} on ArgumentError {{
+    return null;
+  }}
+  final encoded = ptr.cast<Utf8>().toDartString();
+  bindings.membrane_free_membrane_string(ptr);
+
+  final hashes = <String, String>{{}};
+  if (encoded.isEmpty) {{
+    return hashes;
+  }}
+
+  for (final pair in encoded.split(',')) {{
+    final separator = pair.lastIndexOf(':');
+    if (separator < 1 || separator == pair.length - 1) {{
+      throw MembraneLibraryLoadException("The native library '$library' reported malformed schema hashes: '$encoded'");
+    }}
+    hashes[pair.substring(0, separator)] = pair.substring(separator + 1);
+  }}
+  return hashes;
+}}
+
+void _compareHashes(String library, Map<String, String> expected, Map<String, String> actual) {{
+  // namespaces which were left out of this package can't be affected by a mismatch
+  final mismatched = [
+    for (final namespace in expected.keys)
+      if (expected[namespace] != actual[namespace]) namespace,
+  ];
+
+  if (mismatched.isNotEmpty) {{
+    throw MembraneVersionMismatchException(
+        "The native library '$library' was built from a different API than this Dart package was generated from. "
+        "Regenerate the Dart package or load the matching library. Namespaces which differ: ${{mismatched.join(', ')}}",
+        mismatched);
+  }}
+}}
+
 // Prefer using `bindings` without checking `bindingsLoaded` for most cases.
 // This boolean is for special cases where the Dart application needs to
 // perform differently until another part of the application needs to load
@@ -71,23 +244,174 @@ _load() {{
 // begin sending logs over the FFI boundary.
 bool bindingsLoaded = false;
 
-final bindings = _load();
+const _defaultLibrary = '{lib}';
+final _libraries = <String, ffi_bindings.NativeLibrary>{{}};
+
+/// The bindings of a library, loading it on first use.
+ffi_bindings.NativeLibrary libraryBindings(String library) {{
+  return _libraries.putIfAbsent(library, () => _load(library));
+}}
+
+final bindings = libraryBindings(_defaultLibrary);
 "#,
     lib = library,
+    store_dart_post_cobject = if symbol_prefix.is_empty() {
+      "store_dart_post_cobject".to_string()
+    } else {
+      format!("{}membrane_store_dart_post_cobject", symbol_prefix)
+    },
+    search_paths = search_paths
+      .iter()
+      .map(|path| {
+        format!(
+          "'{}'",
+          path
+            .replace('\\', "\\\\")
+            .replace('\'', "\\'")
+            .replace('$', "\\$")
+        )
+      })
+      .collect::<Vec<String>>()
+      .join(", "),
+    schema_hashes = dart_hashes(schema_hashes),
+    signature_hashes = dart_hashes(signature_hashes),
     logger_path = dart_config.logger.import_path,
     logger = dart_config.logger.instance,
     info_logger = dart_config.logger.info_log_fn,
     fine_logger = dart_config.logger.fine_log_fn,
+    warning_logger = dart_config.logger.warning_log_fn,
   )
 }
 
+///
+/// A Dart map literal of the hash of each namespace by library.
+fn dart_hashes(libraries: &BTreeMap<&str, Vec<(&str, String)>>) -> String {
+  libraries
+    .iter()
+    .map(|(library, hashes)| {
+      format!(
+        "'{}': {{{}}}",
+        library,
+        hashes
+          .iter()
+          .map(|(namespace, hash)| format!("'{}': '{}'", namespace, hash))
+          .collect::<Vec<String>>()
+          .join(", ")
+      )
+    })
+    .collect::<Vec<String>>()
+    .join(", ")
+}
+
 pub fn create_web_loader(_library: &str) -> String {
   "// AUTO GENERATED FILE, DO NOT EDIT
 //
 // Generated by `membrane`
+enum MembraneLookup {
+  open,
+  process,
+  executable,
+}
+
+class Membrane {
+  static void initialize({
+    String? path,
+    MembraneLookup? lookup,
+    List<String>? searchPaths,
+  }) {}
+}
+
 _connect() {}
 
+libraryBindings(String library) => _connect();
+
 bool bindingsLoaded = false;
 final bindings = _connect();"
     .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+  use super::create_ffi_loader;
+  use crate::DartConfig;
+  use std::collections::BTreeMap;
+
+  #[test]
+  fn test_ffi_loader() {
+    let libraries = BTreeMap::from([
+      ("libexample", vec![("accounts", "abc123".to_string())]),
+      (
+        "liborders",
+        vec![
+          ("orders", "def456".to_string()),
+          ("refunds", "789abc".to_string()),
+        ],
+      ),
+    ]);
+    let search_paths = vec![
+      r"C:\libs".to_string(),
+      "/opt/it's".to_string(),
+      "$HOME/lib".to_string(),
+    ];
+
+    let signatures = BTreeMap::from([("libexample", vec![("accounts", "0f1e2d".to_string())])]);
+
+    let loader = create_ffi_loader(
+      "libexample",
+      &search_paths,
+      &libraries,
+      &signatures,
+      "",
+      &DartConfig::default(),
+    );
+    assert!(loader.contains(
+      r"static List<String> _searchPaths = const ['C:\\libs', '/opt/it\'s', '\$HOME/lib'];"
+    ));
+    assert!(loader.contains("'store_dart_post_cobject',"));
+    assert!(loader.contains("const _defaultLibrary = 'libexample';"));
+    // one entry for each library
+    assert!(loader.contains(
+      "const _schemaHashes = <String, Map<String, String>>{'libexample': {'accounts': 'abc123'}, 'liborders': {'orders': 'def456', 'refunds': '789abc'}};"
+    ));
+
+    let position = |text: &str| loader.find(text).unwrap();
+    assert!(loader.contains(
+      "const _signatureHashes = <String, Map<String, String>>{'libexample': {'accounts': '0f1e2d'}};"
+    ));
+    // the signatures are only compared when the library wasn't built with its schema hashes
+    assert!(
+      position("_compareHashes(library, _schemaHashes[library]")
+        < position("bindings.membrane_metadata_signature_hashes()")
+    );
+    assert!(loader.contains(
+      "Logger('membrane').warning(\n      \"'$library' was built without MEMBRANE_SCHEMA_HASHES"
+    ));
+    // a pair without a namespace or a hash is rejected rather than read as an empty string
+    assert!(loader.contains("if (separator < 1 || separator == pair.length - 1) {\n      throw MembraneLibraryLoadException("));
+
+    // an explicit path wins over `MEMBRANE_LIB_PATH` which wins over the search paths
+    assert!(
+      position("_resolve(Membrane._path!, library)") < position("_resolve(envPath, library)")
+    );
+    assert!(
+      position("_resolve(envPath, library)") < position("for (final dir in Membrane._searchPaths)")
+    );
+    assert!(loader.contains(
+      "if (lookup == MembraneLookup.process) {\n    Logger('membrane').info('Using the process symbols for native library $library');\n    return DynamicLibrary.process();"
+    ));
+    // `initialize` configures the loader so it can't run after the bindings are loaded
+    assert!(position("if (bindingsLoaded) {\n      throw StateError(") < position("_path = path;"));
+
+    let loader = create_ffi_loader(
+      "libexample",
+      &[],
+      &libraries,
+      &signatures,
+      "acme_",
+      &DartConfig::default(),
+    );
+    assert!(loader.contains("static List<String> _searchPaths = const [];"));
+    assert!(loader.contains("'acme_membrane_store_dart_post_cobject',"));
+    assert!(!loader.contains("'store_dart_post_cobject'"));
+  }
+}
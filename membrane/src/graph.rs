@@ -0,0 +1,345 @@
+//! The dependency graph of namespaces formed by `borrow` attributes.
+//!
+//! Each edge points from the namespace that borrows a type to the namespace which owns it. The graph is
+//! written by `Membrane::write_dependency_graph()` as Graphviz DOT or JSON along with any cycles and
+//! unused borrows that were found.
+use crate::{shared, Membrane};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+  Dot,
+  Json,
+}
+
+impl GraphFormat {
+  pub fn from_extension(extension: &str) -> Option<Self> {
+    match extension {
+      "dot" | "gv" => Some(GraphFormat::Dot),
+      "json" => Some(GraphFormat::Json),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyGraph {
+  pub namespaces: Vec<String>,
+  pub edges: Vec<Edge>,
+  pub cycles: Vec<Cycle>,
+  pub unused_borrows: Vec<UnusedBorrow>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Edge {
+  pub namespace: String,
+  pub borrows_from: String,
+  pub types: Vec<String>,
+  pub locations: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cycle {
+  pub namespaces: Vec<String>,
+  pub locations: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnusedBorrow {
+  pub namespace: String,
+  pub borrows_from: String,
+  #[serde(rename = "type")]
+  pub ty: String,
+  pub locations: Vec<String>,
+}
+
+impl DependencyGraph {
+  pub fn to_string(&self, format: GraphFormat) -> Result<String, String> {
+    match format {
+      GraphFormat::Dot => Ok(self.to_dot()),
+      GraphFormat::Json => serde_json::to_string_pretty(self)
+        .map(|json| json + "\n")
+        .map_err(|err| err.to_string()),
+    }
+  }
+
+  fn to_dot(&self) -> String {
+    let nodes = self
+      .namespaces
+      .iter()
+      .map(|namespace| format!("  \"{}\";\n", namespace));
+
+    let edges = self.edges.iter().map(|edge| {
+      format!(
+        "  \"{}\" -> \"{}\" [label=\"{}\"];\n",
+        edge.namespace,
+        edge.borrows_from,
+        edge.types.join("\\n")
+      )
+    });
+
+    format!(
+      "digraph membrane {{\n{}{}}}\n",
+      nodes.collect::<String>(),
+      edges.collect::<String>()
+    )
+  }
+
+  ///
+  /// A description of each cycle and unused borrow, including the source code locations of the
+  /// functions which declared the borrows.
+  pub fn findings(&self) -> Vec<String> {
+    let cycles = self.cycles.iter().map(|cycle| {
+      format!(
+        "The namespaces {} borrow from each other in a cycle{}",
+        cycle
+          .namespaces
+          .iter()
+          .chain(cycle.namespaces.first())
+          .map(|x| format!("`{}`", x))
+          .collect::<Vec<String>>()
+          .join(" -> "),
+        crate::utils::display_code_location(
+          &cycle
+            .locations
+            .iter()
+            .map(|x| x.as_str())
+            .collect::<Vec<&str>>()
+        )
+      )
+    });
+
+    let unused = self.unused_borrows.iter().map(|borrow| {
+      format!(
+        "`{}::{}` was borrowed by `{}`{} but none of the functions in `{}` use it. Remove the borrow.",
+        borrow.borrows_from,
+        borrow.ty,
+        borrow.namespace,
+        crate::utils::display_code_location(
+          &borrow
+            .locations
+            .iter()
+            .map(|x| x.as_str())
+            .collect::<Vec<&str>>()
+        ),
+        borrow.namespace
+      )
+    });
+
+    cycles.chain(unused).collect()
+  }
+}
+
+impl Membrane {
+  ///
+  /// Build the dependency graph from the borrows and the traced registries.
+  pub(crate) fn build_dependency_graph(&self) -> DependencyGraph {
+    let mut namespaces = self
+      .namespaces
+      .iter()
+      .chain(self.borrows.values().flat_map(|imports| imports.keys()))
+      .map(|x| x.to_string())
+      .collect::<Vec<String>>();
+    namespaces.sort();
+    namespaces.dedup();
+
+    let mut edges = vec![];
+    let mut unused_borrows = vec![];
+    let mut adjacency: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+
+    let mut borrowers = self.borrows.keys().collect::<Vec<_>>();
+    borrowers.sort();
+
+    for namespace in borrowers {
+      let used = self.type_locations(namespace);
+
+      for (from_namespace, (types, locations)) in self.borrows[namespace].iter() {
+        adjacency
+          .entry(namespace)
+          .or_default()
+          .insert(from_namespace);
+
+        let mut edge_locations = locations
+          .values()
+          .flatten()
+          .map(|x| x.to_string())
+          .collect::<Vec<String>>();
+        edge_locations.sort();
+        edge_locations.dedup();
+
+        edges.push(Edge {
+          namespace: namespace.to_string(),
+          borrows_from: from_namespace.to_string(),
+          types: types.iter().map(|x| x.to_string()).collect(),
+          locations: edge_locations,
+        });
+
+        unused_borrows.extend(
+          types
+            .iter()
+            .filter(|x| !used.contains_key(**x))
+            .map(|r#type| UnusedBorrow {
+              namespace: namespace.to_string(),
+              borrows_from: from_namespace.to_string(),
+              ty: r#type.to_string(),
+              locations: locations
+                .get(r#type)
+                .map(|x| x.iter().map(|x| x.to_string()).collect())
+                .unwrap_or_default(),
+            }),
+        );
+      }
+    }
+
+    let cycles = cycles(&adjacency)
+      .into_iter()
+      .map(|members| {
+        let mut locations = edges
+          .iter()
+          .filter(|edge| {
+            members.contains(&edge.namespace.as_str())
+              && members.contains(&edge.borrows_from.as_str())
+          })
+          .flat_map(|edge| edge.locations.clone())
+          .collect::<Vec<String>>();
+        locations.sort();
+        locations.dedup();
+
+        Cycle {
+          namespaces: members.iter().map(|x| x.to_string()).collect(),
+          locations,
+        }
+      })
+      .collect();
+
+    DependencyGraph {
+      namespaces,
+      edges,
+      cycles,
+      unused_borrows,
+    }
+  }
+
+  ///
+  /// The types reachable from the signatures of a namespace's functions along with the source code
+  /// locations of the functions which reach them.
+  pub(crate) fn type_locations(&self, namespace: &str) -> BTreeMap<String, Vec<&'static str>> {
+    let registry = match self.namespaced_registry.get(namespace) {
+      Some(Ok(registry)) => registry,
+      _ => return BTreeMap::new(),
+    };
+
+    let mut locations: BTreeMap<String, Vec<&'static str>> = BTreeMap::new();
+    for fun in self
+      .namespaced_fn_registry
+      .get(namespace)
+      .into_iter()
+      .flatten()
+    {
+      let mut pending = fun
+        .return_type
+        .iter()
+        .chain(fun.error_type.iter())
+        .chain(fun.args.iter().flat_map(|arg| arg.ty.iter()))
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>();
+
+      let mut seen = BTreeSet::new();
+      while let Some(name) = pending.pop() {
+        if let Some(format) = registry.get(&name) {
+          if seen.insert(name.clone()) {
+            pending.extend(shared::references(format));
+            locations.entry(name).or_default().push(fun.location);
+          }
+        }
+      }
+    }
+
+    locations
+  }
+}
+
+///
+/// The strongly connected components with more than one namespace, each starting from its
+/// alphabetically first namespace and ordered by the borrows which connect them.
+fn cycles<'a>(adjacency: &BTreeMap<&'a str, BTreeSet<&'a str>>) -> Vec<Vec<&'a str>> {
+  let reachable = |start: &'a str| {
+    let mut seen = BTreeSet::new();
+    let mut pending = vec![start];
+    while let Some(node) = pending.pop() {
+      for next in adjacency.get(node).into_iter().flatten() {
+        if seen.insert(*next) {
+          pending.push(next);
+        }
+      }
+    }
+    seen
+  };
+
+  let mut found: Vec<Vec<&str>> = vec![];
+  for node in adjacency.keys() {
+    if found.iter().any(|cycle| cycle.contains(node)) {
+      continue;
+    }
+
+    let from_node = reachable(node);
+    let members = from_node
+      .iter()
+      .filter(|other| **other != *node && reachable(other).contains(node))
+      .copied()
+      .collect::<BTreeSet<&str>>();
+
+    if members.is_empty() {
+      continue;
+    }
+
+    // walk the cycle in borrow order so that it reads `a -> b -> c -> a`
+    let mut cycle = vec![*node];
+    let mut current = *node;
+    while let Some(next) = adjacency
+      .get(current)
+      .into_iter()
+      .flatten()
+      .find(|next| members.contains(*next) && !cycle.contains(*next))
+    {
+      cycle.push(next);
+      current = next;
+    }
+    // any members that weren't on the walk are appended in order
+    cycle.extend(
+      members
+        .iter()
+        .filter(|x| !cycle.contains(*x))
+        .collect::<Vec<_>>(),
+    );
+
+    found.push(cycle);
+  }
+
+  found
+}
+
+#[cfg(test)]
+mod tests {
+  use super::cycles;
+  use std::collections::{BTreeMap, BTreeSet};
+
+  #[test]
+  fn test_cycles() {
+    let adjacency = BTreeMap::from([
+      ("accounts", BTreeSet::from(["orgs"])),
+      ("orgs", BTreeSet::from(["locations"])),
+      ("locations", BTreeSet::from(["accounts", "common"])),
+      ("billing", BTreeSet::from(["common"])),
+    ]);
+
+    assert_eq!(
+      cycles(&adjacency),
+      vec![vec!["accounts", "orgs", "locations"]]
+    );
+
+    let adjacency = BTreeMap::from([("billing", BTreeSet::from(["common"]))]);
+    assert!(cycles(&adjacency).is_empty());
+  }
+}
@@ -72,29 +72,44 @@ pub use git_version::git_version;
 #[doc(hidden)]
 pub use inventory;
 #[doc(hidden)]
-pub use membrane_macro::{async_dart, dart_enum, export_metadata, sync_dart};
+pub use membrane_macro::{
+  async_dart, dart_enum, dart_sample, dart_type, export_metadata, sync_dart, DartEnum,
+};
 #[doc(hidden)]
 pub use serde_reflection;
 
+pub mod build;
+pub mod compatibility;
 #[doc(hidden)]
 pub mod emitter;
 #[doc(hidden)]
+pub mod encoding;
+pub mod graph;
+#[doc(hidden)]
+pub mod manifest;
+#[doc(hidden)]
 pub mod metadata;
 pub mod runtime;
+pub mod schema;
 #[doc(hidden)]
 pub mod utils;
 
 mod generators;
+mod shared;
+mod transparent;
+mod validation;
 
+use compatibility::Compatibility;
 use generators::{
   exceptions,
   functions::{Builder, Writable},
   imports, loaders,
 };
+use graph::{DependencyGraph, GraphFormat};
+use manifest::Manifest;
 use membrane_types::heck::{ToSnakeCase, ToUpperCamelCase};
-use serde_reflection::{
-  ContainerFormat, Error, Registry, Samples, Tracer, TracerConfig, VariantFormat,
-};
+use schema::{Schema, SchemaFormat};
+use serde_reflection::{ContainerFormat, Error, Registry, Samples, VariantFormat};
 use std::{
   collections::{BTreeMap, BTreeSet, HashMap},
   fs::remove_file,
@@ -167,6 +182,7 @@ pub struct DartLoggerConfig {
   pub instance: &'static str,
   pub info_log_fn: &'static str,
   pub fine_log_fn: &'static str,
+  pub warning_log_fn: &'static str,
 }
 
 impl Default for DartLoggerConfig {
@@ -177,15 +193,16 @@ impl Default for DartLoggerConfig {
       instance: "Logger('membrane')",
       info_log_fn: "info",
       fine_log_fn: "fine",
+      warning_log_fn: "warning",
     }
   }
 }
 
 type Namespace = &'static str;
 type Borrows =
-  HashMap<Namespace, BTreeMap<&'static str, (BTreeSet<&'static str>, ExplicitBorrowLocations)>>;
+  BTreeMap<Namespace, BTreeMap<&'static str, (BTreeSet<&'static str>, ExplicitBorrowLocations)>>;
 type SourceCodeLocation = &'static str;
-type ExplicitBorrowLocations = HashMap<&'static str, Vec<SourceCodeLocation>>;
+type ExplicitBorrowLocations = BTreeMap<&'static str, Vec<SourceCodeLocation>>;
 
 #[doc(hidden)]
 #[derive(Debug, Clone)]
@@ -197,6 +214,7 @@ pub struct Function {
   pub is_sync: bool,
   pub return_type: &'static [&'static str],
   pub error_type: &'static [&'static str],
+  pub args: &'static [Argument],
   pub namespace: &'static str,
   pub disable_logging: bool,
   pub timeout: Option<i32>,
@@ -209,12 +227,51 @@ pub struct Function {
   pub docblock: &'static str,
 }
 
+#[doc(hidden)]
+#[derive(Debug, Clone)]
+pub struct Argument {
+  pub name: &'static str,
+  pub ty: &'static [&'static str],
+}
+
+///
+/// The parts of a function which Dart relies on to call it, registered in every build profile so that a
+/// library can report a hash of its functions without tracing them.
+#[doc(hidden)]
+#[derive(Debug, Clone)]
+pub struct Signature {
+  pub namespace: &'static str,
+  pub fn_name: &'static str,
+  pub is_stream: bool,
+  pub is_sync: bool,
+  pub return_type: &'static [&'static str],
+  pub error_type: &'static [&'static str],
+  pub args: &'static [Argument],
+}
+
+impl From<&Function> for Signature {
+  fn from(function: &Function) -> Self {
+    Self {
+      namespace: function.namespace,
+      fn_name: function.fn_name,
+      is_stream: function.is_stream,
+      is_sync: function.is_sync,
+      return_type: function.return_type,
+      error_type: function.error_type,
+      args: function.args,
+    }
+  }
+}
+
 #[doc(hidden)]
 #[derive(Clone)]
 pub struct DeferredTrace {
   pub function: Function,
   pub namespace: &'static str,
-  pub trace: fn(tracer: &mut serde_reflection::Tracer, samples: &mut serde_reflection::Samples),
+  pub trace: fn(
+    tracer: &mut serde_reflection::Tracer,
+    samples: &mut serde_reflection::Samples,
+  ) -> Result<Vec<serde_reflection::Format>, String>,
 }
 
 impl std::fmt::Debug for DeferredTrace {
@@ -234,12 +291,18 @@ pub struct Enum {
   pub namespace: &'static str,
 }
 
+#[doc(hidden)]
+pub type EnumTraceFn = fn(
+  tracer: &mut serde_reflection::Tracer,
+  samples: &mut serde_reflection::Samples,
+) -> Result<serde_reflection::Format, String>;
+
 #[doc(hidden)]
 #[derive(Clone)]
 pub struct DeferredEnumTrace {
   pub enum_data: Enum,
   pub namespace: &'static str,
-  pub trace: fn(tracer: &mut serde_reflection::Tracer),
+  pub trace: EnumTraceFn,
 }
 
 impl std::fmt::Debug for DeferredEnumTrace {
@@ -251,8 +314,71 @@ impl std::fmt::Debug for DeferredEnumTrace {
   }
 }
 
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct DeferredSample {
+  pub type_name: &'static str,
+  pub location: SourceCodeLocation,
+  pub trace: fn(
+    tracer: &mut serde_reflection::Tracer,
+    samples: &mut serde_reflection::Samples,
+  ) -> Result<(), String>,
+}
+
+impl std::fmt::Debug for DeferredSample {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("DeferredSample")
+      .field("type_name", &self.type_name)
+      .field("location", &self.location)
+      .finish()
+  }
+}
+
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct DeferredTypeTrace {
+  pub name: &'static str,
+  pub namespace: &'static str,
+  pub location: SourceCodeLocation,
+  pub trace: fn(
+    tracer: &mut serde_reflection::Tracer,
+    samples: &mut serde_reflection::Samples,
+  ) -> Result<serde_reflection::Format, String>,
+}
+
+impl std::fmt::Debug for DeferredTypeTrace {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("DeferredTypeTrace")
+      .field("name", &self.name)
+      .field("namespace", &self.namespace)
+      .field("location", &self.location)
+      .finish()
+  }
+}
+
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct DeferredDerivedEnumTrace {
+  pub name: &'static str,
+  pub location: SourceCodeLocation,
+  pub trace: EnumTraceFn,
+}
+
+impl std::fmt::Debug for DeferredDerivedEnumTrace {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("DeferredDerivedEnumTrace")
+      .field("name", &self.name)
+      .field("location", &self.location)
+      .finish()
+  }
+}
+
 inventory::collect!(DeferredTrace);
 inventory::collect!(DeferredEnumTrace);
+inventory::collect!(DeferredSample);
+inventory::collect!(DeferredTypeTrace);
+inventory::collect!(DeferredDerivedEnumTrace);
+inventory::collect!(Signature);
 
 macro_rules! return_if_error {
   ( $e:expr ) => {
@@ -262,6 +388,16 @@ macro_rules! return_if_error {
   };
 }
 
+#[derive(Default)]
+pub(crate) struct Traced {
+  errors: Vec<String>,
+  namespaces: Vec<&'static str>,
+  namespaced_registry: BTreeMap<&'static str, serde_reflection::Result<Registry>>,
+  namespaced_fn_registry: BTreeMap<&'static str, Vec<Function>>,
+  namespaced_enum_registry: BTreeMap<&'static str, Vec<Enum>>,
+  borrows: Borrows,
+}
+
 #[derive(Debug)]
 pub struct Membrane {
   errors: Vec<String>,
@@ -269,10 +405,17 @@ pub struct Membrane {
   destination: PathBuf,
   library: String,
   llvm_paths: Vec<String>,
+  library_search_paths: Vec<String>,
+  symbol_prefix: String,
+  namespace_libraries: BTreeMap<&'static str, String>,
+  include_namespaces: Vec<String>,
+  exclude_namespaces: Vec<String>,
+  schema_format: SchemaFormat,
+  allow_breaking_changes: bool,
   namespaces: Vec<&'static str>,
-  namespaced_registry: HashMap<&'static str, serde_reflection::Result<Registry>>,
-  namespaced_fn_registry: HashMap<&'static str, Vec<Function>>,
-  namespaced_enum_registry: HashMap<&'static str, Vec<Enum>>,
+  namespaced_registry: BTreeMap<&'static str, serde_reflection::Result<Registry>>,
+  namespaced_fn_registry: BTreeMap<&'static str, Vec<Function>>,
+  namespaced_enum_registry: BTreeMap<&'static str, Vec<Enum>>,
   generated: bool,
   c_style_enums: bool,
   sealed_enums: bool,
@@ -288,7 +431,7 @@ impl<'a> Membrane {
   /// `bin` where Membrane is initialized.
   #[allow(clippy::new_without_default)]
   pub fn new() -> Self {
-    Self::initialize_from_metadata(None::<&std::path::Path>)
+    Self::initialize_from_metadata::<&std::path::Path>(&[])
   }
 
   ///
@@ -305,15 +448,28 @@ impl<'a> Membrane {
   where
     P: AsRef<Path> + std::fmt::Debug,
   {
-    Self::initialize_from_metadata(Some(cdylib_path))
+    Self::initialize_from_metadata(&[cdylib_path])
   }
 
-  fn initialize_from_metadata<P>(cdylib_path: Option<&'a P>) -> Self
+  ///
+  /// Like `new_from_cdylib` but merges the metadata of several libraries into a single Dart package. Each
+  /// namespace is bound to the library it was found in, so the generated Dart code loads every library
+  /// and calls each namespace's functions through its own library. A namespace may only be defined in one
+  /// of the libraries.
+  pub fn new_from_cdylibs<P>(cdylib_paths: &'a [P]) -> Self
   where
-    P: ?Sized + AsRef<Path> + std::fmt::Debug,
+    P: AsRef<Path> + std::fmt::Debug,
+  {
+    Self::initialize_from_metadata(cdylib_paths)
+  }
+
+  fn initialize_from_metadata<P>(cdylib_paths: &[P]) -> Self
+  where
+    P: AsRef<Path> + std::fmt::Debug,
   {
     let mut errors = vec![];
     let mut input_libs = vec![];
+    let mut namespace_libraries: BTreeMap<&'static str, (String, String)> = BTreeMap::new();
 
     std::env::set_var(
       "RUST_LOG",
@@ -322,14 +478,27 @@ impl<'a> Membrane {
 
     let _ = pretty_env_logger::try_init();
 
-    let (mut enums, mut functions) = match cdylib_path {
-      None => {
-        info!("No `lib.so` paths were passed, generating code from local `lib` source");
-
-        (metadata::enums(), metadata::functions())
-      }
-      Some(lib_path) => {
-        let (enums, functions, version, _membrane_version) =
+    let (mut enums, mut functions, mut samples, mut types, mut derived_enums) = if cdylib_paths
+      .is_empty()
+    {
+      info!("No `lib.so` paths were passed, generating code from local `lib` source");
+
+      (
+        metadata::enums(),
+        metadata::functions(),
+        metadata::samples(),
+        metadata::types(),
+        metadata::derived_enums(),
+      )
+    } else {
+      let mut all_enums = vec![];
+      let mut all_samples = vec![];
+      let mut all_functions = vec![];
+      let mut all_types = vec![];
+      let mut all_derived_enums = vec![];
+
+      for lib_path in cdylib_paths {
+        let (enums, functions, samples, types, derived_enums, version, _membrane_version) =
           match metadata::extract_metadata_from_cdylib(
             lib_path.as_ref().as_os_str(),
             &mut input_libs,
@@ -346,99 +515,213 @@ impl<'a> Membrane {
           "Generating code from {:?} which was compiled at version {:?}",
           lib_path, version
         );
-        (enums, functions)
+
+        let library = lib_path
+          .as_ref()
+          .file_stem()
+          .and_then(|x| x.to_str())
+          .unwrap_or_default()
+          .to_string();
+        let display_path = format!("{:?}", lib_path);
+
+        let mut lib_namespaces = [
+          enums.iter().map(|x| x.namespace).collect::<Vec<&str>>(),
+          functions.iter().map(|x| x.namespace).collect::<Vec<&str>>(),
+          types.iter().map(|x| x.namespace).collect::<Vec<&str>>(),
+        ]
+        .concat();
+        lib_namespaces.sort_unstable();
+        lib_namespaces.dedup();
+
+        for namespace in lib_namespaces {
+          match namespace_libraries.get(namespace) {
+            Some((_, other_path)) => errors.push(format!(
+              "The namespace `{}` was found in both {} and {}. A namespace may only be defined in one library.",
+              namespace, other_path, display_path
+            )),
+            None => {
+              namespace_libraries.insert(namespace, (library.clone(), display_path.clone()));
+            }
+          }
+        }
+
+        all_enums.extend(enums);
+        all_samples.extend(samples);
+        all_functions.extend(functions);
+        all_types.extend(types);
+        all_derived_enums.extend(derived_enums);
       }
+
+      (
+        all_enums,
+        all_functions,
+        all_samples,
+        all_types,
+        all_derived_enums,
+      )
     };
 
-    if enums.is_empty() && functions.is_empty() {
+    if enums.is_empty() && functions.is_empty() && types.is_empty() {
       info!(
         "No type information could be found. Do you have #[async_dart] or #[sync_dart] in your code?"
       );
     }
 
-    enums.sort_by_cached_key(|e| &e.enum_data.name);
+    if !errors.is_empty() {
+      // the traces of colliding namespaces would be merged into a meaningless registry
+      enums.clear();
+      functions.clear();
+      samples.clear();
+      types.clear();
+      derived_enums.clear();
+    }
 
-    functions.sort_by_cached_key(|f| {
-      format!(
-        "{}{}{}",
-        f.function.is_stream, f.function.is_sync, f.function.fn_name
-      )
-    });
+    let traced = Self::trace(
+      &mut enums,
+      &mut functions,
+      &mut samples,
+      &mut types,
+      &mut derived_enums,
+    );
+    let mut membrane = Self::from_traced(errors, traced, input_libs);
 
-    let mut namespaces = [
-      enums.iter().map(|x| x.namespace).collect::<Vec<&str>>(),
-      functions.iter().map(|x| x.namespace).collect::<Vec<&str>>(),
-    ]
-    .concat();
+    // a single library is addressed by `using_lib`, only map namespaces when there are several
+    if cdylib_paths.len() > 1 {
+      membrane.namespace_libraries = namespace_libraries
+        .into_iter()
+        .map(|(namespace, (library, _path))| (namespace, library))
+        .collect();
+      membrane.check_library_borrows();
+    }
 
-    namespaces.sort_unstable();
-    namespaces.dedup();
+    membrane
+  }
 
-    let mut namespaced_registry = HashMap::new();
-    let mut namespaced_samples = HashMap::new();
-    let mut namespaced_fn_registry = HashMap::new();
-    let mut namespaced_enum_registry = HashMap::new();
-    let mut borrows: Borrows = HashMap::new();
+  ///
+  /// This method generates code from a manifest previously written by `write_manifest`. No library is loaded
+  /// so it can be used with release builds (which do not contain metadata) and with libraries cross-compiled
+  /// for a platform other than the build host.
+  ///
+  /// Procedural macros are unable to trace types while compiling so the manifest can't be written by the
+  /// compiler itself. Instead write it from a build of the same source which does have metadata, such as
+  /// a debug build of the generator on the build host, and keep it alongside the release artifacts.
+  pub fn new_from_manifest<P>(manifest_path: &'a P) -> Self
+  where
+    P: ?Sized + AsRef<Path> + std::fmt::Debug,
+  {
+    std::env::set_var(
+      "RUST_LOG",
+      std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()),
+    );
 
-    // collect all the metadata about functions (without tracing them yet)
-    functions.iter().for_each(|item| {
-      namespaced_fn_registry
-        .entry(item.namespace)
-        .or_insert_with(Vec::new)
-        .push(item.function.clone());
-    });
+    let _ = pretty_env_logger::try_init();
+
+    let manifest = match std::fs::read_to_string(manifest_path)
+      .map_err(|err| err.to_string())
+      .and_then(|contents| contents.parse::<Manifest>())
+    {
+      Ok(manifest) => manifest,
+      Err(err) => {
+        let error = format!(
+          "Unable to read the Membrane manifest {:?}: {}",
+          manifest_path, err
+        );
+        return Self::from_traced(vec![error], Traced::default(), vec![]);
+      }
+    };
+
+    info!(
+      "Generating code from {:?} which was written by membrane {}",
+      manifest_path, manifest.membrane_version
+    );
+
+    let mut namespaced_fn_registry = BTreeMap::new();
+    let mut namespaced_enum_registry = BTreeMap::new();
+    let mut namespaced_registry = BTreeMap::new();
+    let mut borrows: Borrows = BTreeMap::new();
+    let mut namespaces = vec![];
+
+    for namespace in manifest.namespaces {
+      let name = manifest::leak(namespace.namespace);
+      namespaces.push(name);
+      namespaced_fn_registry.insert(
+        name,
+        namespace
+          .functions
+          .into_iter()
+          .map(|x| x.into_function(name))
+          .collect(),
+      );
+      namespaced_enum_registry.insert(
+        name,
+        namespace
+          .enums
+          .into_iter()
+          .map(|x| x.into_enum(name))
+          .collect(),
+      );
+      namespaced_registry.insert(name, Ok(namespace.types));
+    }
 
-    // work out which namespaces borrow which types from other namespaces
     namespaces.iter().for_each(|namespace| {
       Self::create_borrows(&namespaced_fn_registry, namespace, &mut borrows);
     });
 
-    // collect all the metadata about enums (without tracing them yet)
-    enums.iter().for_each(|item| {
-      namespaced_enum_registry
-        .entry(item.namespace)
-        .or_insert_with(Vec::new)
-        .push(item.enum_data.clone());
-    });
+    let mut membrane = Self::from_traced(
+      vec![],
+      Traced {
+        errors: vec![],
+        namespaces,
+        namespaced_registry,
+        namespaced_fn_registry,
+        namespaced_enum_registry,
+        borrows,
+      },
+      vec![],
+    );
 
-    // trace all the enums at least once
-    enums.iter().for_each(|item| {
-      // trace the enum into the borrowing namespace's registry
-      borrows
-        .clone()
+    membrane.symbol_prefix = manifest.symbol_prefix;
+    if !manifest.namespace_libraries.is_empty() {
+      membrane.namespace_libraries = manifest
+        .namespace_libraries
         .into_iter()
-        .for_each(|(for_namespace, from_namespaces)| {
-          if let Some((types, _location)) = from_namespaces.get(item.namespace) {
-            if types.contains(item.enum_data.name) {
-              let tracer = namespaced_registry
-                .entry(for_namespace)
-                .or_insert_with(|| Tracer::new(TracerConfig::default()));
-
-              (item.trace)(tracer);
-            }
-          }
-        });
-
-      // trace the enum into the owning namespace's registry
-      let tracer = namespaced_registry
-        .entry(item.namespace)
-        .or_insert_with(|| Tracer::new(TracerConfig::default()));
-
-      (item.trace)(tracer);
-    });
+        .map(|(namespace, library)| (manifest::leak(namespace), library))
+        .collect();
+      membrane.check_library_borrows();
+    }
 
-    // now that we have the enums in the registry we'll trace each of the functions
-    functions.iter().for_each(|item| {
-      let tracer = namespaced_registry
-        .entry(item.namespace)
-        .or_insert_with(|| Tracer::new(TracerConfig::default()));
+    membrane
+  }
 
-      let samples = namespaced_samples
-        .entry(item.namespace)
-        .or_insert_with(Samples::new);
+  fn from_traced(
+    mut errors: Vec<String>,
+    traced: Traced,
+    input_libs: Vec<libloading::Library>,
+  ) -> Self {
+    let Traced {
+      errors: trace_errors,
+      namespaces,
+      namespaced_registry,
+      namespaced_fn_registry,
+      namespaced_enum_registry,
+      borrows,
+    } = traced;
+    errors.extend(trace_errors);
 
-      (item.trace)(tracer, samples);
-    });
+    // nested namespaces are flattened for file and C symbol names, which could collide with another namespace
+    for (index, namespace) in namespaces.iter().enumerate() {
+      if let Some(other) = namespaces[index + 1..]
+        .iter()
+        .find(|other| utils::module_name(other) == utils::module_name(namespace))
+      {
+        errors.push(format!(
+          "The namespaces `{}` and `{}` would both be generated as `{}`. Rename one of them.",
+          namespace,
+          other,
+          utils::module_name(namespace)
+        ));
+      }
+    }
 
     Self {
       errors,
@@ -454,19 +737,18 @@ impl<'a> Membrane {
         Some(library) => library.into_string().unwrap(),
         None => "libmembrane".to_string(),
       },
-      llvm_paths: match std::env::var_os("MEMBRANE_LLVM_PATHS") {
-        Some(config) => config
-          .into_string()
-          .unwrap()
-          .split(&[',', ' '][..])
-          .map(|x| x.to_string())
-          .collect(),
-        None => vec![],
-      },
-      namespaced_registry: namespaced_registry
-        .into_iter()
-        .map(|(key, val)| (key, val.registry()))
-        .collect(),
+      llvm_paths: utils::env_list("MEMBRANE_LLVM_PATHS"),
+      library_search_paths: vec![],
+      symbol_prefix: metadata::symbol_prefix(),
+      namespace_libraries: BTreeMap::new(),
+      include_namespaces: utils::env_list("MEMBRANE_INCLUDE_NAMESPACES"),
+      exclude_namespaces: utils::env_list("MEMBRANE_EXCLUDE_NAMESPACES"),
+      schema_format: SchemaFormat::Yaml,
+      allow_breaking_changes: matches!(
+        std::env::var("MEMBRANE_ALLOW_BREAKING_CHANGES").as_deref(),
+        Ok("1") | Ok("true")
+      ),
+      namespaced_registry,
       namespaced_fn_registry,
       namespaced_enum_registry,
       namespaces,
@@ -540,6 +822,75 @@ impl<'a> Membrane {
     self
   }
 
+  ///
+  /// Directories that the generated Dart loader searches (in order) for the library given to `using_lib`
+  /// before falling back to the platform's default library search. These defaults can be replaced at runtime
+  /// by calling `Membrane.initialize(searchPaths: [...])` from Dart or by setting the `MEMBRANE_LIB_PATH`
+  /// environment variable.
+  pub fn library_search_paths(&mut self, paths: Vec<&str>) -> &mut Self {
+    return_if_error!(self);
+    self.library_search_paths = paths.iter().map(|x| x.to_string()).collect();
+    self
+  }
+
+  ///
+  /// Only generate the namespaces matching one of these patterns. A `*` in a pattern matches any
+  /// sequence of characters and a `?` matches a single character, so `vec!["accounts", "billing_*"]`
+  /// generates `accounts` along with every namespace starting with `billing_`.
+  ///
+  /// Namespaces which aren't generated are left out of the package entirely unless a generated namespace
+  /// borrows from them. In that case their types are still generated (so that the borrow resolves) but their
+  /// API classes and C headers are not.
+  ///
+  /// Can be overridden with the environment variable `MEMBRANE_INCLUDE_NAMESPACES`. Takes a comma or space separated list.
+  pub fn include_namespaces(&mut self, patterns: Vec<&str>) -> &mut Self {
+    return_if_error!(self);
+    if self.include_namespaces.is_empty() {
+      self.include_namespaces = patterns.iter().map(|x| x.to_string()).collect();
+    }
+    self.warn_unmatched_patterns(&self.include_namespaces);
+    self
+  }
+
+  ///
+  /// Skip generating the namespaces matching one of these patterns, which take the same form as
+  /// `include_namespaces`. Exclusions are applied after inclusions.
+  ///
+  /// Can be overridden with the environment variable `MEMBRANE_EXCLUDE_NAMESPACES`. Takes a comma or space separated list.
+  pub fn exclude_namespaces(&mut self, patterns: Vec<&str>) -> &mut Self {
+    return_if_error!(self);
+    if self.exclude_namespaces.is_empty() {
+      self.exclude_namespaces = patterns.iter().map(|x| x.to_string()).collect();
+    }
+    self.warn_unmatched_patterns(&self.exclude_namespaces);
+    self
+  }
+
+  ///
+  /// Generate every type which is used (with an identical definition) by more than one namespace once, in a
+  /// new namespace with this name, and import it from there instead of using `borrow` attributes. Types which
+  /// are explicitly borrowed are left where the borrow puts them. The namespace has no API and is not part of
+  /// schemas, manifests, or C headers.
+  ///
+  /// Call this before `include_namespaces` or `exclude_namespaces`.
+  pub fn shared_types_namespace(&mut self, namespace: &str) -> &mut Self {
+    return_if_error!(self);
+    if let Some(existing) = self
+      .namespaces
+      .iter()
+      .find(|x| utils::module_name(x) == utils::module_name(namespace))
+    {
+      self.errors.push(format!(
+        "`shared_types_namespace(\"{}\")` can't be used because it would be generated as `{}` which is already a namespace. Choose a name which is not used by any function or enum.",
+        namespace, existing
+      ));
+      return self;
+    }
+
+    self.place_shared_types(namespace);
+    self
+  }
+
   ///
   /// Write the pub package to the destination set with `package_destination_dir`.
   /// Existing Dart files in this directory may be deleted during this operation.
@@ -554,6 +905,9 @@ impl<'a> Membrane {
     ))]
     return self;
 
+    self.validate_names();
+    return_if_error!(self);
+
     // remove all previously generated type and header files
     let _ = std::fs::remove_dir_all(self.destination.join("lib"));
     let _ = std::fs::remove_file(self.destination.join("pubspec.yaml"));
@@ -563,9 +917,9 @@ impl<'a> Membrane {
     installer.install_serde_runtime().unwrap();
     installer.install_bincode_runtime().unwrap();
 
-    for namespace in self.namespaces.iter() {
+    for namespace in self.required_namespaces().iter() {
       debug!("Generating lib/src/ code for namespace {}", namespace);
-      let config = serde_generate::CodeGeneratorConfig::new(namespace.to_string())
+      let config = serde_generate::CodeGeneratorConfig::new(utils::module_name(namespace))
         .with_encodings(vec![serde_generate::Encoding::Bincode])
         .with_c_style_enums(self.c_style_enums)
         .with_sealed_enums(self.sealed_enums)
@@ -584,28 +938,22 @@ impl<'a> Membrane {
 
       let registry = match self.namespaced_registry.get(namespace).unwrap() {
         Ok(reg) => reg,
-        Err(Error::MissingVariants(names)) => {
-          self.errors.push(format!(r#"
-##
-#
-# An enum was used that has not had the membrane::dart_enum macro applied for a namespace which owns or borrows it.
-#
-# Please add #[dart_enum(namespace = "{}")] to the {} enum.
-#
-##"#,
-            namespace,
-            names.first().unwrap()
-          ));
-
-          return self;
-        }
         Err(err) => {
-          self.errors.push(format!("{}", err));
+          self.errors.push(self.registry_error(err));
           return self;
         }
       };
 
       installer.install_module(&config, registry).unwrap();
+      if namespace.contains("::") {
+        // the public library of a nested namespace is written to a nested path by `create_class`
+        let _ = std::fs::remove_file(
+          self
+            .destination
+            .join("lib")
+            .join(utils::module_name(namespace) + ".dart"),
+        );
+      }
     }
 
     self.generated = true;
@@ -674,6 +1022,7 @@ impl<'a> Membrane {
   ///     instance: "Logger('membrane')",
   ///     info_log_fn: "info",
   ///     fine_log_fn: "fine",
+  ///     warning_log_fn: "warning",
   ///   }
   /// }
   pub fn dart_config(&mut self, config: DartConfig) -> &mut Self {
@@ -683,128 +1032,936 @@ impl<'a> Membrane {
   }
 
   ///
-  /// Write a header file for each namespace that provides the C types
-  /// needed by ffigen to generate the FFI bindings.
-  pub fn write_c_headers(&mut self) -> &mut Self {
-    return_if_error!(self);
-    let head = r#"/*
- * AUTO GENERATED FILE, DO NOT EDIT
- *
- * Generated by `membrane`
- */
-#include <stdint.h>
+  /// The file format used by `write_schema`.
+  ///
+  /// Default: `SchemaFormat::Yaml`
+  pub fn schema_format(&mut self, format: SchemaFormat) -> &mut Self {
+    return_if_error!(self);
+    self.schema_format = format;
+    self
+  }
+
+  ///
+  /// Write a versioned schema file for each namespace to `{destination}/schema/{namespace}.yaml`
+  /// (or `.json`). Each schema holds the namespace's type registry along with the signature of every
+  /// function in it. Committing these files makes every change to the generated API reviewable.
+  pub fn write_schema(&mut self) -> &mut Self {
+    return_if_error!(self);
+
+    #[cfg(all(
+      any(not(debug_assertions), feature = "skip-generate"),
+      not(feature = "generate")
+    ))]
+    return self;
+
+    let dir = self.destination.join("schema");
+    let _ = std::fs::remove_dir_all(&dir);
+    if let Err(err) = std::fs::create_dir_all(&dir) {
+      self.errors.push(format!(
+        "unable to create {}: {}",
+        dir.to_str().unwrap(),
+        err
+      ));
+      return self;
+    }
+
+    let schemas = match self.schemas() {
+      Ok(schemas) => schemas,
+      Err(err) => {
+        self.errors.push(err);
+        return self;
+      }
+    };
+
+    for schema in schemas {
+      let path = dir.join(format!(
+        "{}.{}",
+        utils::module_name(&schema.namespace),
+        self.schema_format.extension()
+      ));
+      debug!(
+        "Writing schema for namespace {} to {:?}",
+        schema.namespace, path
+      );
+
+      match schema.to_string(self.schema_format) {
+        Ok(contents) => {
+          std::fs::write(&path, contents).unwrap_or_else(|_| {
+            self
+              .errors
+              .push(format!("unable to write {}", path.to_str().unwrap()));
+          });
+        }
+        Err(err) => {
+          self.errors.push(format!(
+            "unable to serialize the schema for namespace {}: {}",
+            schema.namespace, err
+          ));
+        }
+      }
+    }
+
+    self
+  }
+
+  ///
+  /// Write a manifest of all function metadata and traced types to `path`. The manifest does not depend on the
+  /// target platform and can later be passed to `Membrane::new_from_manifest` to generate the same Dart package
+  /// without loading a library.
+  pub fn write_manifest<P: ?Sized + AsRef<Path>>(&mut self, path: &P) -> &mut Self {
+    return_if_error!(self);
+
+    #[cfg(all(
+      any(not(debug_assertions), feature = "skip-generate"),
+      not(feature = "generate")
+    ))]
+    return self;
+
+    let mut namespaces = vec![];
+    for namespace in self.namespaces.iter() {
+      let types = match self.namespaced_registry.get(namespace) {
+        Some(Ok(registry)) => registry.clone(),
+        Some(Err(err)) => {
+          self.errors.push(self.registry_error(err));
+          return self;
+        }
+        None => Registry::new(),
+      };
+
+      namespaces.push(manifest::Namespace::new(
+        namespace,
+        self
+          .namespaced_fn_registry
+          .get(namespace)
+          .map(|x| x.as_slice())
+          .unwrap_or_default(),
+        self
+          .namespaced_enum_registry
+          .get(namespace)
+          .map(|x| x.as_slice())
+          .unwrap_or_default(),
+        types,
+      ));
+    }
+
+    let path = path.as_ref();
+    match Manifest::new(namespaces, &self.symbol_prefix, &self.namespace_libraries).to_string() {
+      Ok(contents) => {
+        std::fs::write(path, contents).unwrap_or_else(|_| {
+          self
+            .errors
+            .push(format!("unable to write {}", path.to_str().unwrap()));
+        });
+      }
+      Err(err) => {
+        self
+          .errors
+          .push(format!("unable to serialize the manifest: {}", err));
+      }
+    }
+
+    self
+  }
+
+  ///
+  /// Write the graph of which namespaces borrow from which to `path`, as Graphviz DOT if the extension is `.dot`
+  /// or as JSON if it is `.json`. Borrow cycles and borrowed types which no function in the borrowing namespace
+  /// uses are logged as warnings, along with the location of each function that declared the borrow.
+  pub fn write_dependency_graph<P: ?Sized + AsRef<Path>>(&mut self, path: &P) -> &mut Self {
+    return_if_error!(self);
+    let path = path.as_ref();
+
+    let format = match path
+      .extension()
+      .and_then(|x| x.to_str())
+      .and_then(GraphFormat::from_extension)
+    {
+      Some(format) => format,
+      None => {
+        self.errors.push(format!(
+          "unable to write the dependency graph to {:?}, the file extension must be `.dot` or `.json`",
+          path
+        ));
+        return self;
+      }
+    };
+
+    let graph = self.dependency_graph();
+    graph
+      .findings()
+      .iter()
+      .for_each(|finding| warn!("{}", finding));
+
+    match graph.to_string(format) {
+      Ok(contents) => {
+        std::fs::write(path, contents).unwrap_or_else(|_| {
+          self
+            .errors
+            .push(format!("unable to write {}", path.to_str().unwrap()));
+        });
+      }
+      Err(err) => {
+        self
+          .errors
+          .push(format!("unable to serialize the dependency graph: {}", err));
+      }
+    }
+
+    self
+  }
+
+  ///
+  /// The namespace dependency graph along with any borrow cycles and unused borrows.
+  pub fn dependency_graph(&self) -> DependencyGraph {
+    self.build_dependency_graph()
+  }
+
+  ///
+  /// Allow `check_compatibility` to pass even when breaking changes are found. They are still logged.
+  ///
+  /// Can be overridden with the environment variable `MEMBRANE_ALLOW_BREAKING_CHANGES=true`.
+  pub fn allow_breaking_changes(&mut self, val: bool) -> &mut Self {
+    return_if_error!(self);
+    if !self.allow_breaking_changes {
+      self.allow_breaking_changes = val;
+    }
+    self
+  }
+
+  ///
+  /// Compare the current API against the schemas previously written by `write_schema` to `baseline_dir`.
+  /// Because bincode is positional, reordering or removing struct fields, removing enum variants, and changing
+  /// field types or function signatures all break Dart code generated from the baseline. Any such change is reported
+  /// as an error unless `allow_breaking_changes(true)` has been set. Compatible changes are logged.
+  ///
+  /// Call this before `write_schema` when both use the same directory.
+  pub fn check_compatibility<P: ?Sized + AsRef<Path>>(&mut self, baseline_dir: &P) -> &mut Self {
+    return_if_error!(self);
+
+    #[cfg(all(
+      any(not(debug_assertions), feature = "skip-generate"),
+      not(feature = "generate")
+    ))]
+    return self;
+
+    let baseline = match schema::read_schemas(baseline_dir.as_ref()) {
+      Ok(schemas) => schemas,
+      Err(err) => {
+        self.errors.push(err);
+        return self;
+      }
+    };
+
+    let current = match self.schemas() {
+      Ok(schemas) => schemas,
+      Err(err) => {
+        self.errors.push(err);
+        return self;
+      }
+    };
+
+    let mut changes = vec![];
+    for old in baseline.iter() {
+      match current.iter().find(|x| x.namespace == old.namespace) {
+        Some(new) => changes.extend(compatibility::compare(old, new)),
+        None => changes.extend(compatibility::removed(old)),
+      }
+    }
+    for new in current.iter() {
+      if !baseline.iter().any(|x| x.namespace == new.namespace) {
+        changes.extend(compatibility::added(new));
+      }
+    }
+
+    let breaking = changes
+      .iter()
+      .filter(|x| x.compatibility == Compatibility::Breaking)
+      .collect::<Vec<_>>();
+
+    changes
+      .iter()
+      .filter(|x| x.compatibility == Compatibility::Compatible)
+      .for_each(|change| info!("{}", change));
+
+    if breaking.is_empty() {
+      return self;
+    }
+
+    if self.allow_breaking_changes {
+      breaking.iter().for_each(|change| warn!("{}", change));
+    } else {
+      self.errors.push(format!(
+        r#"
+##
+#
+# Breaking API changes were found when compared to the schemas in {:?}.
+# Dart code generated from the previous schema will not be able to communicate with this library.
+#
+# {}
+#
+# If these changes are intended call `allow_breaking_changes(true)` or set MEMBRANE_ALLOW_BREAKING_CHANGES=true.
+#
+##"#,
+        baseline_dir.as_ref(),
+        breaking
+          .iter()
+          .map(|x| x.to_string())
+          .collect::<Vec<String>>()
+          .join("\n# ")
+      ));
+    }
+
+    self
+  }
+
+  ///
+  /// Write a header file for each namespace that provides the C types
+  /// needed by ffigen to generate the FFI bindings.
+  pub fn write_c_headers(&mut self) -> &mut Self {
+    return_if_error!(self);
+    let head = format!(
+      r#"/*
+ * AUTO GENERATED FILE, DO NOT EDIT
+ *
+ * Generated by `membrane`
+ */
+#include <stdint.h>
+
+#ifndef __MEMBRANE_TYPES_INCLUDED__
+#define __MEMBRANE_TYPES_INCLUDED__
+
+typedef enum MembraneMsgKind {{
+  Ok,
+  Error,
+}} MembraneMsgKind;
+
+typedef enum MembraneResponseKind {{
+  Data,
+  Panic,
+}} MembraneResponseKind;
+
+typedef struct MembraneResponse
+{{
+  uint8_t kind;
+  const void *data;
+}} MembraneResponse;
+
+uint8_t {prefix}membrane_cancel_membrane_task(const void *task_handle);
+uint8_t {prefix}membrane_free_membrane_vec(int64_t len, const void *ptr);
+char * {prefix}membrane_metadata_version();
+char * {prefix}membrane_metadata_schema_hashes();
+char * {prefix}membrane_metadata_signature_hashes();
+uint8_t {prefix}membrane_free_membrane_string(char *ptr);
+
+#endif
+"#,
+      prefix = self.symbol_prefix
+    );
+
+    let path = self.destination.join("lib/src/membrane_types.h");
+    std::fs::write(&path, head).unwrap_or_else(|_| {
+      self
+        .errors
+        .push(format!("unable to write {}", path.to_str().unwrap()));
+    });
+
+    let namespaces = self.generated_namespaces();
+    namespaces.iter().for_each(|x| {
+      self.write_header(x);
+    });
+
+    self
+  }
+
+  ///
+  /// Write all Dart classes needed by the Dart application.
+  pub fn write_api(&mut self) -> &mut Self {
+    return_if_error!(self);
+    self.validate_names();
+    return_if_error!(self);
+
+    let namespaces = self.required_namespaces();
+    namespaces.iter().for_each(|x| {
+      self.create_ffi_impl(x);
+      self.create_web_impl(x);
+      self.create_class(x.to_string());
+    });
+
+    self.create_imports();
+    self.write_schema_hashes();
+
+    if self.generated {
+      self.create_loader();
+      self.create_exceptions();
+      self.format_package();
+    }
+
+    self
+  }
+
+  ///
+  /// Invokes `dart run ffigen` with the appropriate config to generate FFI bindings.
+  pub fn write_bindings(&mut self) -> &mut Self {
+    return_if_error!(self);
+    if !self.generated {
+      return self;
+    }
+
+    self.write_ffigen_config();
+
+    let ffigen = std::process::Command::new("dart")
+      .current_dir(&self.destination)
+      .arg("--disable-analytics")
+      .arg("run")
+      .arg("ffigen")
+      .arg("--config")
+      .arg("ffigen.yaml")
+      .output()
+      .unwrap();
+
+    if ffigen.status.code() != Some(0) {
+      std::io::stderr().write_all(&ffigen.stderr).unwrap();
+      std::io::stdout().write_all(&ffigen.stdout).unwrap();
+      self
+        .errors
+        .push("dart ffigen returned an error".to_string());
+    }
+
+    self
+  }
+
+  ///
+  /// Whether any errors were found during code generation.
+  pub fn is_err(&mut self) -> bool {
+    !self.errors.is_empty()
+  }
+
+  ///
+  /// Returns all codegen errors and empties the error queue. This will prevent Membrane from
+  /// automatically exiting `1` and allow you to implement your own CLI exit handling if needed.
+  pub fn drain_errors(&mut self) -> Vec<String> {
+    self.errors.drain(..).collect()
+  }
+
+  ///
+  /// Private implementations
+  ///
+  fn schemas(&self) -> Result<Vec<Schema>, String> {
+    self
+      .namespaces
+      .iter()
+      .map(|namespace| {
+        let registry = match self.namespaced_registry.get(namespace) {
+          Some(Ok(registry)) => registry.clone(),
+          Some(Err(err)) => return Err(self.registry_error(err)),
+          None => Registry::new(),
+        };
+
+        let functions = self
+          .namespaced_fn_registry
+          .get(namespace)
+          .map(|x| x.as_slice())
+          .unwrap_or_default();
+
+        Ok(Schema::new(namespace, functions, registry))
+      })
+      .collect()
+  }
+
+  ///
+  /// The namespaces selected by `include_namespaces` and `exclude_namespaces`.
+  fn generated_namespaces(&self) -> Vec<&'static str> {
+    let matches = |patterns: &[String], namespace: &str| {
+      patterns
+        .iter()
+        .any(|pattern| utils::glob_match(pattern, namespace))
+    };
+
+    self
+      .namespaces
+      .iter()
+      .filter(|namespace| {
+        (self.include_namespaces.is_empty() || matches(&self.include_namespaces, namespace))
+          && !matches(&self.exclude_namespaces, namespace)
+      })
+      .copied()
+      .collect()
+  }
+
+  ///
+  /// The generated namespaces along with every namespace they borrow types from, directly or through
+  /// another borrowed namespace.
+  fn required_namespaces(&self) -> Vec<&'static str> {
+    let mut required = self.generated_namespaces();
+    let mut index = 0;
+
+    while index < required.len() {
+      if let Some(imports) = self.borrows.get(required[index]) {
+        for from_namespace in imports.keys() {
+          // the namespace from `shared_types_namespace` only has a registry
+          if self.namespaced_registry.contains_key(from_namespace)
+            && !required.contains(from_namespace)
+          {
+            required.push(from_namespace);
+          }
+        }
+      }
+      index += 1;
+    }
+
+    required.sort_unstable();
+    required
+  }
+
+  ///
+  /// Whether the API classes of a namespace are generated, as opposed to only its types.
+  fn generates_api(&self, namespace: &str) -> bool {
+    self.namespaced_fn_registry.contains_key(namespace)
+      && self.generated_namespaces().contains(&namespace)
+  }
+
+  fn warn_unmatched_patterns(&self, patterns: &[String]) {
+    patterns
+      .iter()
+      .filter(|pattern| {
+        !self
+          .namespaces
+          .iter()
+          .any(|namespace| utils::glob_match(pattern, namespace))
+      })
+      .for_each(|pattern| {
+        warn!(
+          "The namespace pattern `{}` did not match any namespace",
+          pattern
+        )
+      });
+  }
+
+  ///
+  /// Trace the types used by the given functions, enums, and `#[dart_type]` types into a registry per
+  /// namespace, starting from the values given by `#[dart_sample]` functions. Enums which are only
+  /// reached through the fields of other types are completed with the traces of `#[derive(DartEnum)]`
+  /// and `#[dart_enum]`.
+  pub(crate) fn trace(
+    enums: &mut [&'static DeferredEnumTrace],
+    functions: &mut [&'static DeferredTrace],
+    samples: &mut [&'static DeferredSample],
+    types: &mut [&'static DeferredTypeTrace],
+    derived_enums: &mut [&'static DeferredDerivedEnumTrace],
+  ) -> Traced {
+    // inventory's order depends on link order, so sort everything that was collected by a key
+    // which is unique to each item for the generated output to be the same on every run
+    enums.sort_by_cached_key(|e| (e.enum_data.name, e.namespace));
+    samples.sort_by_cached_key(|s| (s.type_name, s.location));
+    types.sort_by_cached_key(|t| (t.name, t.namespace));
+    derived_enums.sort_by_cached_key(|e| (e.name, e.location));
+
+    functions.sort_by_cached_key(|f| {
+      (
+        format!(
+          "{}{}{}",
+          f.function.is_stream, f.function.is_sync, f.function.fn_name
+        ),
+        f.namespace,
+      )
+    });
+
+    let mut namespaces = [
+      enums.iter().map(|x| x.namespace).collect::<Vec<&str>>(),
+      functions.iter().map(|x| x.namespace).collect::<Vec<&str>>(),
+      types.iter().map(|x| x.namespace).collect::<Vec<&str>>(),
+    ]
+    .concat();
+
+    namespaces.sort_unstable();
+    namespaces.dedup();
+
+    // every tracer starts with the formats of the samples, the sampled types which a namespace doesn't
+    // use are removed from its registry afterwards
+    let mut recorded_samples = Samples::new();
+    let (_tracer, mut errors) = encoding::sampled_tracer(samples, &mut recorded_samples);
+    let new_tracer = || encoding::sampled_tracer(samples, &mut Samples::new()).0;
+    let mut namespaced_fn_registry = BTreeMap::new();
+    let mut namespaced_enum_registry = BTreeMap::new();
+    let mut borrows: Borrows = BTreeMap::new();
+
+    // collect all the metadata about functions (without tracing them yet)
+    functions.iter().for_each(|item| {
+      namespaced_fn_registry
+        .entry(item.namespace)
+        .or_insert_with(Vec::new)
+        .push(item.function.clone());
+    });
+
+    // work out which namespaces borrow which types from other namespaces
+    namespaces.iter().for_each(|namespace| {
+      Self::create_borrows(&namespaced_fn_registry, namespace, &mut borrows);
+    });
+
+    // collect all the metadata about enums (without tracing them yet)
+    enums.iter().for_each(|item| {
+      namespaced_enum_registry
+        .entry(item.namespace)
+        .or_insert_with(Vec::new)
+        .push(item.enum_data.clone());
+    });
+
+    // the trace of every enum by the name it has in a registry, the first trace of a name wins
+    let mut enum_traces: BTreeMap<String, EnumTraceFn> = BTreeMap::new();
+    for item in derived_enums.iter() {
+      match (item.trace)(&mut new_tracer(), &mut Samples::new()) {
+        Ok(serde_reflection::Format::TypeName(name)) => {
+          enum_traces.entry(name).or_insert(item.trace);
+        }
+        Ok(_) => {}
+        Err(err) => errors.push(format!(
+          "{} It derives `DartEnum`{}.",
+          err,
+          utils::display_code_location(&[item.location])
+        )),
+      }
+    }
+    // a failed `#[dart_enum]` trace is reported by its namespace
+    for item in enums.iter() {
+      if let Ok(serde_reflection::Format::TypeName(name)) =
+        (item.trace)(&mut new_tracer(), &mut Samples::new())
+      {
+        enum_traces.entry(name).or_insert(item.trace);
+      }
+    }
+
+    // which namespaces use a nested enum is only known once they have been traced, so every namespace
+    // is traced again with the enums it was missing until none of the missing enums can be traced
+    let mut completions: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    let (namespaced_registry, mut trace_errors, namespaced_roots, function_roots) = loop {
+      let (tracers, trace_errors, namespaced_roots, function_roots) = Self::trace_round(
+        enums,
+        functions,
+        types,
+        &borrows,
+        &completions,
+        &enum_traces,
+        &new_tracer,
+        &mut recorded_samples,
+      );
+      let namespaced_registry = tracers
+        .into_iter()
+        .map(|(namespace, tracer)| (namespace, tracer.registry_unchecked()))
+        .collect::<BTreeMap<&str, Registry>>();
+
+      let mut progress = false;
+      for (namespace, registry) in namespaced_registry.iter() {
+        let completed = completions.entry(namespace).or_default();
+        for name in encoding::incomplete_enums(registry) {
+          if enum_traces.contains_key(&name) && !completed.contains(&name) {
+            completed.push(name);
+            progress = true;
+          }
+        }
+      }
+
+      if !progress {
+        break (
+          namespaced_registry,
+          trace_errors,
+          namespaced_roots,
+          function_roots,
+        );
+      }
+    };
+
+    // a `#[serde(transparent)]` newtype in a signature is passed to Dart as the type it wraps
+    for function in namespaced_fn_registry.values_mut().flatten() {
+      if let Some(roots) = function_roots.get(function.extern_c_fn_name) {
+        if let Err(err) = transparent::resolve(function, roots) {
+          errors.push(format!(
+            "{}{}.",
+            err,
+            utils::display_code_location(&[function.location])
+          ));
+        }
+      }
+    }
+
+    Traced {
+      errors,
+      namespaces,
+      namespaced_registry: namespaced_registry
+        .into_iter()
+        .map(|(key, val)| match trace_errors.remove(key) {
+          Some(err) => (key, Err(Error::Custom(err))),
+          None if samples.is_empty() => (key, encoding::check_registry(val)),
+          None => (
+            key,
+            encoding::check_registry(val).map(|registry| {
+              encoding::used_types(registry, namespaced_roots.get(key).map_or(&[], |x| x))
+            }),
+          ),
+        })
+        .collect(),
+      namespaced_fn_registry,
+      namespaced_enum_registry,
+      borrows,
+    }
+  }
+
+  ///
+  /// Trace every namespace once, tracing the enums given by `completions` into each namespace before
+  /// its functions. Returns the tracers, the first trace error of each namespace, the formats which
+  /// were traced directly, and the formats which each function was traced as.
+  #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+  fn trace_round(
+    enums: &[&'static DeferredEnumTrace],
+    functions: &[&'static DeferredTrace],
+    types: &[&'static DeferredTypeTrace],
+    borrows: &Borrows,
+    completions: &BTreeMap<&'static str, Vec<String>>,
+    enum_traces: &BTreeMap<String, EnumTraceFn>,
+    new_tracer: &dyn Fn() -> serde_reflection::Tracer,
+    recorded_samples: &mut Samples,
+  ) -> (
+    BTreeMap<&'static str, serde_reflection::Tracer>,
+    BTreeMap<&'static str, String>,
+    BTreeMap<&'static str, Vec<serde_reflection::Format>>,
+    BTreeMap<&'static str, Vec<serde_reflection::Format>>,
+  ) {
+    let mut namespaced_registry = BTreeMap::new();
+    let mut namespaced_roots: BTreeMap<&str, Vec<serde_reflection::Format>> = BTreeMap::new();
+    let mut function_roots: BTreeMap<&str, Vec<serde_reflection::Format>> = BTreeMap::new();
+    // the first trace error of each namespace replaces its registry
+    let mut trace_errors: BTreeMap<&str, String> = BTreeMap::new();
 
-#ifndef __MEMBRANE_TYPES_INCLUDED__
-#define __MEMBRANE_TYPES_INCLUDED__
+    // trace all the enums at least once
+    enums.iter().for_each(|item| {
+      // trace the enum into the borrowing namespace's registry
+      borrows.iter().for_each(|(for_namespace, from_namespaces)| {
+        if let Some((types, _location)) = from_namespaces.get(item.namespace) {
+          if types.contains(item.enum_data.name) {
+            let tracer = namespaced_registry
+              .entry(*for_namespace)
+              .or_insert_with(new_tracer);
+
+            match (item.trace)(tracer, recorded_samples) {
+              Ok(root) => namespaced_roots
+                .entry(*for_namespace)
+                .or_default()
+                .push(root),
+              Err(err) => {
+                trace_errors
+                  .entry(*for_namespace)
+                  .or_insert_with(|| Self::enum_trace_error(item, err));
+              }
+            }
+          }
+        }
+      });
 
-typedef enum MembraneMsgKind {
-  Ok,
-  Error,
-} MembraneMsgKind;
+      // trace the enum into the owning namespace's registry
+      let tracer = namespaced_registry
+        .entry(item.namespace)
+        .or_insert_with(new_tracer);
 
-typedef enum MembraneResponseKind {
-  Data,
-  Panic,
-} MembraneResponseKind;
+      match (item.trace)(tracer, recorded_samples) {
+        Ok(root) => namespaced_roots
+          .entry(item.namespace)
+          .or_default()
+          .push(root),
+        Err(err) => {
+          trace_errors
+            .entry(item.namespace)
+            .or_insert_with(|| Self::enum_trace_error(item, err));
+        }
+      }
+    });
 
-typedef struct MembraneResponse
-{
-  uint8_t kind;
-  const void *data;
-} MembraneResponse;
+    // trace the nested enums which the previous round found to be missing variants, they're reached
+    // from the functions of the namespace so they aren't roots of their own
+    completions.iter().for_each(|(namespace, names)| {
+      let tracer = namespaced_registry
+        .entry(*namespace)
+        .or_insert_with(new_tracer);
 
-uint8_t membrane_cancel_membrane_task(const void *task_handle);
-uint8_t membrane_free_membrane_vec(int64_t len, const void *ptr);
-char * membrane_metadata_version();
-uint8_t membrane_free_membrane_string(char *ptr);
+      for name in names {
+        if let Err(err) = (enum_traces[name])(tracer, recorded_samples) {
+          trace_errors.entry(*namespace).or_insert(err);
+        }
+      }
+    });
 
-#endif
-"#;
+    // trace the types which were registered without being used by a function
+    types.iter().for_each(|item| {
+      let tracer = namespaced_registry
+        .entry(item.namespace)
+        .or_insert_with(new_tracer);
 
-    let path = self.destination.join("lib/src/membrane_types.h");
-    std::fs::write(&path, head).unwrap_or_else(|_| {
-      self
-        .errors
-        .push(format!("unable to write {}", path.to_str().unwrap()));
+      match (item.trace)(tracer, recorded_samples) {
+        Ok(root) => namespaced_roots
+          .entry(item.namespace)
+          .or_default()
+          .push(root),
+        Err(err) => {
+          trace_errors.entry(item.namespace).or_insert_with(|| {
+            format!(
+              "{} It is marked with `#[dart_type(namespace = \"{}\")]`{}.",
+              err,
+              item.namespace,
+              utils::display_code_location(&[item.location])
+            )
+          });
+        }
+      }
     });
 
-    let namespaces = self.namespaces.clone();
-    namespaces.iter().for_each(|x| {
-      self.write_header(x);
+    // now that we have the enums in the registry we'll trace each of the functions
+    functions.iter().for_each(|item| {
+      let tracer = namespaced_registry
+        .entry(item.namespace)
+        .or_insert_with(new_tracer);
+
+      match (item.trace)(tracer, recorded_samples) {
+        Ok(roots) => {
+          namespaced_roots
+            .entry(item.namespace)
+            .or_default()
+            .extend(roots.iter().cloned());
+          function_roots.insert(item.function.extern_c_fn_name, roots);
+        }
+        Err(err) => {
+          trace_errors.entry(item.namespace).or_insert_with(|| {
+            format!(
+              "{} It is used by `{}`{}.",
+              err,
+              item.function.fn_name,
+              utils::display_code_location(&[item.function.location])
+            )
+          });
+        }
+      }
     });
 
-    self
+    (
+      namespaced_registry,
+      trace_errors,
+      namespaced_roots,
+      function_roots,
+    )
   }
 
-  ///
-  /// Write all Dart classes needed by the Dart application.
-  pub fn write_api(&mut self) -> &mut Self {
-    return_if_error!(self);
-    let namespaces = self.namespaces.clone();
-    namespaces.iter().for_each(|x| {
-      self.create_ffi_impl(x);
-      self.create_web_impl(x);
-      self.create_class(x.to_string());
-    });
-
-    self.create_imports();
+  fn enum_trace_error(item: &DeferredEnumTrace, err: String) -> String {
+    format!(
+      "{} It is marked with `#[dart_enum(namespace = \"{}\")]`.",
+      err, item.namespace
+    )
+  }
 
-    if self.generated {
-      self.create_loader();
-      self.create_exceptions();
-      self.format_package();
-    }
+  ///
+  /// The schema hash of every namespace which traced successfully.
+  pub(crate) fn hash_schemas(
+    namespaces: &[&'static str],
+    namespaced_registry: &BTreeMap<&'static str, serde_reflection::Result<Registry>>,
+    namespaced_fn_registry: &BTreeMap<&'static str, Vec<Function>>,
+  ) -> Vec<(&'static str, String)> {
+    namespaces
+      .iter()
+      .filter_map(|namespace| {
+        let registry = match namespaced_registry.get(namespace) {
+          Some(Ok(registry)) => registry.clone(),
+          Some(Err(_)) => return None,
+          None => Registry::new(),
+        };
+
+        let functions = namespaced_fn_registry
+          .get(namespace)
+          .map(|x| x.as_slice())
+          .unwrap_or_default();
+
+        Some((
+          *namespace,
+          Schema::new(namespace, functions, registry).hash(),
+        ))
+      })
+      .collect()
+  }
 
+  fn namespace_library(&self, namespace: &str) -> &str {
     self
+      .namespace_libraries
+      .get(namespace)
+      .unwrap_or(&self.library)
   }
 
   ///
-  /// Invokes `dart run ffigen` with the appropriate config to generate FFI bindings.
-  pub fn write_bindings(&mut self) -> &mut Self {
-    return_if_error!(self);
-    if !self.generated {
-      return self;
-    }
-
-    self.write_ffigen_config();
-
-    let ffigen = std::process::Command::new("dart")
-      .current_dir(&self.destination)
-      .arg("--disable-analytics")
-      .arg("run")
-      .arg("ffigen")
-      .arg("--config")
-      .arg("ffigen.yaml")
-      .output()
-      .unwrap();
+  /// A type borrowed from a namespace in another library is only compatible if both libraries
+  /// were compiled with the same definition of it.
+  fn check_library_borrows(&mut self) {
+    let mut errors = vec![];
+    for (for_namespace, from_namespaces) in self.borrows.iter() {
+      for (from_namespace, (types, locations)) in from_namespaces.iter() {
+        if self.namespace_library(for_namespace) == self.namespace_library(from_namespace) {
+          continue;
+        }
 
-    if ffigen.status.code() != Some(0) {
-      std::io::stderr().write_all(&ffigen.stderr).unwrap();
-      std::io::stdout().write_all(&ffigen.stdout).unwrap();
-      self
-        .errors
-        .push("dart ffigen returned an error".to_string());
+        for r#type in types.iter() {
+          let formats = (
+            self.namespaced_registry.get(for_namespace),
+            self.namespaced_registry.get(from_namespace),
+          );
+
+          if let (Some(Ok(borrower)), Some(Ok(owner))) = formats {
+            if borrower.get(*r#type) != owner.get(*r#type) {
+              errors.push(format!(
+                "`{}::{}`{} was borrowed by `{}` from the library `{}` but `{}` was compiled with a different definition of it",
+                from_namespace,
+                r#type,
+                utils::display_code_location(locations.get(r#type).map(Vec::as_slice).unwrap_or_default()),
+                for_namespace,
+                self.namespace_library(from_namespace),
+                self.namespace_library(for_namespace),
+              ));
+            }
+          }
+        }
+      }
     }
 
-    self
+    errors.sort();
+    self.errors.extend(errors);
   }
 
-  ///
-  /// Whether any errors were found during code generation.
-  pub fn is_err(&mut self) -> bool {
-    !self.errors.is_empty()
-  }
+  fn registry_error(&self, err: &Error) -> String {
+    match err {
+      Error::MissingVariants(_) => {
+        // report every missing enum of every namespace at once instead of one per run
+        let mut missing: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (namespace, registry) in self.namespaced_registry.iter() {
+          if let Err(Error::MissingVariants(names)) = registry {
+            for name in names {
+              missing.entry(name).or_default().push(namespace);
+            }
+          }
+        }
 
-  ///
-  /// Returns all codegen errors and empties the error queue. This will prevent Membrane from
-  /// automatically exiting `1` and allow you to implement your own CLI exit handling if needed.
-  pub fn drain_errors(&mut self) -> Vec<String> {
-    self.errors.drain(..).collect()
+        format!(
+          r#"
+##
+#
+# Not every variant could be traced of enums which are nested in other types and neither derive
+# membrane::DartEnum nor have the membrane::dart_enum macro applied for a namespace which owns or borrows them.
+#
+# Please add #[derive(DartEnum)] to each of these enums:
+#{}
+#
+##"#,
+          missing
+            .iter()
+            .map(|(name, namespaces)| format!("\n#   {} (used by {})", name, namespaces.join(", ")))
+            .collect::<String>()
+        )
+      }
+      err => format!("{}", err),
+    }
   }
 
-  ///
-  /// Private implementations
-  ///
   fn write_pubspec(&mut self) -> &mut Self {
     // serde-generate uses the last namespace as the pubspec name and dart doesn't
     // like that so we set a proper package name from the basename or from an explicitly given name
@@ -885,8 +2042,17 @@ headers:
   entry-points:
     - 'lib/src/membrane_types.h'
     - 'lib/src/*/*.h'
-{}
+{}{}
 "#,
+      if !self.symbol_prefix.is_empty() {
+        // the Dart bindings keep the unprefixed names
+        format!(
+          "functions:\n  rename:\n    '{}(.*)': '$1'\n",
+          self.symbol_prefix
+        )
+      } else {
+        "".to_string()
+      },
       if !self.llvm_paths.is_empty() {
         "llvm-path:".to_string()
           + &self
@@ -915,7 +2081,7 @@ headers:
     use std::io::prelude::*;
     let path = self
       .namespace_path(namespace)
-      .join(namespace.to_string() + ".h");
+      .join(utils::module_name(namespace) + ".h");
     let default = &vec![];
     let fns = self
       .namespaced_fn_registry
@@ -976,8 +2142,90 @@ export './src/membrane_exceptions.dart';";
     self
   }
 
+  ///
+  /// The schema hash of each namespace by the library that it belongs to.
+  fn library_schema_hashes(&self) -> BTreeMap<&str, Vec<(&str, String)>> {
+    let mut libraries: BTreeMap<&str, Vec<(&str, String)>> = BTreeMap::new();
+    let namespaces = self
+      .required_namespaces()
+      .into_iter()
+      // the library knows nothing of the namespace from `shared_types_namespace`
+      .filter(|namespace| self.namespaces.contains(namespace))
+      .collect::<Vec<&str>>();
+    Self::hash_schemas(
+      &namespaces,
+      &self.namespaced_registry,
+      &self.namespaced_fn_registry,
+    )
+    .into_iter()
+    .for_each(|(namespace, hash)| {
+      libraries
+        .entry(self.namespace_library(namespace))
+        .or_default()
+        .push((namespace, hash));
+    });
+
+    if libraries.is_empty() {
+      libraries.insert(&self.library, vec![]);
+    }
+
+    libraries
+  }
+
+  ///
+  /// The hash of the function signatures of each namespace by the library that it belongs to, which the
+  /// library reports whether or not it was built with `MEMBRANE_SCHEMA_HASHES`.
+  fn library_signature_hashes(&self) -> BTreeMap<&str, Vec<(&str, String)>> {
+    let signatures = self
+      .required_namespaces()
+      .into_iter()
+      .filter(|namespace| self.namespaces.contains(namespace))
+      .flat_map(|namespace| {
+        self
+          .namespaced_fn_registry
+          .get(namespace)
+          .into_iter()
+          .flatten()
+      })
+      .map(Signature::from)
+      .collect::<Vec<Signature>>();
+
+    let mut libraries: BTreeMap<&str, Vec<(&str, String)>> = BTreeMap::new();
+    for (namespace, hash) in metadata::hash_signatures(&signatures) {
+      libraries
+        .entry(self.namespace_library(namespace))
+        .or_default()
+        .push((namespace, hash));
+    }
+
+    libraries
+  }
+
+  ///
+  /// A library built with `MEMBRANE_SCHEMA_HASHES` set to the contents of its file reports these hashes.
+  fn write_schema_hashes(&mut self) -> &mut Self {
+    for (library, hashes) in self.library_schema_hashes() {
+      let hashes = hashes
+        .iter()
+        .map(|(namespace, hash)| format!("{}:{}", namespace, hash))
+        .collect::<Vec<String>>()
+        .join(",");
+      let path = self.destination.join(format!("{}.schema_hashes", library));
+      std::fs::write(path, hashes).unwrap();
+    }
+
+    self
+  }
+
   fn create_loader(&mut self) -> &mut Self {
-    let ffi_loader = loaders::create_ffi_loader(&self.library, &self.dart_config);
+    let ffi_loader = loaders::create_ffi_loader(
+      &self.library,
+      &self.library_search_paths,
+      &self.library_schema_hashes(),
+      &self.library_signature_hashes(),
+      &self.symbol_prefix,
+      &self.dart_config,
+    );
     let path = self.destination.join("lib/src/membrane_loader_ffi.dart");
     std::fs::write(path, ffi_loader).unwrap();
 
@@ -993,6 +2241,14 @@ export './membrane_loader_ffi.dart' if (dart.library.html) './membrane_loader_we
     let path = self.destination.join("lib/src/membrane_loader.dart");
     std::fs::write(path, barrel_loader).unwrap();
 
+    let public_loader = "// AUTO GENERATED FILE, DO NOT EDIT
+//
+// Generated by `membrane`
+export './src/membrane_loader.dart' show Membrane, MembraneLookup, bindingsLoaded;";
+
+    let path = self.destination.join("lib/membrane_loader.dart");
+    std::fs::write(path, public_loader).unwrap();
+
     self
   }
 
@@ -1001,27 +2257,36 @@ export './membrane_loader_ffi.dart' if (dart.library.html) './membrane_loader_we
     let path = self
       .destination
       .join("lib")
-      .join(namespace.to_string() + ".dart");
+      .join(utils::library_path(&namespace));
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+    // the library of a nested namespace is in a subdirectory of `lib`
+    let src = match namespace.matches("::").count() {
+      0 => "./src".to_string(),
+      depth => "../".repeat(depth) + "src",
+    };
 
     let head = if utils::new_style_export(&namespace, &self.dart_config) {
       format!(
         r#"// AUTO GENERATED FILE, DO NOT EDIT
 //
 // Generated by `membrane`
-export './src/{ns}_ffi.dart' if (dart.library.html) './src/{ns}_web.dart';
+export '{src}/{ns}_ffi.dart' if (dart.library.html) '{src}/{ns}_web.dart';
 
-export './src/{ns}/{ns}.dart' hide TraitHelpers;
+export '{src}/{ns}/{ns}.dart' hide TraitHelpers;
 "#,
-        ns = &namespace,
+        src = src,
+        ns = utils::module_name(&namespace),
       )
     } else {
       format!(
         r#"// AUTO GENERATED FILE, DO NOT EDIT
 //
 // Generated by `membrane`
-export './src/{ns}_ffi.dart' if (dart.library.html) './src/{ns}_web.dart';
+export '{src}/{ns}_ffi.dart' if (dart.library.html) '{src}/{ns}_web.dart';
 "#,
-        ns = &namespace,
+        src = src,
+        ns = utils::module_name(&namespace),
       )
     };
 
@@ -1033,16 +2298,14 @@ export './src/{ns}_ffi.dart' if (dart.library.html) './src/{ns}_web.dart';
 
   fn create_ffi_impl(&mut self, namespace: &str) -> &mut Self {
     use std::io::prelude::*;
+    let module = utils::module_name(namespace);
     let path = self
       .destination
       .join("lib/src")
-      .join(namespace.to_string() + "_ffi.dart");
+      .join(module.clone() + "_ffi.dart");
 
-    if !self.namespaced_fn_registry.contains_key(namespace) {
-      let head = format!(
-        "export './{ns}/{ns}.dart' hide TraitHelpers;",
-        ns = &namespace
-      );
+    if !self.generates_api(namespace) {
+      let head = format!("export './{ns}/{ns}.dart' hide TraitHelpers;", ns = &module);
       let mut buffer = std::fs::File::create(path).expect("class could not be written at path");
       buffer.write_all(head.as_bytes()).unwrap();
 
@@ -1072,7 +2335,7 @@ import './bincode/bincode.dart';
 import './ffi_bindings.dart' show MembraneMsgKind, MembraneResponse, MembraneResponseKind;
 import './{ns}/{ns}.dart';
 {export}
-final _bindings = loader.bindings;
+final _bindings = {bindings};
 final _loggingDisabled = bool.fromEnvironment('MEMBRANE_DISABLE_LOGS');
 
 @immutable
@@ -1093,21 +2356,30 @@ class {class_name}Api {{
   static final _log = {logger};
   const {class_name}Api();
 "#,
-      ns = &namespace,
+      ns = &module,
       class_name = &namespace.to_upper_camel_case(),
+      // a package merged from several libraries names the library of every namespace
+      bindings = if self.namespace_libraries.is_empty() {
+        "loader.bindings".to_string()
+      } else {
+        format!(
+          "loader.libraryBindings('{}')",
+          self.namespace_library(namespace)
+        )
+      },
       logger_path = self.dart_config.logger.import_path,
       logger = self
         .dart_config
         .logger
         .instance
-        .replace("')", &format!(".{}')", &namespace))
-        .replace("\")", &format!(".{}\")", &namespace)),
+        .replace("')", &format!(".{}')", namespace.replace("::", ".")))
+        .replace("\")", &format!(".{}\")", namespace.replace("::", "."))),
       export = if utils::new_style_export(namespace, &self.dart_config) {
         "".to_string()
       } else {
         format!(
           "\nexport './{ns}/{ns}.dart' hide TraitHelpers;\n",
-          ns = &namespace
+          ns = &module
         )
       }
     );
@@ -1128,20 +2400,18 @@ class {class_name}Api {{
 
   fn create_web_impl(&mut self, namespace: &str) -> &mut Self {
     use std::io::prelude::*;
+    let module = utils::module_name(namespace);
     let path = self
       .destination
       .join("lib/src")
-      .join(namespace.to_string() + "_web.dart");
+      .join(module.clone() + "_web.dart");
 
-    // perhaps this namespace has only enums in it and no functions
-    if !self.namespaced_fn_registry.contains_key(namespace) {
+    // perhaps this namespace has only enums in it and no functions, or only its types are generated
+    if !self.generates_api(namespace) {
       let head = if utils::new_style_export(namespace, &self.dart_config) {
         "".to_string()
       } else {
-        format!(
-          "export './{ns}/{ns}.dart' hide TraitHelpers;",
-          ns = &namespace
-        )
+        format!("export './{ns}/{ns}.dart' hide TraitHelpers;", ns = &module)
       };
       let mut buffer = std::fs::File::create(path).expect("class could not be written at path");
       buffer.write_all(head.as_bytes()).unwrap();
@@ -1159,6 +2429,7 @@ class {class_name}Api {{
       r#"// AUTO GENERATED FILE, DO NOT EDIT
 //
 // Generated by `membrane`
+import 'dart:typed_data';
 import 'package:meta/meta.dart';
 import './membrane_exceptions.dart';
 import './{ns}/{ns}.dart';
@@ -1180,15 +2451,12 @@ class {class_name}ApiError implements Exception {{
 class {class_name}Api {{
   const {class_name}Api();
 "#,
-      ns = &namespace,
+      ns = &module,
       class_name = &namespace.to_upper_camel_case(),
       export = if utils::new_style_export(namespace, &self.dart_config) {
         "".to_string()
       } else {
-        format!(
-          "export './{ns}/{ns}.dart' hide TraitHelpers;",
-          ns = namespace
-        )
+        format!("export './{ns}/{ns}.dart' hide TraitHelpers;", ns = &module)
       }
     );
 
@@ -1207,11 +2475,15 @@ class {class_name}Api {{
   }
 
   fn namespace_path(&mut self, namespace: &str) -> PathBuf {
-    self.destination.join("lib").join("src").join(namespace)
+    self
+      .destination
+      .join("lib")
+      .join("src")
+      .join(utils::module_name(namespace))
   }
 
   fn create_borrows(
-    namespaced_fn_registry: &HashMap<&str, Vec<Function>>,
+    namespaced_fn_registry: &BTreeMap<&str, Vec<Function>>,
     namespace: &'static str,
     borrows: &mut Borrows,
   ) {
@@ -1222,15 +2494,14 @@ class {class_name}Api {{
       fun
         .borrow
         .iter()
-        .map(|borrow| borrow.split("::").map(|x| x.trim()).collect::<Vec<&str>>())
-        .for_each(|borrow_list| {
-          if let [from_namespace, r#type] = borrow_list[..] {
+        .for_each(|borrow| {
+          if let Some((from_namespace, r#type)) = utils::split_borrow(borrow) {
             let imports = borrows.entry(namespace).or_default();
-            let (types, source_code_locations) = imports.entry(from_namespace).or_insert((BTreeSet::new(), HashMap::new()));
+            let (types, source_code_locations) = imports.entry(from_namespace).or_insert((BTreeSet::new(), BTreeMap::new()));
             types.insert(r#type);
             source_code_locations.entry(r#type).or_default().push(fun.location);
           } else {
-            tracing::error!("Found an invalid `borrow`: `{:?}`{location_hint}. Borrows must be of form `borrow = \"namespace::Type\"`", fun.borrow, location_hint = utils::display_code_location(Some(&vec![fun.location])));
+            tracing::error!("Found an invalid `borrow`: `{:?}`{location_hint}. Borrows must be of form `borrow = \"namespace::Type\"`", fun.borrow, location_hint = utils::display_code_location(&[fun.location]));
             exit(1);
           }
         });
@@ -1241,24 +2512,32 @@ class {class_name}Api {{
     let mut owned_types: Vec<String> = vec![];
     let mut non_owned_types: Vec<String> = vec![];
     let borrows = self.borrows.clone();
+    let generated = self.generated_namespaces();
+    let required = self.required_namespaces();
 
-    borrows.iter().for_each(|(namespace, imports)| {
+    borrows.iter().filter(|(namespace, _)| required.contains(namespace)).for_each(|(namespace, imports)| {
       imports
         .iter()
         // sort the imports in reverse order so that we can append them to existing
         // lines and end up with a descending order
         .rev()
         .for_each(|(from_namespace, (borrowed_types, borrow_locations_for_type))| {
+          if generated.contains(namespace) && !generated.contains(from_namespace) && self.namespaces.contains(from_namespace) {
+            warn!("`{ns}` borrows `{types}` from `{from_ns}`{location_hint} which is not included in the generated namespaces. The types of `{from_ns}` will be generated without its API.",
+              ns = namespace, from_ns = from_namespace, types = borrowed_types.iter().copied().collect::<Vec<&str>>().join(", "),
+              location_hint = utils::display_code_location(&borrow_locations_for_type.values().flatten().copied().collect::<Vec<&str>>()));
+          }
+
           let mut borrowed_types: Vec<String> = borrowed_types.iter().flat_map(|r#type| {
             if namespace == from_namespace {
-              self.errors.push(format!("`{ns}::{import}`{location_hint} was borrowed by `{ns}` which is a self reference", location_hint = utils::display_code_location(borrow_locations_for_type.get(r#type)), ns = namespace, import = r#type));
+              self.errors.push(format!("`{ns}::{import}`{location_hint} was borrowed by `{ns}` which is a self reference", location_hint = utils::display_code_location(borrow_locations_for_type.get(r#type).map(Vec::as_slice).unwrap_or_default()), ns = namespace, import = r#type));
             }
 
             let auto_import = self.with_child_borrows(from_namespace, r#type);
             auto_import.iter().for_each(|x| {
               if borrowed_types.contains(x.as_str()) && x != r#type {
                 warn!("{ns}::{import} was explicitly borrowed{manual_hint} but it is already implicitly borrowed because it is a subtype of `{ns}::{type}`{auto_hint}. Remove the `{ns}::{import}` borrow.",
-                ns = from_namespace, manual_hint = utils::display_code_location(borrow_locations_for_type.get(x.as_str())), import = x, r#type = r#type, auto_hint = utils::display_code_location(borrow_locations_for_type.get(r#type)));
+                ns = from_namespace, manual_hint = utils::display_code_location(borrow_locations_for_type.get(x.as_str()).map(Vec::as_slice).unwrap_or_default()), import = x, r#type = r#type, auto_hint = utils::display_code_location(borrow_locations_for_type.get(r#type).map(Vec::as_slice).unwrap_or_default()));
               }
             });
 
@@ -1273,10 +2552,12 @@ class {class_name}Api {{
           // and this is the borrowed path
           non_owned_types.extend(borrowed_types.iter().map(|ty| format!("{}::{}", from_namespace, ty)));
 
+          let module = utils::module_name(namespace);
+          let from_module = utils::module_name(from_namespace);
           let src_path = self.destination.join("lib/src");
-          let namespace_path = src_path.join(namespace);
+          let namespace_path = src_path.join(&module);
 
-          imports::inject_imports(namespace_path.join(format!("{ns}.dart", ns = namespace)),
+          imports::inject_imports(namespace_path.join(format!("{ns}.dart", ns = module)),
             |line| {
               // because CamelCasing the snake_cased `part 'central_usa.dart'` won't match the
               // acronym borrow `CentralUSA` we instead convert the borrows to snake_case to do the match
@@ -1291,7 +2572,7 @@ class {class_name}Api {{
                   line.to_string(),
                   format!(
                     "import '../{ns}/{ns}.dart' show {types};",
-                    ns = from_namespace,
+                    ns = from_module,
                     types = borrowed_types.join(",")
                   ),
                 ])
@@ -1300,7 +2581,7 @@ class {class_name}Api {{
                   line.to_string(),
                   format!(
                     "export '../{ns}/{ns}.dart' show {types};",
-                    ns = from_namespace,
+                    ns = from_module,
                     types = borrowed_types.join(",")
                   ),
                 ])
@@ -1310,14 +2591,14 @@ class {class_name}Api {{
             }).unwrap();
 
           if utils::new_style_export(namespace, &self.dart_config) {
-            imports::inject_imports(src_path.join(format!("{ns}_ffi.dart", ns = namespace)),
+            imports::inject_imports(src_path.join(format!("{ns}_ffi.dart", ns = module)),
               |line| {
-                if line.starts_with(&format!("import './{ns}/{ns}.dart'", ns = namespace)) {
+                if line.starts_with(&format!("import './{ns}/{ns}.dart'", ns = module)) {
                   Some(vec![
                     line.to_string(),
                     format!(
                       "import './{ns}/{ns}.dart' show {types};",
-                      ns = from_namespace,
+                      ns = from_module,
                       types = borrowed_types.join(",")
                     ),
                   ])
@@ -1326,14 +2607,14 @@ class {class_name}Api {{
                 }
               }).unwrap();
 
-            imports::inject_imports(src_path.join(format!("{ns}_web.dart", ns = namespace)),
+            imports::inject_imports(src_path.join(format!("{ns}_web.dart", ns = module)),
               |line| {
-                if line.starts_with(&format!("import './{ns}/{ns}.dart'", ns = namespace)) {
+                if line.starts_with(&format!("import './{ns}/{ns}.dart'", ns = module)) {
                   Some(vec![
                     line.to_string(),
                     format!(
                       "import './{ns}/{ns}.dart' show {types};",
-                      ns = from_namespace,
+                      ns = from_module,
                       types = borrowed_types.join(",")
                     ),
                   ])
@@ -1403,11 +2684,47 @@ pub enum MembraneMsgKind {
   Error,
 }
 
+///
+/// An error which isn't `Serialize`, such as `anyhow::Error` or `Box<dyn std::error::Error>`, is sent to Dart
+/// as a `MembraneErrorMessage`. The `message` is the error's `to_string()` and the `causes` are its source chain.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct MembraneErrorMessage {
+  pub message: String,
+  pub causes: Vec<String>,
+}
+
+impl MembraneErrorMessage {
+  ///
+  /// The message of an error and of each error in its source chain.
+  pub fn from_error(error: &(dyn std::error::Error + 'static)) -> Self {
+    let mut causes = vec![];
+    let mut source = error.source();
+    while let Some(cause) = source {
+      causes.push(cause.to_string());
+      source = cause.source();
+    }
+
+    Self {
+      message: error.to_string(),
+      causes,
+    }
+  }
+
+  ///
+  /// The message of a type which only implements `Display`, it has no causes.
+  pub fn from_display(error: &impl std::fmt::Display) -> Self {
+    Self {
+      message: error.to_string(),
+      causes: vec![],
+    }
+  }
+}
+
 #[doc(hidden)]
 pub struct TaskHandle(pub Box<dyn Fn()>);
 
 #[doc(hidden)]
-#[no_mangle]
+#[membrane_macro::prefixed_symbol]
 pub unsafe extern "C" fn membrane_cancel_membrane_task(task_handle: *mut TaskHandle) -> i32 {
   // turn the pointer back into a box and Rust will drop it when it goes out of scope
   let handle = Box::from_raw(task_handle);
@@ -1417,7 +2734,7 @@ pub unsafe extern "C" fn membrane_cancel_membrane_task(task_handle: *mut TaskHan
 }
 
 #[doc(hidden)]
-#[no_mangle]
+#[membrane_macro::prefixed_symbol]
 pub unsafe extern "C" fn membrane_free_membrane_vec(len: i64, ptr: *const u8) -> i32 {
   // turn the pointer back into a vec and Rust will drop it
   let _ = ::std::slice::from_raw_parts::<u8>(ptr, len as usize);
@@ -1426,7 +2743,7 @@ pub unsafe extern "C" fn membrane_free_membrane_vec(len: i64, ptr: *const u8) ->
 }
 
 #[doc(hidden)]
-#[no_mangle]
+#[membrane_macro::prefixed_symbol]
 pub unsafe extern "C" fn membrane_free_membrane_string(ptr: *mut c_char) -> i32 {
   // turn the pointer back into a CString and Rust will drop it
   let _ = ::std::ffi::CString::from_raw(ptr);
@@ -1434,6 +2751,17 @@ pub unsafe extern "C" fn membrane_free_membrane_string(ptr: *mut c_char) -> i32
   1
 }
 
+///
+/// Registers Dart's `NativeApi.postCObject` under the configured symbol prefix. When no prefix is used the
+/// Dart loader calls allo-isolate's own unprefixed `store_dart_post_cobject` instead.
+#[doc(hidden)]
+#[membrane_macro::prefixed_symbol]
+pub unsafe extern "C" fn membrane_store_dart_post_cobject(
+  ptr: allo_isolate::ffi::DartPostCObjectFnType,
+) {
+  allo_isolate::store_dart_post_cobject(ptr);
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! error {
@@ -1446,7 +2774,7 @@ macro_rules! error {
       Err(e) => {
         ::membrane::ffi_helpers::update_last_error(e);
         // silence unreachable code warnings to enable panicking on invalid data
-        #[allow(unreachable_code)]
+        #[allow(unreachable_code, clippy::diverging_sub_expression)]
         return $error;
       }
     }
@@ -1467,10 +2795,11 @@ macro_rules! cstr {
 
 #[cfg(test)]
 mod tests {
+  use std::collections::{BTreeMap, BTreeSet};
   use std::env::{remove_var, set_var};
   use std::path::PathBuf;
 
-  use crate::Membrane;
+  use crate::{Borrows, Function, Membrane, Traced};
 
   #[test]
   fn test_envars_are_used() {
@@ -1499,4 +2828,158 @@ mod tests {
     remove_var("MEMBRANE_LIBRARY");
     remove_var("MEMBRANE_LLVM_PATHS");
   }
+
+  #[test]
+  fn test_namespace_filters_keep_borrowed_namespaces() {
+    let mut borrows: Borrows = BTreeMap::new();
+    borrows
+      .entry("accounts")
+      .or_default()
+      .insert("locations", (BTreeSet::from(["Location"]), BTreeMap::new()));
+    borrows
+      .entry("locations")
+      .or_default()
+      .insert("common", (BTreeSet::from(["Arg"]), BTreeMap::new()));
+
+    let namespaces = vec![
+      "accounts",
+      "billing_v1",
+      "billing_v2",
+      "common",
+      "locations",
+      "orgs",
+    ];
+    let mut project = Membrane::from_traced(
+      vec![],
+      Traced {
+        errors: vec![],
+        namespaced_registry: namespaces
+          .iter()
+          .map(|namespace| (*namespace, Ok(Default::default())))
+          .collect(),
+        namespaces,
+        namespaced_fn_registry: BTreeMap::new(),
+        namespaced_enum_registry: BTreeMap::new(),
+        borrows,
+      },
+      vec![],
+    );
+
+    assert_eq!(project.generated_namespaces().len(), 6);
+
+    project
+      .include_namespaces(vec!["accounts", "billing_*"])
+      .exclude_namespaces(vec!["*_v1"]);
+
+    assert_eq!(
+      project.generated_namespaces(),
+      vec!["accounts", "billing_v2"]
+    );
+    assert_eq!(
+      project.required_namespaces(),
+      vec!["accounts", "billing_v2", "common", "locations"]
+    );
+  }
+
+  #[test]
+  fn test_borrows_from_missing_namespaces_are_reported() {
+    let function = Function {
+      extern_c_fn_name: "membrane_accounts_contact",
+      extern_c_fn_types: "",
+      fn_name: "contact",
+      is_stream: false,
+      is_sync: false,
+      return_type: &["Contact"],
+      error_type: &["String"],
+      args: &[],
+      namespace: "accounts",
+      disable_logging: false,
+      timeout: None,
+      borrow: &["locations::Location", "billing::Invoice"],
+      output: "",
+      dart_outer_params: "",
+      dart_transforms: "",
+      dart_inner_args: "",
+      location: "src/accounts.rs:10",
+      docblock: "",
+    };
+
+    let namespaced_fn_registry = BTreeMap::from([("accounts", vec![function])]);
+    let mut borrows: Borrows = BTreeMap::new();
+    Membrane::create_borrows(&namespaced_fn_registry, "accounts", &mut borrows);
+
+    let namespaces = vec!["accounts", "locations"];
+    let mut project = Membrane::from_traced(
+      vec![],
+      Traced {
+        errors: vec![],
+        namespaced_registry: namespaces
+          .iter()
+          .map(|namespace| (*namespace, Ok(Default::default())))
+          .collect(),
+        namespaces,
+        namespaced_fn_registry,
+        namespaced_enum_registry: BTreeMap::new(),
+        borrows,
+      },
+      vec![],
+    );
+
+    assert_eq!(project.required_namespaces(), vec!["accounts", "locations"]);
+
+    project.validate_names();
+    assert_eq!(
+      project.drain_errors(),
+      vec!["The function `contact` in `accounts` at src/accounts.rs:10 borrows `billing::Invoice` but there is no `billing` namespace. Borrow the type from the namespace which defines it."]
+    );
+  }
+
+  #[test]
+  fn test_shared_types_are_borrowed_from_the_shared_namespace() {
+    use serde_reflection::{ContainerFormat, Format, Named, Registry};
+
+    let contact = ContainerFormat::Struct(vec![Named {
+      name: "id".to_string(),
+      value: Format::I64,
+    }]);
+    let registry = |extra: &str| {
+      Ok(Registry::from([
+        ("Contact".to_string(), contact.clone()),
+        (extra.to_string(), ContainerFormat::UnitStruct),
+      ]))
+    };
+
+    let mut project = Membrane::from_traced(
+      vec![],
+      Traced {
+        errors: vec![],
+        namespaces: vec!["accounts", "orgs"],
+        namespaced_registry: BTreeMap::from([
+          ("accounts", registry("Account")),
+          ("orgs", registry("Org")),
+        ]),
+        namespaced_fn_registry: BTreeMap::new(),
+        namespaced_enum_registry: BTreeMap::new(),
+        borrows: BTreeMap::new(),
+      },
+      vec![],
+    );
+
+    project.shared_types_namespace("shared");
+    assert_eq!(project.drain_errors(), Vec::<String>::new());
+
+    for namespace in ["accounts", "orgs"] {
+      assert_eq!(
+        project.borrows[namespace]["shared"].0,
+        BTreeSet::from(["Contact"])
+      );
+    }
+    assert_eq!(
+      project.required_namespaces(),
+      vec!["accounts", "orgs", "shared"]
+    );
+
+    project.shared_types_namespace("orgs");
+    assert_eq!(project.drain_errors().len(), 1);
+  }
 }
@@ -0,0 +1,219 @@
+//! A target independent record of everything Membrane needs to generate a Dart package.
+//!
+//! Function and enum metadata is normally read from `inventory` (in the generator) or from a loaded
+//! cdylib. The manifest stores the same metadata, plus the traced registries, as JSON so that code
+//! can be generated for release builds and cross-compiled libraries.
+use crate::{Argument, Enum, Function};
+use serde::{Deserialize, Serialize};
+use serde_reflection::Registry;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Incremented whenever the layout of `Manifest` changes.
+pub const MANIFEST_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+  pub version: u32,
+  pub membrane_version: String,
+  /// The `MEMBRANE_SYMBOL_PREFIX` the libraries were built with.
+  pub symbol_prefix: String,
+  /// The library of each namespace when the package is generated from several libraries.
+  pub namespace_libraries: BTreeMap<String, String>,
+  pub namespaces: Vec<Namespace>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Namespace {
+  pub namespace: String,
+  pub functions: Vec<ManifestFunction>,
+  pub enums: Vec<ManifestEnum>,
+  pub types: Registry,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestFunction {
+  pub extern_c_fn_name: String,
+  pub extern_c_fn_types: String,
+  pub fn_name: String,
+  pub is_stream: bool,
+  pub is_sync: bool,
+  pub return_type: Vec<String>,
+  pub error_type: Vec<String>,
+  pub args: Vec<ManifestArgument>,
+  pub disable_logging: bool,
+  pub timeout: Option<i32>,
+  pub borrow: Vec<String>,
+  pub output: String,
+  pub dart_outer_params: String,
+  pub dart_transforms: String,
+  pub dart_inner_args: String,
+  pub location: String,
+  pub docblock: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestArgument {
+  pub name: String,
+  pub ty: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEnum {
+  pub name: String,
+  pub output: Option<String>,
+}
+
+impl Manifest {
+  pub fn new(
+    namespaces: Vec<Namespace>,
+    symbol_prefix: &str,
+    namespace_libraries: &BTreeMap<&str, String>,
+  ) -> Self {
+    Self {
+      version: MANIFEST_VERSION,
+      membrane_version: crate::metadata::version().to_string(),
+      symbol_prefix: symbol_prefix.to_string(),
+      namespace_libraries: namespace_libraries
+        .iter()
+        .map(|(namespace, library)| (namespace.to_string(), library.clone()))
+        .collect(),
+      namespaces,
+    }
+  }
+
+  pub fn to_string(&self) -> Result<String, String> {
+    serde_json::to_string_pretty(self)
+      .map(|json| json + "\n")
+      .map_err(|err| err.to_string())
+  }
+}
+
+impl FromStr for Manifest {
+  type Err = String;
+
+  fn from_str(input: &str) -> Result<Self, String> {
+    let manifest: Self = serde_json::from_str(input).map_err(|err| err.to_string())?;
+
+    if manifest.version != MANIFEST_VERSION {
+      return Err(format!(
+        "manifest version {} is not supported by this release of membrane (expected {})",
+        manifest.version, MANIFEST_VERSION
+      ));
+    }
+
+    Ok(manifest)
+  }
+}
+
+impl Namespace {
+  pub fn new(namespace: &str, functions: &[Function], enums: &[Enum], types: Registry) -> Self {
+    Self {
+      namespace: namespace.to_string(),
+      functions: functions.iter().map(ManifestFunction::from).collect(),
+      enums: enums
+        .iter()
+        .map(|x| ManifestEnum {
+          name: x.name.to_string(),
+          output: x.output.map(|x| x.to_string()),
+        })
+        .collect(),
+      types,
+    }
+  }
+}
+
+impl From<&Function> for ManifestFunction {
+  fn from(function: &Function) -> Self {
+    let strings = |list: &[&str]| list.iter().map(|x| x.to_string()).collect();
+
+    Self {
+      extern_c_fn_name: function.extern_c_fn_name.to_string(),
+      extern_c_fn_types: function.extern_c_fn_types.to_string(),
+      fn_name: function.fn_name.to_string(),
+      is_stream: function.is_stream,
+      is_sync: function.is_sync,
+      return_type: strings(function.return_type),
+      error_type: strings(function.error_type),
+      args: function
+        .args
+        .iter()
+        .map(|x| ManifestArgument {
+          name: x.name.to_string(),
+          ty: strings(x.ty),
+        })
+        .collect(),
+      disable_logging: function.disable_logging,
+      timeout: function.timeout,
+      borrow: strings(function.borrow),
+      output: function.output.to_string(),
+      dart_outer_params: function.dart_outer_params.to_string(),
+      dart_transforms: function.dart_transforms.to_string(),
+      dart_inner_args: function.dart_inner_args.to_string(),
+      location: function.location.to_string(),
+      docblock: function.docblock.to_string(),
+    }
+  }
+}
+
+impl ManifestFunction {
+  ///
+  /// The rest of Membrane works with the `'static` metadata produced by the macros so the
+  /// strings are leaked. A manifest is only loaded once per generator run.
+  pub(crate) fn into_function(self, namespace: &'static str) -> Function {
+    Function {
+      extern_c_fn_name: leak(self.extern_c_fn_name),
+      extern_c_fn_types: leak(self.extern_c_fn_types),
+      fn_name: leak(self.fn_name),
+      is_stream: self.is_stream,
+      is_sync: self.is_sync,
+      return_type: leak_all(self.return_type),
+      error_type: leak_all(self.error_type),
+      args: Box::leak(
+        self
+          .args
+          .into_iter()
+          .map(|x| Argument {
+            name: leak(x.name),
+            ty: leak_all(x.ty),
+          })
+          .collect::<Vec<Argument>>()
+          .into_boxed_slice(),
+      ),
+      namespace,
+      disable_logging: self.disable_logging,
+      timeout: self.timeout,
+      borrow: leak_all(self.borrow),
+      output: leak(self.output),
+      dart_outer_params: leak(self.dart_outer_params),
+      dart_transforms: leak(self.dart_transforms),
+      dart_inner_args: leak(self.dart_inner_args),
+      location: leak(self.location),
+      docblock: leak(self.docblock),
+    }
+  }
+}
+
+impl ManifestEnum {
+  pub(crate) fn into_enum(self, namespace: &'static str) -> Enum {
+    Enum {
+      name: leak(self.name),
+      output: self.output.map(leak),
+      namespace,
+    }
+  }
+}
+
+pub(crate) fn leak(value: String) -> &'static str {
+  Box::leak(value.into_boxed_str())
+}
+
+pub(crate) fn leak_all(values: Vec<String>) -> &'static [&'static str] {
+  Box::leak(
+    values
+      .into_iter()
+      .map(leak)
+      .collect::<Vec<&'static str>>()
+      .into_boxed_slice(),
+  )
+}
@@ -1,9 +1,16 @@
-use crate::{DeferredEnumTrace, DeferredTrace};
+use crate::{
+  DeferredDerivedEnumTrace, DeferredEnumTrace, DeferredSample, DeferredTrace, DeferredTypeTrace,
+  Signature,
+};
+use std::collections::BTreeMap;
 use std::ffi::CString;
 
 type Metadata = (
   Vec<&'static DeferredEnumTrace>,
   Vec<&'static DeferredTrace>,
+  Vec<&'static DeferredSample>,
+  Vec<&'static DeferredTypeTrace>,
+  Vec<&'static DeferredDerivedEnumTrace>,
   String,
   String,
 );
@@ -16,6 +23,79 @@ pub fn functions() -> Vec<&'static DeferredTrace> {
   inventory::iter::<DeferredTrace>().collect()
 }
 
+pub fn samples() -> Vec<&'static DeferredSample> {
+  inventory::iter::<DeferredSample>().collect()
+}
+
+pub fn types() -> Vec<&'static DeferredTypeTrace> {
+  inventory::iter::<DeferredTypeTrace>().collect()
+}
+
+pub fn derived_enums() -> Vec<&'static DeferredDerivedEnumTrace> {
+  inventory::iter::<DeferredDerivedEnumTrace>().collect()
+}
+
+///
+/// The hash of the function signatures of each namespace in the library as `namespace:hash` pairs. Unlike the
+/// schema hashes these are always available as the signatures are registered in every build profile.
+pub fn signature_hashes() -> String {
+  hash_signatures(inventory::iter::<Signature>())
+    .iter()
+    .map(|(namespace, hash)| format!("{}:{}", namespace, hash))
+    .collect::<Vec<String>>()
+    .join(",")
+}
+
+///
+/// Hash the signatures by namespace. The generator hashes the functions it traced the same way so that
+/// the Dart package can compare them with the library it loads.
+#[doc(hidden)]
+pub fn hash_signatures<'a>(
+  signatures: impl IntoIterator<Item = &'a Signature>,
+) -> BTreeMap<&'static str, String> {
+  let types = |types: &[&str]| types.join(" ");
+
+  let mut lines: BTreeMap<&'static str, Vec<String>> = BTreeMap::new();
+  for signature in signatures {
+    lines.entry(signature.namespace).or_default().push(format!(
+      "{}({}) -> {} | {}{}{}",
+      signature.fn_name,
+      signature
+        .args
+        .iter()
+        .map(|arg| format!("{}: {}", arg.name, types(arg.ty)))
+        .collect::<Vec<String>>()
+        .join(", "),
+      types(signature.return_type),
+      types(signature.error_type),
+      if signature.is_stream { " stream" } else { "" },
+      if signature.is_sync { " sync" } else { "" },
+    ));
+  }
+
+  lines
+    .into_iter()
+    .map(|(namespace, mut lines)| {
+      // inventory's order depends on link order
+      lines.sort();
+      (
+        namespace,
+        crate::schema::stable_hash(lines.join("\n").as_bytes()),
+      )
+    })
+    .collect()
+}
+
+///
+/// The prefix of every exported symbol. Read from `MEMBRANE_SYMBOL_PREFIX` when the generator runs, falling
+/// back to the value the generator itself was compiled with.
+pub fn symbol_prefix() -> String {
+  std::env::var("MEMBRANE_SYMBOL_PREFIX")
+    .ok()
+    .or_else(|| option_env!("MEMBRANE_SYMBOL_PREFIX").map(|x| x.to_string()))
+    .unwrap_or_default()
+}
+
 pub fn version() -> &'static str {
   const VERSION: Option<&str> = option_env!("CARGO_PKG_VERSION");
   VERSION.unwrap_or("unknown")
@@ -54,18 +134,30 @@ fn extract_metadata(
 ) -> Result<Metadata, libloading::Error> {
   unsafe {
     let lib = libloading::Library::new(lib_path)?;
+    let prefix = symbol_prefix();
+    let symbol = |name: &str| (prefix.clone() + name).into_bytes();
 
     let enums: libloading::Symbol<fn() -> Box<Vec<&'static DeferredEnumTrace>>> =
-      lib.get(b"membrane_metadata_enums")?;
+      lib.get(&symbol("membrane_metadata_enums"))?;
     let functions: libloading::Symbol<fn() -> Box<Vec<&'static DeferredTrace>>> =
-      lib.get(b"membrane_metadata_functions")?;
-    let version: libloading::Symbol<fn() -> CString> = lib.get(b"membrane_metadata_version")?;
+      lib.get(&symbol("membrane_metadata_functions"))?;
+    let samples: libloading::Symbol<fn() -> Box<Vec<&'static DeferredSample>>> =
+      lib.get(&symbol("membrane_metadata_samples"))?;
+    let types: libloading::Symbol<fn() -> Box<Vec<&'static DeferredTypeTrace>>> =
+      lib.get(&symbol("membrane_metadata_types"))?;
+    let derived_enums: libloading::Symbol<fn() -> Box<Vec<&'static DeferredDerivedEnumTrace>>> =
+      lib.get(&symbol("membrane_metadata_derived_enums"))?;
+    let version: libloading::Symbol<fn() -> CString> =
+      lib.get(&symbol("membrane_metadata_version"))?;
     let membrane_version: libloading::Symbol<fn() -> CString> =
-      lib.get(b"membrane_metadata_membrane_version")?;
+      lib.get(&symbol("membrane_metadata_membrane_version"))?;
 
     let output = (
       (*(enums)()),
       (*(functions)()),
+      (*(samples)()),
+      (*(types)()),
+      (*(derived_enums)()),
       (version)().to_str().unwrap().to_string(),
       (membrane_version)().to_str().unwrap().to_string(),
     );
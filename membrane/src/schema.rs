@@ -0,0 +1,178 @@
+//! A serializable description of a namespace's public API.
+//!
+//! A schema holds the traced type registry of a namespace along with the signature of
+//! every function exported from it. It is written by `Membrane::write_schema()` and is
+//! intended to be committed alongside the Rust source so that API changes can be reviewed.
+use crate::Function;
+use serde::{Deserialize, Serialize};
+use serde_reflection::Registry;
+use std::path::Path;
+
+/// Incremented whenever the layout of `Schema` changes in a way that older readers can't handle.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaFormat {
+  Yaml,
+  Json,
+}
+
+impl SchemaFormat {
+  pub fn extension(&self) -> &'static str {
+    match self {
+      SchemaFormat::Yaml => "yaml",
+      SchemaFormat::Json => "json",
+    }
+  }
+
+  pub fn from_extension(extension: &str) -> Option<Self> {
+    match extension {
+      "yaml" | "yml" => Some(SchemaFormat::Yaml),
+      "json" => Some(SchemaFormat::Json),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Schema {
+  pub version: u32,
+  pub namespace: String,
+  pub functions: Vec<FunctionSchema>,
+  pub types: Registry,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionSchema {
+  pub name: String,
+  pub args: Vec<ArgSchema>,
+  pub return_type: Vec<String>,
+  pub error_type: Vec<String>,
+  pub is_stream: bool,
+  pub is_sync: bool,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub timeout: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArgSchema {
+  pub name: String,
+  #[serde(rename = "type")]
+  pub ty: Vec<String>,
+}
+
+impl Schema {
+  pub fn new(namespace: &str, functions: &[Function], types: Registry) -> Self {
+    let mut functions = functions
+      .iter()
+      .map(FunctionSchema::from)
+      .collect::<Vec<FunctionSchema>>();
+    functions.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Self {
+      version: SCHEMA_VERSION,
+      namespace: namespace.to_string(),
+      functions,
+      types,
+    }
+  }
+
+  pub fn to_string(&self, format: SchemaFormat) -> Result<String, String> {
+    match format {
+      SchemaFormat::Yaml => serde_yaml::to_string(self).map_err(|err| err.to_string()),
+      SchemaFormat::Json => serde_json::to_string_pretty(self)
+        .map(|json| json + "\n")
+        .map_err(|err| err.to_string()),
+    }
+  }
+
+  ///
+  /// A stable hash of everything which affects the wire format of the namespace. The timeout is excluded
+  /// as it only changes Dart behavior. Used to detect a Dart package being paired with a different build
+  /// of the library than the one it was generated from.
+  pub fn hash(&self) -> String {
+    let mut schema = self.clone();
+    schema.functions.iter_mut().for_each(|f| f.timeout = None);
+
+    stable_hash(&serde_json::to_vec(&schema).unwrap_or_default())
+  }
+
+  pub fn from_str(input: &str, format: SchemaFormat) -> Result<Self, String> {
+    let schema: Self = match format {
+      SchemaFormat::Yaml => serde_yaml::from_str(input).map_err(|err| err.to_string())?,
+      SchemaFormat::Json => serde_json::from_str(input).map_err(|err| err.to_string())?,
+    };
+
+    if schema.version > SCHEMA_VERSION {
+      return Err(format!(
+        "schema version {} is newer than the version supported by this release of membrane ({})",
+        schema.version, SCHEMA_VERSION
+      ));
+    }
+
+    Ok(schema)
+  }
+}
+
+///
+/// Read every `.yaml` or `.json` schema file in `dir`, sorted by namespace.
+pub fn read_schemas(dir: &Path) -> Result<Vec<Schema>, String> {
+  let entries = std::fs::read_dir(dir)
+    .map_err(|err| format!("unable to read the schema directory {:?}: {}", dir, err))?;
+
+  let mut schemas = vec![];
+  for entry in entries {
+    let path = entry.map_err(|err| err.to_string())?.path();
+    let format = match path
+      .extension()
+      .and_then(|x| x.to_str())
+      .and_then(SchemaFormat::from_extension)
+    {
+      Some(format) => format,
+      None => continue,
+    };
+
+    let contents = std::fs::read_to_string(&path)
+      .map_err(|err| format!("unable to read {:?}: {}", path, err))?;
+    schemas.push(
+      Schema::from_str(&contents, format)
+        .map_err(|err| format!("invalid schema {:?}: {}", path, err))?,
+    );
+  }
+
+  schemas.sort_by(|a, b| a.namespace.cmp(&b.namespace));
+  Ok(schemas)
+}
+
+///
+/// FNV-1a, chosen over `DefaultHasher` because it is guaranteed to be stable across Rust releases.
+pub(crate) fn stable_hash(bytes: &[u8]) -> String {
+  let hash = bytes.iter().fold(0xcbf29ce484222325_u64, |hash, byte| {
+    (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+  });
+
+  format!("{:016x}", hash)
+}
+
+impl From<&Function> for FunctionSchema {
+  fn from(function: &Function) -> Self {
+    let strings = |types: &[&str]| types.iter().map(|x| x.to_string()).collect();
+
+    Self {
+      name: function.fn_name.to_string(),
+      args: function
+        .args
+        .iter()
+        .map(|arg| ArgSchema {
+          name: arg.name.to_string(),
+          ty: strings(arg.ty),
+        })
+        .collect(),
+      return_type: strings(function.return_type),
+      error_type: strings(function.error_type),
+      is_stream: function.is_stream,
+      is_sync: function.is_sync,
+      timeout: function.timeout,
+    }
+  }
+}
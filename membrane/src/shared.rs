@@ -0,0 +1,251 @@
+//! Automatic placement of types used by more than one namespace.
+//!
+//! Every namespace gets its own copy of each type it uses, so a struct returned from two namespaces
+//! is generated twice and the two Dart classes are incompatible. Rather than asking for a `borrow` on
+//! each function, `Membrane::shared_types_namespace` moves every type traced identically into several
+//! namespaces into one generated namespace and borrows it from there.
+use crate::{manifest, Enum};
+use serde_reflection::{ContainerFormat, Format, Registry, VariantFormat};
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::{debug, info};
+
+impl crate::Membrane {
+  pub(crate) fn place_shared_types(&mut self, shared_namespace: &str) {
+    let registries = self
+      .namespaces
+      .iter()
+      .filter_map(|namespace| match self.namespaced_registry.get(namespace) {
+        Some(Ok(registry)) => Some((*namespace, registry)),
+        // the trace error is reported when the package is generated
+        _ => None,
+      })
+      .collect::<Vec<(&str, &Registry)>>();
+
+    // types that are borrowed explicitly are left where the borrow puts them
+    let mut explicit = BTreeSet::new();
+    for imports in self.borrows.values() {
+      for (from_namespace, (types, _locations)) in imports.iter() {
+        if !matches!(self.namespaced_registry.get(from_namespace), Some(Ok(_))) {
+          continue;
+        }
+        for r#type in types.iter() {
+          explicit.extend(self.with_child_borrows(from_namespace, r#type));
+        }
+      }
+    }
+
+    let shared = shared_types(&registries, &explicit);
+    if shared.is_empty() {
+      info!(
+        "No types are used by more than one namespace, the `{}` namespace was not generated",
+        shared_namespace
+      );
+      return;
+    }
+
+    let shared_namespace = manifest::leak(shared_namespace.to_string());
+    let mut borrows: BTreeMap<&'static str, BTreeSet<&'static str>> = BTreeMap::new();
+    for (namespace, registry) in registries.iter() {
+      let types = registry
+        .keys()
+        .filter(|name| shared.contains_key(*name))
+        .collect::<Vec<&String>>();
+      borrows.insert(
+        namespace,
+        types
+          .iter()
+          .map(|x| manifest::leak(x.to_string()))
+          .collect(),
+      );
+    }
+
+    self
+      .namespaced_registry
+      .insert(shared_namespace, Ok(shared));
+
+    // enum output overrides follow the enum into the shared namespace
+    let enums = self
+      .namespaced_enum_registry
+      .values()
+      .flatten()
+      .filter(|x| matches!(self.namespaced_registry.get(shared_namespace), Some(Ok(registry)) if registry.contains_key(x.name)))
+      .map(|x| {
+        (
+          x.name,
+          Enum {
+            namespace: shared_namespace,
+            ..x.clone()
+          },
+        )
+      })
+      .collect::<BTreeMap<&str, Enum>>();
+    self
+      .namespaced_enum_registry
+      .insert(shared_namespace, enums.into_values().collect());
+
+    for (namespace, types) in borrows {
+      // only borrow the outermost types, their children are imported along with them
+      let children = types
+        .iter()
+        .flat_map(|r#type| {
+          self
+            .with_child_borrows(shared_namespace, r#type)
+            .into_iter()
+            .filter(move |x| x != r#type)
+        })
+        .collect::<BTreeSet<String>>();
+
+      for r#type in types.into_iter().filter(|x| !children.contains(*x)) {
+        debug!("`{}` borrows `{}::{}`", namespace, shared_namespace, r#type);
+        let imports = self.borrows.entry(namespace).or_default();
+        let (types, _locations) = imports
+          .entry(shared_namespace)
+          .or_insert((BTreeSet::new(), BTreeMap::new()));
+        types.insert(r#type);
+      }
+    }
+  }
+}
+
+///
+/// The types which are traced into more than one of the registries with an identical format, along
+/// with everything they reference.
+pub(crate) fn shared_types(registries: &[(&str, &Registry)], skip: &BTreeSet<String>) -> Registry {
+  let mut candidates: BTreeMap<String, (ContainerFormat, usize)> = BTreeMap::new();
+  let mut conflicting = BTreeSet::new();
+
+  for (namespace, registry) in registries.iter() {
+    for (name, format) in registry.iter().filter(|(name, _)| !skip.contains(*name)) {
+      match candidates.get_mut(name) {
+        Some((existing, count)) if existing == format => *count += 1,
+        Some(_) => {
+          debug!(
+            "`{}::{}` differs from another type of the same name so it is not shared",
+            namespace, name
+          );
+          conflicting.insert(name.clone());
+        }
+        None => {
+          candidates.insert(name.clone(), (format.clone(), 1));
+        }
+      }
+    }
+  }
+
+  candidates.retain(|name, (format, count)| {
+    *count > 1
+      && !conflicting.contains(name)
+      && matches!(
+        format,
+        ContainerFormat::Struct(_) | ContainerFormat::Enum(_) | ContainerFormat::NewTypeStruct(_)
+      )
+  });
+
+  // a type can only be shared if everything it references is shared too
+  loop {
+    let names = candidates.keys().cloned().collect::<BTreeSet<String>>();
+    let before = candidates.len();
+    candidates.retain(|name, (format, _)| {
+      references(format)
+        .iter()
+        .all(|x| x == name || names.contains(x))
+    });
+
+    if candidates.len() == before {
+      break;
+    }
+  }
+
+  candidates
+    .into_iter()
+    .map(|(name, (format, _))| (name, format))
+    .collect()
+}
+
+pub(crate) fn references(container: &ContainerFormat) -> Vec<String> {
+  let mut names = vec![];
+  match container {
+    ContainerFormat::UnitStruct => {}
+    ContainerFormat::NewTypeStruct(format) => format_references(format, &mut names),
+    ContainerFormat::TupleStruct(formats) => formats
+      .iter()
+      .for_each(|x| format_references(x, &mut names)),
+    ContainerFormat::Struct(fields) => fields
+      .iter()
+      .for_each(|x| format_references(&x.value, &mut names)),
+    ContainerFormat::Enum(variants) => variants.values().for_each(|variant| match &variant.value {
+      VariantFormat::NewType(format) => format_references(format, &mut names),
+      VariantFormat::Tuple(formats) => formats
+        .iter()
+        .for_each(|x| format_references(x, &mut names)),
+      VariantFormat::Struct(fields) => fields
+        .iter()
+        .for_each(|x| format_references(&x.value, &mut names)),
+      VariantFormat::Unit | VariantFormat::Variable(_) => {}
+    }),
+  }
+  names
+}
+
+pub(crate) fn format_references(format: &Format, names: &mut Vec<String>) {
+  match format {
+    Format::TypeName(name) => names.push(name.clone()),
+    Format::Option(format) | Format::Seq(format) => format_references(format, names),
+    Format::TupleArray { content, .. } => format_references(content, names),
+    Format::Map { key, value } => {
+      format_references(key, names);
+      format_references(value, names);
+    }
+    Format::Tuple(formats) => formats.iter().for_each(|x| format_references(x, names)),
+    _ => {}
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::shared_types;
+  use serde_reflection::{ContainerFormat, Format, Named, Registry};
+  use std::collections::BTreeSet;
+
+  fn structure(fields: Vec<(&str, Format)>) -> ContainerFormat {
+    ContainerFormat::Struct(
+      fields
+        .into_iter()
+        .map(|(name, value)| Named {
+          name: name.to_string(),
+          value,
+        })
+        .collect(),
+    )
+  }
+
+  #[test]
+  fn test_types_in_several_namespaces_are_shared() {
+    let contact = structure(vec![
+      ("id", Format::I64),
+      ("address", Format::TypeName("Address".to_string())),
+    ]);
+    let address = structure(vec![("line", Format::Str)]);
+
+    let a = Registry::from([
+      ("Contact".to_string(), contact.clone()),
+      ("Address".to_string(), address.clone()),
+      ("Order".to_string(), structure(vec![("id", Format::I64)])),
+    ]);
+    let b = Registry::from([
+      ("Contact".to_string(), contact.clone()),
+      ("Address".to_string(), address),
+      ("Order".to_string(), structure(vec![("id", Format::Str)])),
+    ]);
+
+    let shared = shared_types(&[("a", &a), ("b", &b)], &BTreeSet::new());
+    assert_eq!(
+      shared.keys().collect::<Vec<&String>>(),
+      vec!["Address", "Contact"]
+    );
+
+    // a type can't be shared without the types it references
+    let skip = BTreeSet::from(["Address".to_string()]);
+    assert!(shared_types(&[("a", &a), ("b", &b)], &skip).is_empty());
+  }
+}
@@ -0,0 +1,259 @@
+//! Resolves the types of a function which serialize as a different type.
+//!
+//! `#[async_dart]` and `#[sync_dart]` only see the names in a signature, so the Dart they generate for a
+//! `#[serde(transparent)]` newtype expects a class by that name. serde-reflection records the type which is
+//! actually serialized, so once a function is traced its return, error, and argument types are replaced by
+//! the traced types and the Dart for its arguments is generated again. The Rust side is unchanged because
+//! the newtype already reads and writes the bincode of the type it wraps.
+use crate::manifest::{leak, leak_all};
+use crate::{Argument, Function};
+use membrane_types::heck::ToUpperCamelCase;
+use membrane_types::rust::tuple_elements;
+use membrane_types::{dart, syn, Input};
+use serde_reflection::Format;
+use std::convert::TryFrom;
+
+///
+/// Replace the types of a function with the types it was traced as, the roots are the formats of the
+/// return type, the error type, and then each argument.
+pub(crate) fn resolve(function: &mut Function, roots: &[Format]) -> Result<(), String> {
+  if roots.len() != function.args.len() + 2 {
+    return Ok(());
+  }
+
+  if let Some(types) = resolved(function.return_type, &roots[0]) {
+    function.return_type = leak_all(types);
+  }
+
+  // an infallible function has no error type
+  if let Some(types) =
+    resolved(function.error_type, &roots[1]).filter(|_| !function.error_type.is_empty())
+  {
+    function.error_type = leak_all(types);
+  }
+
+  let resolved_args = function
+    .args
+    .iter()
+    .zip(&roots[2..])
+    .map(|(arg, format)| resolved(arg.ty, format))
+    .collect::<Vec<Option<Vec<String>>>>();
+
+  if resolved_args.iter().all(Option::is_none) {
+    return Ok(());
+  }
+
+  let mut args = vec![];
+  let mut params = vec![];
+  let mut transforms = vec![];
+  for (arg, resolved) in function.args.iter().zip(resolved_args) {
+    // Rust deserializes an argument of a named type, or an option of one, from the bincode of that type,
+    // which is also how a `Vec` of a named type is passed even when it resolves to typed data
+    let optional = arg.ty.first() == Some(&"Option");
+    let types = resolved.clone().unwrap_or_else(|| strings(arg.ty));
+    let serialized = resolved.is_some()
+      && (arg.ty.len() == optional as usize + 1 || dart::typed_data(&strs(&types)).is_some());
+    let input = || -> Result<Input, String> {
+      Ok(Input {
+        variable: arg.name.to_string(),
+        rust_type: rust_type(&strs(&types)),
+        ty: syn::parse_str(&rust_type(&strs(&types))).map_err(|err| err.to_string())?,
+      })
+    };
+    let error = |err: syn::Error| {
+      format!(
+        "The argument `{}` of `{}` can't be passed from Dart: {}",
+        arg.name, function.fn_name, err
+      )
+    };
+
+    let param: Vec<String> = dart::DartParams::try_from(&vec![input()?])
+      .map_err(error)?
+      .into();
+    params.extend(param);
+
+    if serialized {
+      transforms.push(format!(
+        "final c{} = {}",
+        arg.name.to_upper_camel_case(),
+        dart::serialized_dart_type_to_c(
+          &strs(&types)[optional as usize..],
+          arg.name,
+          optional,
+          &input()?.ty
+        )
+        .map_err(error)?
+      ));
+    } else {
+      let transform: Vec<String> = dart::DartTransforms::try_from(&vec![input()?])
+        .map_err(error)?
+        .into();
+      transforms.extend(transform);
+    }
+
+    args.push(Argument {
+      name: arg.name,
+      ty: leak_all(types),
+    });
+  }
+
+  function.args = Box::leak(args.into_boxed_slice());
+  function.dart_outer_params = leak(params.join(", "));
+  function.dart_transforms = leak(transforms.join(";\n    "));
+
+  Ok(())
+}
+
+///
+/// The types of a format when they differ from the declared types, `None` when they're the same or when
+/// the format has no flattened representation.
+fn resolved(declared: &[&str], format: &Format) -> Option<Vec<String>> {
+  let mut types = vec![];
+  if !flatten_format(format, &mut types) {
+    return None;
+  }
+
+  // a `&str` is traced as the `String` it's received as
+  let declared = declared
+    .iter()
+    .map(|x| if *x == "&str" { "String" } else { x })
+    .collect::<Vec<&str>>();
+  let types = declared_collections(&declared, &strs(&types));
+
+  (declared != strs(&types)).then_some(types)
+}
+
+///
+/// Restore the collections which are traced as another format, a set is traced as a sequence and an
+/// array as a tuple of its elements.
+fn declared_collections(declared: &[&str], traced: &[&str]) -> Vec<String> {
+  match (declared, traced) {
+    (["Array", len, elem @ ..], ["(", ..]) => match tuple_elements(traced).first() {
+      Some(first) => [strings(&["Array", len]), declared_collections(elem, first)].concat(),
+      None => strings(traced),
+    },
+    (["(", ..], ["(", ..]) => {
+      let declared_elements = tuple_elements(declared);
+      let traced_elements = tuple_elements(traced);
+      if declared_elements.len() != traced_elements.len() {
+        return strings(traced);
+      }
+      let mut types = strings(&["("]);
+      for (i, (declared, traced)) in declared_elements.iter().zip(traced_elements).enumerate() {
+        if i > 0 {
+          types.push(",".to_string());
+        }
+        types.extend(declared_collections(declared, traced));
+      }
+      types.push(")".to_string());
+      types
+    }
+    (["Set", declared @ ..], ["Vec", traced @ ..]) => {
+      [strings(&["Set"]), declared_collections(declared, traced)].concat()
+    }
+    (["Map", _, declared @ ..], ["Map", key, traced @ ..]) => [
+      strings(&["Map", key]),
+      declared_collections(declared, traced),
+    ]
+    .concat(),
+    ([wrapper, declared @ ..], [traced_wrapper, traced @ ..])
+      if !declared.is_empty() && wrapper == traced_wrapper =>
+    {
+      [strings(&[wrapper]), declared_collections(declared, traced)].concat()
+    }
+    _ => strings(traced),
+  }
+}
+
+fn flatten_format(format: &Format, types: &mut Vec<String>) -> bool {
+  let ty = match format {
+    Format::TypeName(name) => name.as_str(),
+    Format::Unit => "()",
+    Format::Bool => "bool",
+    Format::I8 => "i8",
+    Format::I16 => "i16",
+    Format::I32 => "i32",
+    Format::I64 => "i64",
+    Format::I128 => "i128",
+    Format::U8 => "u8",
+    Format::U16 => "u16",
+    Format::U32 => "u32",
+    Format::U64 => "u64",
+    Format::U128 => "u128",
+    Format::F32 => "f32",
+    Format::F64 => "f64",
+    Format::Char => "char",
+    Format::Str => "String",
+    Format::Bytes => {
+      types.extend(["Vec".to_string(), "u8".to_string()]);
+      return true;
+    }
+    Format::Option(format) => {
+      types.push("Option".to_string());
+      return flatten_format(format, types);
+    }
+    Format::Seq(format) => {
+      types.push("Vec".to_string());
+      return flatten_format(format, types);
+    }
+    Format::Map { key, value } => {
+      let mut key_types = vec![];
+      if !flatten_format(key, &mut key_types) || key_types.len() != 1 {
+        return false;
+      }
+      types.push("Map".to_string());
+      types.extend(key_types);
+      return flatten_format(value, types);
+    }
+    Format::Tuple(formats) => {
+      types.push("(".to_string());
+      for (i, format) in formats.iter().enumerate() {
+        if i > 0 {
+          types.push(",".to_string());
+        }
+        if !flatten_format(format, types) {
+          return false;
+        }
+      }
+      types.push(")".to_string());
+      return true;
+    }
+    Format::TupleArray { content, size } => {
+      types.extend(["Array".to_string(), size.to_string()]);
+      return flatten_format(content, types);
+    }
+    Format::Variable(_) => return false,
+  };
+
+  types.push(ty.to_string());
+  true
+}
+
+///
+/// The Rust type of flattened types, which is parsed to generate the Dart for an argument.
+fn rust_type(types: &[&str]) -> String {
+  match types {
+    [] => String::new(),
+    [ty] => ty.to_string(),
+    ["Map", key, rest @ ..] => format!("HashMap<{}, {}>", key, rust_type(rest)),
+    ["Set", rest @ ..] => format!("HashSet<{}>", rust_type(rest)),
+    ["Array", len, rest @ ..] => format!("[{}; {}]", rust_type(rest), len),
+    ["(", ..] => format!(
+      "({},)",
+      tuple_elements(types)
+        .into_iter()
+        .map(rust_type)
+        .collect::<Vec<String>>()
+        .join(", ")
+    ),
+    [ty, rest @ ..] => format!("{}<{}>", ty, rust_type(rest)),
+  }
+}
+
+fn strings(types: &[&str]) -> Vec<String> {
+  types.iter().map(|x| x.to_string()).collect()
+}
+
+fn strs(types: &[String]) -> Vec<&str> {
+  types.iter().map(|x| x.as_str()).collect()
+}
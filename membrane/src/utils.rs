@@ -1,5 +1,4 @@
-use crate::SourceCodeLocation;
-use allo_isolate::Isolate;
+use allo_isolate::{IntoDart, Isolate, ZeroCopyBuffer};
 use serde::ser::Serialize;
 
 pub fn send<T: Serialize, E: Serialize>(isolate: Isolate, result: Result<T, E>) -> bool {
@@ -21,13 +20,64 @@ pub fn send<T: Serialize, E: Serialize>(isolate: Isolate, result: Result<T, E>)
   }
 }
 
-pub(crate) fn display_code_location(location: Option<&Vec<SourceCodeLocation>>) -> String {
-  match location {
-    Some(loc) if !loc.is_empty() => {
-      let last = loc.len() - 1;
+///
+/// Send a `Vec` of bytes or numbers as Dart typed data which is handed over without a copy. It's sent in a
+/// list so that Dart can tell it apart from the bincode of an error.
+pub fn send_typed_data<T, E: Serialize>(isolate: Isolate, result: Result<Vec<T>, E>) -> bool
+where
+  ZeroCopyBuffer<Vec<T>>: IntoDart,
+{
+  match result {
+    Ok(value) => isolate.post((ZeroCopyBuffer(value),)),
+    Err(err) => send::<(), E>(isolate, Err(err)),
+  }
+}
+
+///
+/// Read the typed data that Dart passes as the native-endian bytes of its elements.
+pub fn typed_data<T: TypedDataElement>(bytes: &[u8]) -> Vec<T> {
+  // the bytes may not be aligned for `T` so each element is decoded rather than cast
+  bytes
+    .chunks_exact(std::mem::size_of::<T>())
+    .map(T::from_ne_slice)
+    .collect()
+}
+
+mod sealed {
+  pub trait Sealed {}
+}
+
+///
+/// The element types of a `Vec` which is passed as Dart typed data.
+pub trait TypedDataElement: sealed::Sealed + Sized {
+  #[doc(hidden)]
+  fn from_ne_slice(bytes: &[u8]) -> Self;
+}
+
+macro_rules! typed_data_element {
+  ($($ty:ty),*) => {
+    $(
+      impl sealed::Sealed for $ty {}
+
+      impl TypedDataElement for $ty {
+        fn from_ne_slice(bytes: &[u8]) -> Self {
+          <$ty>::from_ne_bytes(std::convert::TryInto::try_into(bytes).unwrap())
+        }
+      }
+    )*
+  };
+}
+
+typed_data_element!(u8, i8, u16, i16, u32, i32, i64, f32, f64);
+
+pub(crate) fn display_code_location(locations: &[&str]) -> String {
+  match locations {
+    [] => String::new(),
+    _ => {
+      let last = locations.len() - 1;
       format!(
         " at {}",
-        loc
+        locations
           .iter()
           .enumerate()
           .map(|(index, path)| {
@@ -54,7 +104,6 @@ pub(crate) fn display_code_location(location: Option<&Vec<SourceCodeLocation>>)
           .join(" ")
       )
     }
-    _ => String::new(),
   }
 }
 
@@ -62,27 +111,205 @@ pub(crate) fn new_style_export<S: AsRef<str>>(namespace: S, config: &crate::Dart
   !config.v1_import_style.contains(&namespace.as_ref())
 }
 
+///
+/// The flat name of a namespace's module under `lib/src`, nested namespaces such as `accounts::admin`
+/// become `accounts_admin`.
+pub(crate) fn module_name(namespace: &str) -> String {
+  namespace.replace("::", "_")
+}
+
+///
+/// The path of a namespace's public library relative to `lib`, nested namespaces such as `accounts::admin`
+/// are written to `accounts/admin.dart`.
+pub(crate) fn library_path(namespace: &str) -> std::path::PathBuf {
+  let mut segments = namespace.split("::").collect::<Vec<&str>>();
+  let file = format!("{}.dart", segments.pop().unwrap_or_default());
+  segments
+    .into_iter()
+    .collect::<std::path::PathBuf>()
+    .join(file)
+}
+
+///
+/// Split a borrow of the form `namespace::Type` or `parent::child::Type` into its namespace and type.
+pub(crate) fn split_borrow(borrow: &str) -> Option<(&str, &str)> {
+  borrow
+    .rsplit_once("::")
+    .map(|(namespace, r#type)| (namespace.trim(), r#type.trim()))
+    .filter(|(namespace, r#type)| {
+      !r#type.is_empty() && namespace.split("::").all(|x| !x.trim().is_empty())
+    })
+}
+
+pub(crate) fn env_list(key: &str) -> Vec<String> {
+  match std::env::var_os(key) {
+    Some(config) => config
+      .into_string()
+      .unwrap()
+      .split(&[',', ' '][..])
+      .map(|x| x.to_string())
+      .collect(),
+    None => vec![],
+  }
+}
+
+///
+/// Match a namespace against a pattern where `*` matches any sequence of characters and `?` matches one.
+pub(crate) fn glob_match(pattern: &str, value: &str) -> bool {
+  let pattern = pattern.chars().collect::<Vec<char>>();
+  let value = value.chars().collect::<Vec<char>>();
+  let (mut p, mut v) = (0, 0);
+  // the most recent `*` and the position in the value that it has consumed up to
+  let mut star = None;
+
+  while v < value.len() {
+    match pattern.get(p) {
+      Some('*') => {
+        star = Some((p, v));
+        p += 1;
+      }
+      Some(c) if *c == '?' || *c == value[v] => {
+        p += 1;
+        v += 1;
+      }
+      _ => match star {
+        Some((star_p, star_v)) => {
+          star = Some((star_p, star_v + 1));
+          p = star_p + 1;
+          v = star_v + 1;
+        }
+        None => return false,
+      },
+    }
+  }
+
+  pattern[p..].iter().all(|c| *c == '*')
+}
+
 #[cfg(test)]
 mod tests {
-  use super::display_code_location;
+  use super::{
+    display_code_location, glob_match, library_path, send_typed_data, split_borrow, typed_data,
+  };
+  use allo_isolate::ffi::{DartCObject, DartCObjectType, DartTypedDataType};
+  use allo_isolate::Isolate;
+  use std::path::PathBuf;
+  use std::sync::Mutex;
 
   #[test]
-  fn test_source_code_display_location() {
-    assert_eq!(display_code_location(Some(&vec![])), "");
-
+  fn test_nested_namespaces() {
+    assert_eq!(library_path("accounts"), PathBuf::from("accounts.dart"));
+    assert_eq!(
+      library_path("accounts::admin"),
+      PathBuf::from("accounts/admin.dart")
+    );
+    assert_eq!(split_borrow("common::Arg"), Some(("common", "Arg")));
     assert_eq!(
-      display_code_location(Some(&vec!["app.rs:30"])),
-      " at app.rs:30"
+      split_borrow("accounts::admin::Role"),
+      Some(("accounts::admin", "Role"))
     );
+    assert_eq!(split_borrow("Arg"), None);
+    assert_eq!(split_borrow("accounts::::Role"), None);
+  }
+
+  #[test]
+  fn test_glob_match() {
+    assert!(glob_match("accounts", "accounts"));
+    assert!(!glob_match("accounts", "accounts_v2"));
+    assert!(glob_match("accounts*", "accounts_v2"));
+    assert!(glob_match("*", "orgs"));
+    assert!(glob_match("*_v?", "billing_v2"));
+    assert!(!glob_match("*_v?", "billing_v20"));
+    assert!(glob_match("b*ing*", "billing"));
+    assert!(!glob_match("b*x", "billing"));
+  }
+
+  #[test]
+  fn test_source_code_display_location() {
+    assert_eq!(display_code_location(&[]), "");
+
+    assert_eq!(display_code_location(&["app.rs:30"]), " at app.rs:30");
 
     assert_eq!(
-      display_code_location(Some(&vec!["app.rs:30", "foo.rs:10"])),
+      display_code_location(&["app.rs:30", "foo.rs:10"]),
       " at app.rs:30 and foo.rs:10"
     );
 
     assert_eq!(
-      display_code_location(Some(&vec!["app.rs:30", "foo.rs:10", "bar.rs:5"])),
+      display_code_location(&["app.rs:30", "foo.rs:10", "bar.rs:5"]),
       " at app.rs:30, foo.rs:10, and bar.rs:5"
     );
   }
+
+  #[test]
+  fn test_typed_data_from_unaligned_bytes() {
+    // Dart passes the native-endian bytes of its elements after the 8 byte length of the payload, which
+    // leaves them at any alignment
+    let values = [1.5f64, -0.25, f64::MAX];
+    let mut bytes = vec![0u8];
+    bytes.extend(values.iter().flat_map(|x| x.to_ne_bytes()));
+    assert_eq!(typed_data::<f64>(&bytes[1..]), values);
+
+    let values = [i16::MIN, -1, 0, i16::MAX];
+    let mut bytes = vec![0u8];
+    bytes.extend(values.iter().flat_map(|x| x.to_ne_bytes()));
+    assert_eq!(typed_data::<i16>(&bytes[1..]), values);
+
+    assert_eq!(typed_data::<u32>(&[]), Vec::<u32>::new());
+  }
+
+  // whether the message was wrapped in a list, and the type and bytes of the typed data in it
+  static POSTED: Mutex<Vec<(bool, DartTypedDataType, Vec<u8>)>> = Mutex::new(vec![]);
+
+  unsafe extern "C" fn post(_port: i64, message: *mut DartCObject) -> bool {
+    let (in_list, message) = match (*message).ty {
+      DartCObjectType::DartArray => {
+        let array = (*message).value.as_array;
+        assert_eq!(array.length, 1);
+        (true, &**array.values)
+      }
+      _ => (false, &*message),
+    };
+    assert!(matches!(message.ty, DartCObjectType::DartExternalTypedData));
+
+    let data = message.value.as_external_typed_data;
+    let size = match data.ty {
+      DartTypedDataType::Uint8 => 1,
+      DartTypedDataType::Float32 => 4,
+      ty => panic!("unexpected typed data {:?}", ty),
+    };
+    let bytes = std::slice::from_raw_parts(data.data, data.length as usize * size).to_vec();
+    POSTED.lock().unwrap().push((in_list, data.ty, bytes));
+
+    // not delivered, so allo-isolate frees the buffers
+    false
+  }
+
+  #[test]
+  fn test_send_typed_data() {
+    unsafe { allo_isolate::store_dart_post_cobject(post) };
+    let isolate = Isolate::new(0);
+
+    let values = vec![1.5f32, -2.0, f32::MIN_POSITIVE];
+    send_typed_data::<f32, String>(isolate, Ok(values.clone()));
+    send_typed_data::<f32, String>(isolate, Err("failed".to_string()));
+
+    let posted = std::mem::take(&mut *POSTED.lock().unwrap());
+    assert_eq!(posted.len(), 2);
+
+    // the elements are sent in a list as they are, which is read back the way Dart passes typed data
+    let (in_list, ty, bytes) = &posted[0];
+    assert!(in_list);
+    assert_eq!(*ty, DartTypedDataType::Float32);
+    assert_eq!(typed_data::<f32>(bytes), values);
+
+    // an error is the bincode of the message kind and the error
+    let (in_list, ty, bytes) = &posted[1];
+    assert!(!in_list);
+    assert_eq!(*ty, DartTypedDataType::Uint8);
+    assert_eq!(
+      bytes,
+      &crate::bincode::serialize(&(crate::MembraneMsgKind::Error as u8, "failed")).unwrap()
+    );
+  }
 }
@@ -0,0 +1,418 @@
+//! Checks for Rust names which can't be generated as-is in Dart.
+//!
+//! Generated Dart uses the camel cased names of functions, arguments, and fields, and it defines
+//! classes of its own next to the traced types. A field named `is` or a struct named `AccountsApi`
+//! compiles in Rust but produces Dart which doesn't, so these are reported along with the locations of
+//! the functions that use them before any files are written.
+use crate::{utils, Membrane};
+use membrane_types::heck::{ToLowerCamelCase, ToUpperCamelCase};
+use serde_reflection::{ContainerFormat, Format, Named, Registry, VariantFormat};
+use std::collections::BTreeMap;
+
+const RESERVED_WORDS: &[&str] = &[
+  "assert", "break", "case", "catch", "class", "const", "continue", "default", "do", "else",
+  "enum", "extends", "false", "final", "finally", "for", "if", "in", "is", "new", "null",
+  "rethrow", "return", "super", "switch", "this", "throw", "true", "try", "var", "void", "while",
+  "with",
+];
+
+// built-in identifiers may name a field but not a class
+const BUILT_IN_IDENTIFIERS: &[&str] = &[
+  "abstract",
+  "as",
+  "covariant",
+  "deferred",
+  "dynamic",
+  "export",
+  "extension",
+  "external",
+  "factory",
+  "Function",
+  "get",
+  "implements",
+  "import",
+  "interface",
+  "late",
+  "library",
+  "mixin",
+  "operator",
+  "part",
+  "required",
+  "set",
+  "static",
+  "typedef",
+];
+
+// every Dart enum has these members so a c-style variant can't use them
+const ENUM_MEMBERS: &[&str] = &["index", "values"];
+
+// classes which are generated or imported alongside the traced types
+const GENERATED_CLASSES: &[&str] = &[
+  "BcsDeserializer",
+  "BcsSerializer",
+  "BinaryDeserializer",
+  "BinarySerializer",
+  "BincodeDeserializer",
+  "BincodeSerializer",
+  "Bytes",
+  "Int128",
+  "Membrane",
+  "MembraneCancellationFailedException",
+  "MembraneException",
+  "MembraneLibraryLoadException",
+  "MembraneLookup",
+  "MembraneMemoryFreeFailedException",
+  "MembraneMsgKind",
+  "MembraneResponse",
+  "MembraneResponseKind",
+  "MembraneRustPanicException",
+  "MembraneUnknownResponseVariantException",
+  "MembraneVersionMismatchException",
+  "Slice",
+  "TraitHelpers",
+  "Uint128",
+  "Uint64",
+  "Unit",
+];
+
+impl Membrane {
+  ///
+  /// Report every name in the required namespaces which would produce invalid Dart, along with borrows
+  /// from namespaces which don't exist.
+  pub(crate) fn validate_names(&mut self) {
+    let errors = self.name_collisions();
+    self.errors.extend(errors);
+    let errors = self.missing_borrows();
+    self.errors.extend(errors);
+  }
+
+  ///
+  /// Borrows of the required namespaces from a namespace which has no types to borrow, which
+  /// `required_namespaces` can't pull in.
+  fn missing_borrows(&self) -> Vec<String> {
+    let required = self.required_namespaces();
+
+    let mut errors = vec![];
+    for (namespace, imports) in self.borrows.iter() {
+      if !required.contains(namespace) {
+        continue;
+      }
+
+      for (from_namespace, (types, _)) in imports.iter() {
+        if self.namespaced_registry.contains_key(from_namespace) {
+          continue;
+        }
+
+        let fns = self
+          .namespaced_fn_registry
+          .get(namespace)
+          .into_iter()
+          .flatten();
+        for fun in fns {
+          for r#type in types.iter() {
+            if fun
+              .borrow
+              .iter()
+              .any(|x| utils::split_borrow(x) == Some((from_namespace, r#type)))
+            {
+              errors.push(format!(
+                "The function `{}` in `{}`{} borrows `{}::{}` but there is no `{}` namespace. Borrow the type from the namespace which defines it.",
+                fun.fn_name,
+                namespace,
+                utils::display_code_location(&[fun.location]),
+                from_namespace,
+                r#type,
+                from_namespace
+              ));
+            }
+          }
+        }
+      }
+    }
+
+    errors
+  }
+
+  fn name_collisions(&self) -> Vec<String> {
+    let namespaces = self.required_namespaces();
+
+    let mut api_classes = BTreeMap::new();
+    for namespace in namespaces.iter().filter(|x| self.generates_api(x)) {
+      let class_name = namespace.to_upper_camel_case();
+      let description = format!("a class of the `{}` API", namespace);
+      api_classes.insert(format!("{}Api", class_name), description.clone());
+      api_classes.insert(format!("{}ApiError", class_name), description);
+    }
+
+    let mut errors = vec![];
+    for namespace in namespaces {
+      if let Some(Ok(registry)) = self.namespaced_registry.get(namespace) {
+        errors.extend(self.type_collisions(namespace, registry, &api_classes));
+      }
+
+      if self.generates_api(namespace) {
+        errors.extend(self.function_collisions(namespace));
+      }
+    }
+
+    errors
+  }
+
+  fn type_collisions(
+    &self,
+    namespace: &str,
+    registry: &Registry,
+    api_classes: &BTreeMap<String, String>,
+  ) -> Vec<String> {
+    let locations = self.type_locations(namespace);
+    let owner = |name: &str| {
+      format!(
+        "`{}::{}`{}",
+        namespace,
+        name,
+        utils::display_code_location(locations.get(name).map(Vec::as_slice).unwrap_or_default())
+      )
+    };
+
+    let mut classes = GENERATED_CLASSES
+      .iter()
+      .map(|x| (x.to_string(), "a class generated by Membrane".to_string()))
+      .chain(api_classes.clone())
+      .collect::<BTreeMap<String, String>>();
+    // the class generated for each variant of a non c-style enum
+    let mut variant_classes = BTreeMap::new();
+
+    for (name, format) in registry.iter() {
+      if let ContainerFormat::Enum(variants) = format {
+        if self.is_c_style_enum(namespace, name, variants) {
+          classes.insert(
+            format!("{}Extension", name),
+            format!("the extension of `{}::{}`", namespace, name),
+          );
+        } else {
+          for variant in variants.values() {
+            variant_classes.insert(format!("{}{}", name, variant.name), (name, &variant.name));
+          }
+        }
+      }
+    }
+
+    let mut errors = vec![];
+    for (name, format) in registry.iter() {
+      if RESERVED_WORDS.contains(&name.as_str()) || BUILT_IN_IDENTIFIERS.contains(&name.as_str()) {
+        errors.push(format!(
+          "{} can't be used as a Dart class because `{}` is a reserved word in Dart. Rename it with `#[serde(rename = \"...\")]`.",
+          owner(name),
+          name
+        ));
+      } else if let Some(description) = classes.get(name) {
+        errors.push(format!(
+          "{} has the same name as {}. Rename it with `#[serde(rename = \"...\")]`.",
+          owner(name),
+          description
+        ));
+      } else if let Some((enum_name, variant)) = variant_classes.get(name) {
+        errors.push(format!(
+          "{} has the same name as the class generated for the `{}` variant of `{}::{}`. Rename one of them with `#[serde(rename = \"...\")]`.",
+          owner(name),
+          variant,
+          namespace,
+          enum_name
+        ));
+      }
+
+      match format {
+        ContainerFormat::Struct(fields) => errors.extend(field_collisions(&owner(name), fields)),
+        ContainerFormat::Enum(variants) if self.is_c_style_enum(namespace, name, variants) => {
+          for variant in variants.values() {
+            let dart_name = variant.name.to_lower_camel_case();
+            if RESERVED_WORDS.contains(&dart_name.as_str())
+              || ENUM_MEMBERS.contains(&dart_name.as_str())
+            {
+              errors.push(format!(
+                "The `{}` variant of {} is named `{}` in Dart which is reserved in a Dart enum. Rename it with `#[serde(rename = \"...\")]`.",
+                variant.name,
+                owner(name),
+                dart_name
+              ));
+            }
+          }
+        }
+        ContainerFormat::Enum(variants) => {
+          for variant in variants.values() {
+            if let VariantFormat::Struct(fields) = &variant.value {
+              errors.extend(field_collisions(
+                &format!("the `{}` variant of {}", variant.name, owner(name)),
+                fields,
+              ));
+            }
+          }
+        }
+        _ => {}
+      }
+    }
+
+    for (class, (enum_name, variant)) in variant_classes.iter() {
+      if let Some(description) = classes.get(class) {
+        errors.push(format!(
+          "The `{}` variant of {} is generated as the Dart class `{}` which has the same name as {}. Rename it with `#[serde(rename = \"...\")]`.",
+          variant,
+          owner(enum_name),
+          class,
+          description
+        ));
+      }
+    }
+
+    errors
+  }
+
+  fn function_collisions(&self, namespace: &str) -> Vec<String> {
+    let fns = self
+      .namespaced_fn_registry
+      .get(namespace)
+      .map(|x| x.as_slice())
+      .unwrap_or_default();
+
+    let mut errors = vec![];
+    for (dart_name, group) in dart_names(fns.iter().map(|fun| (fun.fn_name, fun.location))) {
+      if RESERVED_WORDS.contains(&dart_name.as_str()) {
+        errors.push(format!(
+          "The function `{}` in `{}`{} can't be called from Dart because `{}` is a reserved word in Dart. Rename the function.",
+          dart_name,
+          namespace,
+          utils::display_code_location(&group),
+          dart_name
+        ));
+      } else if group.len() > 1 {
+        errors.push(format!(
+          "`{}` has more than one function named `{}` in Dart{}. Rename all but one of them.",
+          namespace,
+          dart_name,
+          utils::display_code_location(&group)
+        ));
+      }
+    }
+
+    for fun in fns {
+      let owner = format!(
+        "`{}` in `{}`{}",
+        fun.fn_name,
+        namespace,
+        utils::display_code_location(&[fun.location])
+      );
+
+      for (dart_name, args) in dart_names(fun.args.iter().map(|arg| (arg.name, arg.name))) {
+        if RESERVED_WORDS.contains(&dart_name.as_str()) {
+          errors.push(format!(
+            "The argument `{}` of {} is named `{}` in Dart which is a reserved word. Rename the argument.",
+            args[0], owner, dart_name
+          ));
+        } else if args.len() > 1 {
+          errors.push(format!(
+            "The arguments {} of {} are all named `{}` in Dart. Rename all but one of them.",
+            quoted(&args),
+            owner,
+            dart_name
+          ));
+        }
+      }
+    }
+
+    errors
+  }
+
+  fn is_c_style_enum(
+    &self,
+    namespace: &str,
+    name: &str,
+    variants: &BTreeMap<u32, Named<VariantFormat>>,
+  ) -> bool {
+    let output = self
+      .namespaced_enum_registry
+      .get(namespace)
+      .into_iter()
+      .flatten()
+      .find(|x| x.name == name)
+      .and_then(|x| x.output);
+
+    variants.values().all(|x| x.value == VariantFormat::Unit)
+      && output.map_or(self.c_style_enums, |x| x == "enum")
+  }
+}
+
+///
+/// The fields of a struct or struct variant which are reserved or collide once they are camel cased.
+fn field_collisions(owner: &str, fields: &[Named<Format>]) -> Vec<String> {
+  dart_names(fields.iter().map(|x| (x.name.as_str(), x.name.as_str())))
+    .into_iter()
+    .filter_map(|(dart_name, names)| {
+      if RESERVED_WORDS.contains(&dart_name.as_str()) {
+        Some(format!(
+          "The field `{}` of {} is named `{}` in Dart which is a reserved word. Rename it with `#[serde(rename = \"...\")]`.",
+          names[0], owner, dart_name
+        ))
+      } else if names.len() > 1 {
+        Some(format!(
+          "The fields {} of {} are all named `{}` in Dart. Rename all but one of them.",
+          quoted(&names),
+          owner,
+          dart_name
+        ))
+      } else {
+        None
+      }
+    })
+    .collect()
+}
+
+///
+/// Group values by the Dart name of their Rust name.
+fn dart_names<'a, T>(names: impl Iterator<Item = (&'a str, T)>) -> BTreeMap<String, Vec<T>> {
+  let mut grouped: BTreeMap<String, Vec<T>> = BTreeMap::new();
+  for (name, value) in names {
+    grouped
+      .entry(name.to_lower_camel_case())
+      .or_default()
+      .push(value);
+  }
+  grouped
+}
+
+fn quoted(names: &[&str]) -> String {
+  names
+    .iter()
+    .map(|x| format!("`{}`", x))
+    .collect::<Vec<String>>()
+    .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::field_collisions;
+  use serde_reflection::{Format, Named};
+
+  fn fields(names: &[&str]) -> Vec<Named<Format>> {
+    names
+      .iter()
+      .map(|name| Named {
+        name: name.to_string(),
+        value: Format::I64,
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_field_collisions() {
+    assert!(field_collisions("`accounts::User`", &fields(&["id", "is_admin"])).is_empty());
+
+    assert_eq!(
+      field_collisions("`accounts::User`", &fields(&["is", "user_id", "userId", "new"])),
+      vec![
+        "The field `is` of `accounts::User` is named `is` in Dart which is a reserved word. Rename it with `#[serde(rename = \"...\")]`.",
+        "The field `new` of `accounts::User` is named `new` in Dart which is a reserved word. Rename it with `#[serde(rename = \"...\")]`.",
+        "The fields `user_id`, `userId` of `accounts::User` are all named `userId` in Dart. Rename all but one of them.",
+      ]
+    );
+  }
+}
@@ -0,0 +1,57 @@
+mod mock;
+mod test_utils;
+use crate::mock::RUNTIME;
+
+mod test {
+  use super::test_utils::{assert_contains_part, generate};
+
+  mod app {
+    use membrane::{async_dart, sync_dart};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize)]
+    pub struct Contact {
+      pub id: i64,
+    }
+
+    #[async_dart(namespace = "arrays")]
+    pub async fn hash(
+      key: [u8; 32],
+      salt: Option<[u8; 16]>,
+      points: [Option<i64>; 3],
+    ) -> Result<[u8; 32], String> {
+      let _ = (salt, points);
+      Ok(key)
+    }
+
+    #[sync_dart(namespace = "arrays")]
+    pub fn pair(contacts: Vec<[Contact; 2]>) -> Result<[String; 2], String> {
+      Ok([contacts.len().to_string(), String::new()])
+    }
+  }
+
+  #[test]
+  fn test_arrays_are_passed_without_a_length() {
+    let (api, header, _) = generate("arrays");
+
+    assert_contains_part(
+      &api,
+      "Future<Uint8List> hash({required Uint8List key, Uint8List? salt, required List<int?> points})",
+    );
+    assert_contains_part(
+      &api,
+      "List<String> pair({required List<List<Contact>> contacts})",
+    );
+
+    // the length is checked in Dart since bincode doesn't write it
+    assert_contains_part(&api, "if (key.length != 32) {");
+    assert_contains_part(&api, "throw MembraneArrayLengthException(");
+    assert!(!api.contains("serializer.serializeLength(key.length);"));
+    assert_contains_part(&api, "return Uint8List.fromList(List.generate(32, (_i) {");
+
+    assert_contains_part(
+      &header,
+      "const uint8_t *key, const uint8_t *salt, const uint8_t *points",
+    );
+  }
+}
@@ -0,0 +1,56 @@
+mod mock;
+mod test_utils;
+use crate::mock::RUNTIME;
+
+mod test {
+  use super::test_utils::{assert_contains_part, generate};
+
+  mod app {
+    use membrane::{async_dart, sync_dart};
+    use serde::{Deserialize, Serialize};
+    use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+    #[derive(Deserialize, Serialize)]
+    pub struct Contact {
+      pub id: i64,
+    }
+
+    #[async_dart(namespace = "collections")]
+    pub async fn contacts(
+      ids: HashSet<i64>,
+      names: Option<BTreeMap<String, Option<String>>>,
+    ) -> Result<HashMap<String, Contact>, String> {
+      let _ = (ids, names);
+      todo!()
+    }
+
+    #[sync_dart(namespace = "collections")]
+    pub fn tags(by_id: BTreeMap<i64, BTreeSet<String>>) -> Result<BTreeSet<String>, String> {
+      Ok(by_id.into_values().flatten().collect())
+    }
+  }
+
+  #[test]
+  fn test_maps_and_sets_are_passed_as_dart_collections() {
+    let (api, header, schema) = generate("collections");
+
+    assert_contains_part(
+      &api,
+      "Future<Map<String, Contact>> contacts({required Set<int> ids, Map<String, String?>? names})",
+    );
+    assert_contains_part(
+      &api,
+      "Set<String> tags({required Map<int, Set<String>> byId})",
+    );
+
+    // each entry is written as its key followed by its value
+    assert_contains_part(&api, "names.forEach((key, value) {");
+    assert_contains_part(&api, "serializer.serializeOptionTag(value != null);");
+    assert_contains_part(&api, "final map = <String, Contact>{};");
+    assert_contains_part(&api, "final set = <String>{};");
+
+    assert_contains_part(&header, "const uint8_t *ids, const uint8_t *names");
+
+    assert_contains_part(&schema, "Contact:");
+  }
+}
@@ -0,0 +1,60 @@
+mod mock;
+use crate::mock::RUNTIME;
+
+mod test {
+  use membrane::Membrane;
+
+  mod app {
+    use membrane::{async_dart, dart_enum, dart_type};
+    use serde::{Deserialize, Serialize};
+
+    #[dart_type(namespace = "payloads")]
+    #[derive(Deserialize, Serialize)]
+    pub struct Preferences {
+      pub theme: Theme,
+      pub page_size: u32,
+    }
+
+    #[dart_enum(namespace = "payloads")]
+    #[derive(Deserialize, Serialize)]
+    pub enum Theme {
+      Light,
+      Dark,
+    }
+
+    #[dart_type(namespace = "messages")]
+    #[derive(Deserialize, Serialize)]
+    pub enum Event {
+      Created { id: i64 },
+      Deleted(i64),
+    }
+
+    #[async_dart(namespace = "messages")]
+    pub async fn events() -> Result<Vec<u8>, String> {
+      todo!()
+    }
+  }
+
+  #[test]
+  fn test_types_which_no_function_uses_are_generated() {
+    let destination = std::env::temp_dir().join("membrane_dart_type_test");
+    let _ = std::fs::remove_dir_all(&destination);
+
+    let mut membrane = Membrane::new();
+    membrane
+      .package_destination_dir(&destination)
+      .write_schema();
+    assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+    // a namespace can consist of only registered types
+    let payloads = std::fs::read_to_string(destination.join("schema/payloads.yaml")).unwrap();
+    assert!(payloads.contains("Preferences:"));
+    assert!(payloads.contains("Theme:"));
+
+    let messages = std::fs::read_to_string(destination.join("schema/messages.yaml")).unwrap();
+    assert!(messages.contains("Event:"));
+    assert!(!messages.contains("Preferences:"));
+
+    let _ = std::fs::remove_dir_all(destination);
+  }
+}
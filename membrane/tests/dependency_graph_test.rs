@@ -0,0 +1,83 @@
+mod mock;
+use crate::mock::RUNTIME;
+
+mod test {
+  use membrane::graph::DependencyGraph;
+  use membrane::Membrane;
+  use pretty_assertions::assert_eq;
+
+  mod app {
+    use membrane::async_dart;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize)]
+    pub struct Contact {
+      pub id: i64,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    pub struct Org {
+      pub id: i64,
+    }
+
+    #[async_dart(namespace = "orgs")]
+    pub async fn org() -> Result<Org, String> {
+      todo!()
+    }
+
+    #[async_dart(namespace = "orgs", borrow = "contacts::Contact")]
+    pub async fn owner() -> Result<Contact, String> {
+      todo!()
+    }
+
+    #[async_dart(namespace = "contacts")]
+    pub async fn contact() -> Result<Contact, String> {
+      todo!()
+    }
+
+    #[async_dart(namespace = "contacts", borrow = "orgs::Org")]
+    pub async fn employer() -> Result<Org, String> {
+      todo!()
+    }
+
+    #[async_dart(namespace = "reports", borrow = "orgs::Org")]
+    pub async fn count() -> Result<i64, String> {
+      todo!()
+    }
+  }
+
+  #[test]
+  fn test_dependency_graph_reports_cycles_and_unused_borrows() {
+    let destination = std::env::temp_dir().join("membrane_dependency_graph_test");
+    std::fs::create_dir_all(&destination).unwrap();
+
+    let mut membrane = Membrane::new();
+    membrane
+      .write_dependency_graph(&destination.join("graph.json"))
+      .write_dependency_graph(&destination.join("graph.dot"));
+    assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+    let graph: DependencyGraph =
+      serde_json::from_str(&std::fs::read_to_string(destination.join("graph.json")).unwrap())
+        .unwrap();
+    assert_eq!(graph, membrane.dependency_graph());
+    assert_eq!(graph.namespaces, vec!["contacts", "orgs", "reports"]);
+    assert_eq!(graph.edges.len(), 3);
+
+    let findings = graph.findings();
+    assert_eq!(findings.len(), 2);
+    assert!(findings[0].starts_with(
+      "The namespaces `contacts` -> `orgs` -> `contacts` borrow from each other in a cycle at "
+    ));
+    assert!(findings[0].contains("dependency_graph_test.rs"));
+    assert!(findings[1].starts_with("`orgs::Org` was borrowed by `reports` at "));
+
+    let dot = std::fs::read_to_string(destination.join("graph.dot")).unwrap();
+    assert!(dot.contains("  \"reports\" -> \"orgs\" [label=\"Org\"];\n"));
+
+    membrane.write_dependency_graph(&destination.join("graph.txt"));
+    assert_eq!(membrane.drain_errors().len(), 1);
+
+    let _ = std::fs::remove_dir_all(destination);
+  }
+}
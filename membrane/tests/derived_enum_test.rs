@@ -0,0 +1,90 @@
+mod mock;
+use crate::mock::RUNTIME;
+
+mod test {
+  use membrane::schema::SchemaFormat;
+  use membrane::Membrane;
+
+  mod app {
+    use membrane::{async_dart, dart_enum, sync_dart, DartEnum};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize)]
+    pub struct Order {
+      pub id: i64,
+      pub status: Status,
+      pub priority: Priority,
+    }
+
+    #[derive(Deserialize, Serialize, DartEnum)]
+    pub enum Status {
+      Pending,
+      Shipped { tracking: String },
+      Cancelled(Reason),
+    }
+
+    // only reachable through a variant of `Status` which isn't its first
+    #[derive(Deserialize, Serialize, DartEnum)]
+    pub enum Reason {
+      OutOfStock,
+      Customer { note: String },
+      Fraud,
+    }
+
+    #[dart_enum(namespace = "orders")]
+    #[derive(Deserialize, Serialize)]
+    pub enum Priority {
+      Low,
+      High,
+    }
+
+    #[async_dart(namespace = "orders")]
+    pub async fn order(_id: i64) -> Result<Order, String> {
+      todo!()
+    }
+
+    #[sync_dart(namespace = "reports")]
+    pub fn cancelled() -> Result<Vec<Order>, String> {
+      todo!()
+    }
+  }
+
+  #[test]
+  fn test_nested_enums_are_completed_in_every_namespace() {
+    let destination = std::env::temp_dir().join("membrane_derived_enum_test");
+    let _ = std::fs::remove_dir_all(&destination);
+
+    let mut membrane = Membrane::new();
+    membrane
+      .package_destination_dir(&destination)
+      // YAML can't hold the nested enums
+      .schema_format(SchemaFormat::Json)
+      .write_schema();
+    assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+    // `reports` neither owns nor borrows `Priority` but gets every variant of it from `#[dart_enum]`
+    for namespace in ["orders", "reports"] {
+      let schema =
+        std::fs::read_to_string(destination.join(format!("schema/{}.json", namespace))).unwrap();
+      for name in [
+        "Pending",
+        "Shipped",
+        "Cancelled",
+        "OutOfStock",
+        "Customer",
+        "Fraud",
+        "Low",
+        "High",
+      ] {
+        assert!(
+          schema.contains(&format!("\"{}\"", name)),
+          "{} is missing {}",
+          namespace,
+          name
+        );
+      }
+    }
+
+    let _ = std::fs::remove_dir_all(destination);
+  }
+}
@@ -0,0 +1,140 @@
+mod mock;
+use crate::mock::RUNTIME;
+
+mod test {
+  use membrane::Membrane;
+  use pretty_assertions::assert_eq;
+  use std::collections::BTreeMap;
+  use std::path::{Path, PathBuf};
+
+  mod app {
+    use membrane::{async_dart, dart_enum, sync_dart};
+    use serde::{Deserialize, Serialize};
+
+    #[dart_enum(namespace = "tags")]
+    #[derive(Deserialize, Serialize)]
+    pub enum Tag {
+      Red,
+      Blue,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    pub struct Item {
+      pub id: i64,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    pub struct Order {
+      pub item: Item,
+      pub tag: Tag,
+    }
+
+    #[async_dart(namespace = "tags")]
+    pub async fn tag() -> Result<Tag, String> {
+      todo!()
+    }
+
+    #[async_dart(namespace = "items")]
+    pub async fn item() -> Result<Item, String> {
+      todo!()
+    }
+
+    #[async_dart(namespace = "items", borrow = "tags::Tag")]
+    pub async fn item_tag(_id: i64) -> Result<Tag, String> {
+      todo!()
+    }
+
+    #[sync_dart(namespace = "orders", borrow = "items::Item", borrow = "tags::Tag")]
+    pub fn order(_id: i64) -> Result<Order, String> {
+      todo!()
+    }
+
+    #[async_dart(namespace = "orders", timeout = 100, borrow = "tags::Tag")]
+    pub async fn tagged(_tag: bool) -> Result<Vec<Tag>, String> {
+      todo!()
+    }
+  }
+
+  // the parts of the serde-generate library file which `write_api` splices imports into
+  fn write_module(destination: &Path, namespace: &str, types: &[&str]) {
+    let dir = destination.join("lib/src").join(namespace);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+      dir.join(format!("{}.dart", namespace)),
+      format!(
+        "library {}_types;\n\nimport '../serde/serde.dart';\nimport '../bincode/bincode.dart';\n\nexport '../serde/serde.dart';\n\npart 'trait_helpers.dart';\n{}\n",
+        namespace,
+        types
+          .iter()
+          .map(|x| format!("part '{}.dart';", x))
+          .collect::<Vec<String>>()
+          .join("\n")
+      ),
+    )
+    .unwrap();
+  }
+
+  fn generate(destination: &Path) -> BTreeMap<PathBuf, Vec<u8>> {
+    let _ = std::fs::remove_dir_all(destination);
+    write_module(destination, "items", &["item", "tag"]);
+    write_module(destination, "orders", &["item", "order", "tag"]);
+    write_module(destination, "tags", &["tag"]);
+
+    let mut membrane = Membrane::new();
+    membrane
+      .package_destination_dir(destination)
+      .write_schema()
+      .write_manifest(&destination.join("membrane.json"))
+      .write_dependency_graph(&destination.join("graph.json"))
+      .write_c_headers()
+      .write_api();
+    assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+    let mut files = BTreeMap::new();
+    let mut pending = vec![destination.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+      for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+          pending.push(path);
+        } else {
+          files.insert(
+            path.strip_prefix(destination).unwrap().to_path_buf(),
+            std::fs::read(&path).unwrap(),
+          );
+        }
+      }
+    }
+    files
+  }
+
+  #[test]
+  fn test_generating_twice_produces_identical_output() {
+    let destination = std::env::temp_dir().join("membrane_deterministic_output_test");
+
+    let first = generate(&destination.join("first"));
+    let second = generate(&destination.join("second"));
+
+    assert_eq!(
+      first.keys().collect::<Vec<_>>(),
+      second.keys().collect::<Vec<_>>()
+    );
+    for (path, contents) in first.iter() {
+      assert_eq!(
+        String::from_utf8_lossy(contents),
+        String::from_utf8_lossy(&second[path]),
+        "{:?} differs between runs",
+        path
+      );
+    }
+
+    // the borrowed imports are spliced in a fixed order
+    let orders =
+      String::from_utf8_lossy(&first[Path::new("lib/src/orders/orders.dart")]).to_string();
+    assert!(orders.contains(
+      "import '../bincode/bincode.dart';\nimport '../items/items.dart' show Item;\nimport '../tags/tags.dart' show Tag,TagExtension;"
+    ));
+
+    let _ = std::fs::remove_dir_all(destination);
+  }
+}
@@ -0,0 +1,63 @@
+mod mock;
+use crate::mock::RUNTIME;
+
+mod test {
+  use membrane::Membrane;
+
+  mod app {
+    use membrane::async_dart;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize)]
+    pub struct Contact {
+      pub id: i64,
+      #[serde(skip_serializing_if = "Option::is_none")]
+      pub name: Option<String>,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(untagged)]
+    pub enum Filter {
+      Id(i64),
+      Name(String),
+    }
+
+    #[async_dart(namespace = "skipped")]
+    pub async fn contact() -> Result<Contact, String> {
+      todo!()
+    }
+
+    #[async_dart(namespace = "untagged")]
+    pub async fn search(_filter: Filter) -> Result<i64, String> {
+      todo!()
+    }
+  }
+
+  #[test]
+  fn test_types_which_bincode_cannot_round_trip_are_reported() {
+    let destination = std::env::temp_dir().join("membrane_encoding_test");
+
+    let mut membrane = Membrane::new();
+    membrane
+      .package_destination_dir(&destination)
+      .write_schema();
+    let errors = membrane.drain_errors();
+
+    assert_eq!(errors.len(), 1, "{:#?}", errors);
+    assert!(errors[0]
+      .starts_with("`encoding_test::test::app::Contact` doesn't serialize to the same bincode"));
+    assert!(errors[0].contains("It is used by `contact` at "));
+    assert!(errors[0].contains("encoding_test.rs"));
+
+    membrane
+      .include_namespaces(vec!["untagged"])
+      .create_pub_package();
+    let errors = membrane.drain_errors();
+
+    assert_eq!(errors.len(), 1, "{:#?}", errors);
+    assert!(errors[0].starts_with("`encoding_test::test::app::Filter` can't be used from Dart because deserializing it calls `deserialize_any`"));
+    assert!(errors[0].contains("It is used by `search` at "));
+
+    let _ = std::fs::remove_dir_all(destination);
+  }
+}
@@ -0,0 +1,71 @@
+mod mock;
+mod test_utils;
+use crate::mock::RUNTIME;
+
+mod test {
+  use super::test_utils::{assert_contains_part, generate};
+
+  mod app {
+    use membrane::{async_dart, sync_dart};
+    use std::fmt;
+
+    #[derive(Debug)]
+    pub struct DisplayError;
+
+    impl fmt::Display for DisplayError {
+      fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "display error")
+      }
+    }
+
+    #[async_dart(namespace = "error_message")]
+    pub async fn load(id: i64) -> anyhow::Result<String> {
+      Err(anyhow::anyhow!("missing {}", id).context("loading"))
+    }
+
+    #[async_dart(namespace = "error_message")]
+    pub fn watch() -> impl futures::Stream<Item = Result<i64, anyhow::Error>> {
+      futures::stream::iter(vec![])
+    }
+
+    #[sync_dart(namespace = "error_message")]
+    pub fn parse(value: String) -> Result<i64, Box<dyn std::error::Error + Send + Sync>> {
+      Ok(value.parse()?)
+    }
+
+    #[async_dart(namespace = "error_message", error_display = true)]
+    pub async fn display() -> Result<(), DisplayError> {
+      Err(DisplayError)
+    }
+  }
+
+  #[test]
+  fn test_error_messages() {
+    let (api, _, schema) = generate("error_message");
+
+    assert_eq!(
+      api
+        .matches("throw ErrorMessageApiError(MembraneErrorMessage.deserialize(deserializer));")
+        .count(),
+      4
+    );
+
+    assert_contains_part(&schema, "MembraneErrorMessage:");
+    assert_contains_part(&schema, "- causes:");
+  }
+
+  #[test]
+  fn test_error_message_causes() {
+    let error = app::parse("one".to_string()).unwrap_err();
+    let message = membrane::MembraneErrorMessage::from_error(&*error);
+    assert_eq!(message.message, "invalid digit found in string");
+    assert!(message.causes.is_empty());
+
+    let error = anyhow::anyhow!("missing")
+      .context("loading")
+      .context("request");
+    let message = membrane::MembraneErrorMessage::from_error(&*error);
+    assert_eq!(message.message, "request");
+    assert_eq!(message.causes, vec!["loading", "missing"]);
+  }
+}
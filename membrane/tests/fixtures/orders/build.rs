@@ -0,0 +1,3 @@
+fn main() {
+  membrane::build::hide_unprefixed_symbols();
+}
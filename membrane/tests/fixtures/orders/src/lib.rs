@@ -0,0 +1,13 @@
+use membrane::sync_dart;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize)]
+pub struct Order {
+  pub id: i64,
+  pub total: f64,
+}
+
+#[sync_dart(namespace = "orders")]
+pub fn order(id: i64) -> Result<Order, String> {
+  Ok(Order { id, total: 0.0 })
+}
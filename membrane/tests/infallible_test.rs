@@ -0,0 +1,57 @@
+mod mock;
+mod test_utils;
+use crate::mock::RUNTIME;
+
+mod test {
+  use super::test_utils::{assert_contains_part, generate};
+
+  mod app {
+    use membrane::{async_dart, sync_dart};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize)]
+    pub struct AppError {
+      pub code: i32,
+    }
+
+    pub type Result<T> = std::result::Result<T, AppError>;
+
+    #[async_dart(namespace = "infallible")]
+    pub async fn ping(name: String) -> String {
+      name
+    }
+
+    #[sync_dart(namespace = "infallible")]
+    pub fn reset() {}
+
+    #[async_dart(namespace = "infallible")]
+    pub fn ticks() -> impl futures::Stream<Item = i64> {
+      futures::stream::iter(vec![])
+    }
+
+    #[async_dart(namespace = "infallible", error = "AppError")]
+    pub async fn fetch(id: i64) -> Result<Option<String>> {
+      Err(AppError { code: id as i32 })
+    }
+  }
+
+  #[test]
+  fn test_infallible_functions_and_result_aliases() {
+    let (api, _, schema) = generate("infallible");
+
+    assert_contains_part(&api, "Future<String> ping({required String name})");
+    assert_contains_part(&api, "void reset()");
+    assert_contains_part(&api, "Stream<int> ticks()");
+    assert_contains_part(&api, "Future<String?> fetch({required int id})");
+
+    // only the alias has an error to throw
+    assert_eq!(api.matches("throw InfallibleApiError(").count(), 1);
+    assert_contains_part(
+      &api,
+      "throw InfallibleApiError(AppError.deserialize(deserializer));",
+    );
+    assert_contains_part(&api, "Received an error from `ping` which is infallible");
+
+    assert_contains_part(&schema, "AppError:");
+  }
+}
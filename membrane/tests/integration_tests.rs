@@ -203,6 +203,261 @@ import '../locations/locations.dart' show GDPR, Location;
     );
   }
 
+  #[test]
+  #[serial]
+  fn multiple_cdylibs_with_the_same_namespace() {
+    let path = dart_example_path();
+
+    build_lib(&path.to_path_buf(), &mut vec![]);
+
+    let (lib, copy) = if cfg!(target_os = "macos") {
+      ("libexample.dylib", "libexample_copy.dylib")
+    } else {
+      ("libexample.so", "libexample_copy.so")
+    };
+    std::fs::copy(path.join(lib), path.join(copy)).unwrap();
+
+    let mut membrane = Membrane::new_from_cdylibs(&[path.join(lib), path.join(copy)]);
+    let errors = membrane.drain_errors();
+    let _ = std::fs::remove_file(path.join(copy));
+
+    assert!(!errors.is_empty());
+    assert!(errors
+      .iter()
+      .all(|x| x.contains("A namespace may only be defined in one library")));
+    assert!(errors
+      .iter()
+      .any(|x| x.starts_with("The namespace `accounts` was found in both")));
+  }
+
+  #[test]
+  #[serial]
+  fn multiple_cdylibs_merged_into_one_package() {
+    let path = dart_example_path();
+    let destination = env::temp_dir().join("membrane_merged_cdylibs_test");
+    let _ = std::fs::remove_dir_all(&destination);
+
+    build_lib(&path.to_path_buf(), &mut vec![]);
+    let orders = build_fixture("orders", &mut vec![]);
+
+    let lib = if cfg!(target_os = "macos") {
+      "libexample.dylib"
+    } else {
+      "libexample.so"
+    };
+
+    let mut membrane = Membrane::new_from_cdylibs(&[path.join(lib), orders]);
+    assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+    // normally created by `create_pub_package`
+    for namespace in ["accounts", "common", "locations", "orgs", "orders"] {
+      let dir = destination.join("lib/src").join(namespace);
+      std::fs::create_dir_all(&dir).unwrap();
+      std::fs::write(dir.join(format!("{}.dart", namespace)), "").unwrap();
+    }
+    membrane
+      .package_destination_dir(&destination)
+      .using_lib("libexample")
+      .write_api();
+    assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+    let api = |namespace: &str| {
+      read_to_string(destination.join(format!("lib/src/{}_ffi.dart", namespace))).unwrap()
+    };
+    assert!(api("accounts").contains("loader.libraryBindings('libexample')"));
+    assert!(api("orders").contains("loader.libraryBindings('liborders')"));
+    assert!(!api("orders").contains("loader.bindings"));
+
+    // the generated loader checks each library against the hashes of its own namespaces
+    let hashes = |library: &str| {
+      read_to_string(destination.join(format!("{}.schema_hashes", library))).unwrap()
+    };
+    assert!(hashes("libexample").starts_with("accounts:"));
+    assert!(!hashes("libexample").contains("orders:"));
+    assert!(hashes("liborders").starts_with("orders:"));
+    assert!(!hashes("liborders").contains(","));
+
+    let _ = std::fs::remove_dir_all(destination);
+  }
+
+  #[test]
+  #[serial]
+  fn symbol_prefix() {
+    let path = dart_example_path();
+    let destination = env::temp_dir().join("membrane_symbol_prefix_test");
+
+    build_lib(
+      &path.to_path_buf(),
+      &mut vec!["--config", "env.MEMBRANE_SYMBOL_PREFIX=\"acme_\""],
+    );
+
+    let lib = if cfg!(target_os = "macos") {
+      "libexample.dylib"
+    } else {
+      "libexample.so"
+    };
+
+    unsafe {
+      let library = libloading::Library::new(path.join(lib)).unwrap();
+      for symbol in [
+        "acme_membrane_accounts_contact",
+        "acme_membrane_free_membrane_vec",
+        "acme_membrane_store_dart_post_cobject",
+        "acme_membrane_metadata_version",
+        "acme_membrane_metadata_signature_hashes",
+      ] {
+        assert!(library.get::<fn()>(symbol.as_bytes()).is_ok(), "{}", symbol);
+      }
+      assert!(library.get::<fn()>(b"membrane_free_membrane_vec").is_err());
+      assert!(library.get::<fn()>(b"store_dart_post_cobject").is_err());
+      assert!(library.get::<fn()>(b"free_zero_copy_buffer_u8").is_err());
+    }
+
+    env::set_var("MEMBRANE_SYMBOL_PREFIX", "acme_");
+    let mut membrane = Membrane::new_from_cdylib(&path.join(lib));
+    env::remove_var("MEMBRANE_SYMBOL_PREFIX");
+
+    // normally created by `create_pub_package`
+    for namespace in ["accounts", "common", "locations", "orgs"] {
+      std::fs::create_dir_all(destination.join("lib/src").join(namespace)).unwrap();
+    }
+    membrane
+      .package_destination_dir(&destination)
+      .write_c_headers();
+    assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+    let types = read_to_string(destination.join("lib/src/membrane_types.h")).unwrap();
+    assert!(
+      types.contains("uint8_t acme_membrane_free_membrane_vec(int64_t len, const void *ptr);")
+    );
+    let accounts = read_to_string(destination.join("lib/src/accounts/accounts.h")).unwrap();
+    assert!(accounts.contains("MembraneResponse acme_membrane_accounts_contact(int64_t port"));
+
+    let _ = std::fs::remove_dir_all(destination);
+  }
+
+  #[test]
+  #[serial]
+  #[cfg(target_os = "linux")]
+  fn two_prefixed_cdylibs_in_one_binary() {
+    let path = dart_example_path();
+    let destination = env::temp_dir().join("membrane_two_prefixed_cdylibs_test");
+    std::fs::create_dir_all(&destination).unwrap();
+
+    build_lib(
+      &path.to_path_buf(),
+      &mut vec!["--config", "env.MEMBRANE_SYMBOL_PREFIX=\"acme_\""],
+    );
+    let orders = build_fixture(
+      "orders",
+      &mut vec!["--config", "env.MEMBRANE_SYMBOL_PREFIX=\"orders_\""],
+    );
+    let example = path.join("libexample.so");
+
+    let exports = |lib: &PathBuf| {
+      let output = std::process::Command::new("nm")
+        .args(["-D", "--defined-only"])
+        .arg(lib)
+        .output()
+        .unwrap();
+      assert!(output.status.success());
+      String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .filter_map(|x| x.split_whitespace().nth(2).map(|x| x.to_string()))
+        .collect::<std::collections::BTreeSet<String>>()
+    };
+    let example_exports = exports(&example);
+    let orders_exports = exports(&orders);
+    assert!(example_exports.iter().all(|x| x.starts_with("acme_")));
+    assert!(orders_exports.iter().all(|x| x.starts_with("orders_")));
+    assert!(example_exports.is_disjoint(&orders_exports));
+
+    std::fs::write(
+      destination.join("main.c"),
+      r#"#include <stdio.h>
+
+void acme_membrane_store_dart_post_cobject(void *ptr);
+void orders_membrane_store_dart_post_cobject(void *ptr);
+char *acme_membrane_metadata_membrane_version();
+char *orders_membrane_metadata_membrane_version();
+
+static int post(long port, void *message) { return 0; }
+
+int main() {
+  acme_membrane_store_dart_post_cobject((void *)post);
+  orders_membrane_store_dart_post_cobject((void *)post);
+  printf("%s %s\n", acme_membrane_metadata_membrane_version(), orders_membrane_metadata_membrane_version());
+  return 0;
+}
+"#,
+    )
+    .unwrap();
+
+    let mut link_args = vec![];
+    for dir in [path.clone(), orders.parent().unwrap().to_path_buf()] {
+      link_args.push(format!("-L{}", dir.display()));
+      link_args.push(format!("-Wl,-rpath,{}", dir.display()));
+    }
+    let output = std::process::Command::new("cc")
+      .current_dir(&destination)
+      .args(["main.c", "-o", "main", "-lexample", "-lorders"])
+      .args(link_args)
+      .output()
+      .unwrap();
+    assert!(
+      output.status.success(),
+      "{}",
+      String::from_utf8_lossy(&output.stderr)
+    );
+
+    // cargo points this at the workspace build of the example which would win over the rpath
+    let output = std::process::Command::new(destination.join("main"))
+      .env_remove("LD_LIBRARY_PATH")
+      .output()
+      .unwrap();
+    assert!(
+      output.status.success(),
+      "{}",
+      String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+      String::from_utf8(output.stdout).unwrap(),
+      format!("{0} {0}\n", env!("CARGO_PKG_VERSION"))
+    );
+
+    let _ = std::fs::remove_dir_all(destination);
+  }
+
+  // builds a library from `tests/fixtures` and returns the path to it
+  fn build_fixture(name: &str, additional_args: &mut Vec<&str>) -> PathBuf {
+    let fixture_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap())
+      .join("tests")
+      .join("fixtures")
+      .join(name);
+
+    let mut args = vec!["build"];
+    args.append(additional_args);
+
+    let output = std::process::Command::new("cargo")
+      .current_dir(&fixture_dir)
+      .args(args)
+      .output()
+      .expect("fixture could not be compiled for integration tests");
+    assert!(
+      output.status.success(),
+      "{}",
+      String::from_utf8_lossy(&output.stderr)
+    );
+
+    let lib = if cfg!(target_os = "macos") {
+      format!("lib{}.dylib", name)
+    } else {
+      format!("lib{}.so", name)
+    };
+    fixture_dir.join("target").join("debug").join(lib)
+  }
+
   fn dart_example_path() -> PathBuf {
     PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap())
       .join("..")
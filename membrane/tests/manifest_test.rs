@@ -0,0 +1,114 @@
+mod mock;
+use crate::mock::RUNTIME;
+
+mod test {
+  use membrane::manifest::Manifest;
+  use membrane::Membrane;
+  use pretty_assertions::assert_eq;
+  use std::collections::BTreeMap;
+
+  mod app {
+    use membrane::{async_dart, dart_enum};
+    use serde::{Deserialize, Serialize};
+
+    #[dart_enum(namespace = "manifest")]
+    #[derive(Deserialize, Serialize)]
+    pub enum Status {
+      Active,
+      Closed,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    pub struct Order {
+      pub id: i64,
+      pub status: Status,
+    }
+
+    /// Fetch an order
+    #[async_dart(namespace = "manifest", timeout = 500)]
+    pub async fn order(_id: i64) -> Result<Order, String> {
+      todo!()
+    }
+  }
+
+  #[test]
+  fn test_generating_from_a_manifest_matches_the_source() {
+    let destination = std::env::temp_dir().join("membrane_manifest_test");
+    let manifest = destination.join("membrane.json");
+    std::fs::create_dir_all(&destination).unwrap();
+
+    let mut membrane = Membrane::new();
+    membrane
+      .package_destination_dir(&destination.join("from_source"))
+      .write_manifest(&manifest)
+      .write_schema();
+    assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+    let mut membrane = Membrane::new_from_manifest(&manifest);
+    membrane
+      .package_destination_dir(&destination.join("from_manifest"))
+      .write_schema();
+    assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+    let read = |dir: &str| {
+      std::fs::read_to_string(destination.join(dir).join("schema/manifest.yaml")).unwrap()
+    };
+    assert_eq!(read("from_manifest"), read("from_source"));
+
+    let contents = std::fs::read_to_string(&manifest).unwrap();
+    assert!(contents.contains("Fetch an order"));
+    assert!(contents.contains("\"name\": \"Status\""));
+
+    let _ = std::fs::remove_dir_all(destination);
+  }
+
+  #[test]
+  fn test_manifest_keeps_the_symbol_prefix_and_libraries() {
+    let destination = std::env::temp_dir().join("membrane_manifest_libraries_test");
+    let manifest = destination.join("membrane.json");
+    let _ = std::fs::remove_dir_all(&destination);
+    std::fs::create_dir_all(destination.join("lib/src/manifest")).unwrap();
+
+    let mut membrane = Membrane::new();
+    membrane.write_manifest(&manifest);
+    assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+    // as written by a generator which merged several prefixed libraries
+    let mut contents = std::fs::read_to_string(&manifest)
+      .unwrap()
+      .parse::<Manifest>()
+      .unwrap();
+    contents.symbol_prefix = "acme_".to_string();
+    contents.namespace_libraries =
+      BTreeMap::from([("manifest".to_string(), "liborders".to_string())]);
+    std::fs::write(&manifest, contents.to_string().unwrap()).unwrap();
+
+    let mut membrane = Membrane::new_from_manifest(&manifest);
+    membrane
+      .package_destination_dir(&destination)
+      .write_c_headers()
+      .write_manifest(&destination.join("rewritten.json"));
+    assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+    let header = std::fs::read_to_string(destination.join("lib/src/manifest/manifest.h")).unwrap();
+    assert!(header.contains("acme_membrane_manifest_order("));
+
+    let rewritten = std::fs::read_to_string(destination.join("rewritten.json"))
+      .unwrap()
+      .parse::<Manifest>()
+      .unwrap();
+    assert_eq!(rewritten.symbol_prefix, "acme_");
+    assert_eq!(rewritten.namespace_libraries, contents.namespace_libraries);
+
+    let _ = std::fs::remove_dir_all(destination);
+  }
+
+  #[test]
+  fn test_an_unreadable_manifest_is_reported() {
+    let path = std::env::temp_dir().join("membrane_missing_manifest.json");
+    let mut membrane = Membrane::new_from_manifest(&path);
+    let errors = membrane.drain_errors();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].starts_with("Unable to read the Membrane manifest"));
+  }
+}
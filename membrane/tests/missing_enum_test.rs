@@ -0,0 +1,63 @@
+mod mock;
+use crate::mock::RUNTIME;
+
+mod test {
+  use membrane::Membrane;
+
+  mod app {
+    use membrane::async_dart;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize)]
+    pub struct Order {
+      pub status: Status,
+      pub kind: Kind,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    pub enum Status {
+      Pending,
+      Shipped,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    pub enum Kind {
+      Retail,
+      Wholesale,
+    }
+
+    #[async_dart(namespace = "orders")]
+    pub async fn order() -> Result<Order, String> {
+      todo!()
+    }
+
+    #[async_dart(namespace = "reports")]
+    pub async fn statuses() -> Result<Vec<Status>, String> {
+      todo!()
+    }
+
+    #[async_dart(namespace = "reports")]
+    pub async fn orders() -> Result<Vec<Order>, String> {
+      todo!()
+    }
+  }
+
+  #[test]
+  fn test_every_missing_enum_is_reported_at_once() {
+    let destination = std::env::temp_dir().join("membrane_missing_enum_test");
+    let _ = std::fs::remove_dir_all(&destination);
+
+    let mut membrane = Membrane::new();
+    membrane
+      .package_destination_dir(&destination)
+      .write_schema();
+    let errors = membrane.drain_errors();
+
+    assert_eq!(errors.len(), 1, "{:#?}", errors);
+    assert!(errors[0].contains("Please add #[derive(DartEnum)] to each of these enums:"));
+    assert!(errors[0]
+      .contains("#   Kind (used by orders, reports)\n#   Status (used by orders, reports)\n"));
+
+    let _ = std::fs::remove_dir_all(destination);
+  }
+}
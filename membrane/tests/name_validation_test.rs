@@ -0,0 +1,83 @@
+mod mock;
+use crate::mock::RUNTIME;
+
+mod test {
+  use membrane::Membrane;
+
+  mod app {
+    use membrane::{async_dart, sync_dart};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize)]
+    pub struct Contact {
+      pub id: i64,
+      pub is: bool,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    pub struct ValidationsApi {
+      pub id: i64,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    pub enum Status {
+      Active { since: i64 },
+      Closed,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    pub struct StatusClosed {
+      pub at: i64,
+    }
+
+    #[async_dart(namespace = "validations")]
+    pub async fn contact(_new: i64) -> Result<Contact, String> {
+      todo!()
+    }
+
+    #[async_dart(namespace = "validations")]
+    pub async fn api() -> Result<ValidationsApi, String> {
+      todo!()
+    }
+
+    #[async_dart(namespace = "validations")]
+    pub async fn status(_closed: StatusClosed) -> Result<Status, String> {
+      todo!()
+    }
+
+    #[sync_dart(namespace = "validations")]
+    pub fn get_contact() -> Result<i64, String> {
+      todo!()
+    }
+
+    #[allow(non_snake_case)]
+    #[sync_dart(namespace = "validations")]
+    pub fn getContact() -> Result<i64, String> {
+      todo!()
+    }
+  }
+
+  #[test]
+  fn test_dart_name_collisions_are_reported_before_writing() {
+    let destination = std::env::temp_dir().join("membrane_name_validation_test");
+    let _ = std::fs::remove_dir_all(&destination);
+
+    let mut membrane = Membrane::new();
+    membrane.package_destination_dir(&destination).write_api();
+    let errors = membrane.drain_errors();
+
+    assert!(!destination.exists());
+    assert_eq!(errors.len(), 5, "{:#?}", errors);
+    assert!(errors[0].starts_with("The field `is` of `validations::Contact` at "));
+    assert!(errors[0].contains("name_validation_test.rs"));
+    assert!(errors[1].starts_with("`validations::StatusClosed` at "));
+    assert!(
+      errors[1].contains("the class generated for the `Closed` variant of `validations::Status`")
+    );
+    assert!(errors[2].starts_with("`validations::ValidationsApi` at "));
+    assert!(errors[2].contains("has the same name as a class of the `validations` API"));
+    assert!(errors[3]
+      .starts_with("`validations` has more than one function named `getContact` in Dart at "));
+    assert!(errors[4].starts_with("The argument `_new` of `contact` in `validations` at "));
+  }
+}
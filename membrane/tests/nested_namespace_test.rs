@@ -0,0 +1,49 @@
+mod mock;
+use crate::mock::RUNTIME;
+
+mod test {
+  use membrane::Membrane;
+  use pretty_assertions::assert_eq;
+
+  mod app {
+    use membrane::async_dart;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize)]
+    pub struct Role {
+      pub id: i64,
+    }
+
+    #[async_dart(namespace = "nested::admin")]
+    pub async fn role(_id: i64) -> Result<Role, String> {
+      todo!()
+    }
+  }
+
+  #[test]
+  fn test_nested_namespaces_are_flattened_for_paths_and_symbols() {
+    let destination = std::env::temp_dir().join("membrane_nested_namespace_test");
+    let _ = std::fs::remove_dir_all(&destination);
+    std::fs::create_dir_all(destination.join("lib/src/nested_admin")).unwrap();
+
+    let mut membrane = Membrane::new();
+    membrane
+      .package_destination_dir(&destination)
+      .write_schema()
+      .write_c_headers()
+      .write_api();
+    assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+    let read = |path: &str| std::fs::read_to_string(destination.join(path)).unwrap();
+
+    assert!(read("schema/nested_admin.yaml").contains("namespace: nested::admin"));
+    assert!(read("lib/src/nested_admin/nested_admin.h").contains("membrane_nested_admin_role("));
+    assert!(read("lib/src/nested_admin_ffi.dart").contains("class NestedAdminApi {"));
+    assert_eq!(
+      read("lib/nested/admin.dart").lines().nth(3),
+      Some("export '../src/nested_admin_ffi.dart' if (dart.library.html) '../src/nested_admin_web.dart';")
+    );
+
+    let _ = std::fs::remove_dir_all(destination);
+  }
+}
@@ -0,0 +1,95 @@
+mod mock;
+use crate::mock::RUNTIME;
+
+mod test {
+  use membrane::Membrane;
+  use pretty_assertions::assert_eq;
+
+  mod app {
+    use membrane::{async_dart, dart_sample};
+    use serde::{de::Error, Deserialize, Deserializer, Serialize};
+    use std::num::NonZeroU32;
+
+    #[derive(Serialize)]
+    pub struct Email(String);
+
+    impl<'de> Deserialize<'de> for Email {
+      fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "Email")]
+        struct Unchecked(String);
+
+        let Unchecked(email) = Unchecked::deserialize(deserializer)?;
+        if email.contains('@') {
+          Ok(Email(email))
+        } else {
+          Err(D::Error::custom("an email must contain @"))
+        }
+      }
+    }
+
+    #[derive(Deserialize, Serialize)]
+    pub struct Contact {
+      pub email: Email,
+      pub rank: NonZeroU32,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    pub struct Unused {
+      pub id: i64,
+    }
+
+    #[dart_sample]
+    fn email() -> Email {
+      Email("someone@example.com".to_string())
+    }
+
+    #[dart_sample]
+    fn contact() -> Contact {
+      Contact {
+        email: email(),
+        rank: NonZeroU32::new(1).unwrap(),
+      }
+    }
+
+    #[dart_sample]
+    fn unused() -> Unused {
+      Unused { id: 1 }
+    }
+
+    #[async_dart(namespace = "sampled")]
+    pub async fn contact_by_email(_email: Email) -> Result<Contact, String> {
+      todo!()
+    }
+
+    #[async_dart(namespace = "unsampled")]
+    pub async fn count() -> Result<i64, String> {
+      todo!()
+    }
+  }
+
+  #[test]
+  fn test_samples_are_used_to_trace_validated_types() {
+    let destination = std::env::temp_dir().join("membrane_samples_test");
+    let _ = std::fs::remove_dir_all(&destination);
+
+    let mut membrane = Membrane::new();
+    membrane
+      .package_destination_dir(&destination)
+      .write_schema();
+    assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+    let schema = std::fs::read_to_string(destination.join("schema/sampled.yaml")).unwrap();
+    assert!(schema.contains("Contact:"));
+    assert!(schema.contains("Email:"));
+    // a sampled type is only part of the namespaces which use it
+    assert!(!schema.contains("Unused:"));
+    assert!(
+      !std::fs::read_to_string(destination.join("schema/unsampled.yaml"))
+        .unwrap()
+        .contains("Unused:")
+    );
+
+    let _ = std::fs::remove_dir_all(destination);
+  }
+}
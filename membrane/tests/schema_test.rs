@@ -0,0 +1,186 @@
+mod mock;
+use crate::mock::RUNTIME;
+
+mod test {
+  use membrane::schema::{Schema, SchemaFormat, SCHEMA_VERSION};
+  use membrane::Membrane;
+  use pretty_assertions::assert_eq;
+  use serde_reflection::{ContainerFormat, Format, Named};
+
+  mod app {
+    use membrane::{async_dart, sync_dart};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize)]
+    pub struct Contact {
+      pub id: i64,
+      pub name: String,
+    }
+
+    #[async_dart(namespace = "schema", timeout = 500)]
+    pub async fn contact(_id: i64, _active: bool) -> Result<Contact, String> {
+      todo!()
+    }
+
+    #[sync_dart(namespace = "schema")]
+    pub fn contact_ids(_limit: Option<i64>) -> Result<Vec<i64>, String> {
+      todo!()
+    }
+  }
+
+  #[test]
+  fn test_schema_is_written_and_read() {
+    let destination = std::env::temp_dir().join("membrane_schema_test");
+
+    for format in [SchemaFormat::Yaml, SchemaFormat::Json] {
+      let mut membrane = Membrane::new();
+      membrane
+        .package_destination_dir(&destination)
+        .schema_format(format)
+        .write_schema();
+
+      assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+      let path = destination
+        .join("schema")
+        .join(format!("schema.{}", format.extension()));
+      let schema = Schema::from_str(&std::fs::read_to_string(path).unwrap(), format).unwrap();
+
+      assert_eq!(schema.version, SCHEMA_VERSION);
+      assert_eq!(schema.namespace, "schema");
+
+      let names = schema
+        .functions
+        .iter()
+        .map(|f| f.name.as_str())
+        .collect::<Vec<&str>>();
+      assert_eq!(names, vec!["contact", "contactIds"]);
+
+      let contact = &schema.functions[0];
+      assert_eq!(contact.args.len(), 2);
+      assert_eq!(contact.args[0].name, "_id");
+      assert_eq!(contact.args[0].ty, vec!["i64"]);
+      assert_eq!(contact.args[1].ty, vec!["bool"]);
+      assert_eq!(contact.return_type, vec!["Contact"]);
+      assert_eq!(contact.error_type, vec!["String"]);
+      assert_eq!(contact.timeout, Some(500));
+      assert!(!contact.is_sync && !contact.is_stream);
+
+      let contact_ids = &schema.functions[1];
+      assert_eq!(contact_ids.args[0].ty, vec!["Option", "i64"]);
+      assert_eq!(contact_ids.return_type, vec!["Vec", "i64"]);
+      assert!(contact_ids.is_sync);
+
+      assert_eq!(
+        schema.types.get("Contact"),
+        Some(&ContainerFormat::Struct(vec![
+          Named {
+            name: "id".to_string(),
+            value: Format::I64
+          },
+          Named {
+            name: "name".to_string(),
+            value: Format::Str
+          },
+        ]))
+      );
+    }
+
+    let _ = std::fs::remove_dir_all(destination);
+  }
+
+  #[test]
+  fn test_breaking_changes_fail_generation() {
+    let destination = std::env::temp_dir().join("membrane_compatibility_test");
+    let baseline = destination.join("schema");
+
+    let mut membrane = Membrane::new();
+    membrane
+      .package_destination_dir(&destination)
+      .write_schema()
+      .check_compatibility(&baseline);
+    assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+    // simulate a baseline where the fields of `Contact` were declared in a different order
+    let path = baseline.join("schema.yaml");
+    let mut schema =
+      Schema::from_str(&std::fs::read_to_string(&path).unwrap(), SchemaFormat::Yaml).unwrap();
+    if let Some(ContainerFormat::Struct(fields)) = schema.types.get_mut("Contact") {
+      fields.reverse();
+    }
+    std::fs::write(&path, schema.to_string(SchemaFormat::Yaml).unwrap()).unwrap();
+
+    let mut membrane = Membrane::new();
+    membrane.check_compatibility(&baseline);
+    let errors = membrane.drain_errors();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains(
+      "[breaking] schema: type `Contact` fields were reordered from (name, id) to (id, name)"
+    ));
+
+    let mut membrane = Membrane::new();
+    membrane
+      .allow_breaking_changes(true)
+      .check_compatibility(&baseline);
+    assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+    let _ = std::fs::remove_dir_all(destination);
+  }
+
+  #[test]
+  fn test_signature_hashes_match_the_traced_functions() {
+    // what the library reports at runtime, from the signatures registered in every build profile
+    let reported = membrane::metadata::signature_hashes();
+    assert!(reported.starts_with("schema:"));
+
+    // what the generator embeds in the Dart package, from the functions it traced
+    let functions = membrane::metadata::functions();
+    let traced = membrane::metadata::hash_signatures(
+      &functions
+        .iter()
+        .map(|x| membrane::Signature::from(&x.function))
+        .collect::<Vec<_>>(),
+    );
+    assert_eq!(
+      reported,
+      traced
+        .iter()
+        .map(|(namespace, hash)| format!("{}:{}", namespace, hash))
+        .collect::<Vec<String>>()
+        .join(",")
+    );
+  }
+
+  #[test]
+  fn test_schema_hashes_are_written_for_the_library() {
+    let destination = std::env::temp_dir().join("membrane_schema_hash_test");
+    let _ = std::fs::remove_dir_all(&destination);
+    std::fs::create_dir_all(destination.join("lib/src/schema")).unwrap();
+
+    let mut membrane = Membrane::new();
+    membrane
+      .package_destination_dir(&destination)
+      .write_schema()
+      .write_api();
+    assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+    let path = destination.join("schema").join("schema.yaml");
+    let mut schema =
+      Schema::from_str(&std::fs::read_to_string(path).unwrap(), SchemaFormat::Yaml).unwrap();
+
+    // the library embeds this file's contents which the generated Dart loader checks against
+    let hashes = std::fs::read_to_string(destination.join("libmembrane.schema_hashes")).unwrap();
+    assert_eq!(hashes, format!("schema:{}", schema.hash()));
+
+    // timeouts are not part of the wire format
+    schema.functions[0].timeout = Some(1);
+    assert_eq!(hashes, format!("schema:{}", schema.hash()));
+
+    if let Some(ContainerFormat::Struct(fields)) = schema.types.get_mut("Contact") {
+      fields.reverse();
+    }
+    assert_ne!(hashes, format!("schema:{}", schema.hash()));
+
+    let _ = std::fs::remove_dir_all(destination);
+  }
+}
@@ -1,3 +1,6 @@
+#![allow(dead_code)]
+
+use membrane::Membrane;
 use pretty_assertions::assert_eq;
 use std::fs;
 use std::io::Write;
@@ -27,6 +30,36 @@ impl<'a> fmt::Debug for PrettyString<'a> {
   }
 }
 
+/// Generate the package for the functions of `namespace` traced into this test binary and return its
+/// Dart API, C header and schema.
+pub fn generate(namespace: &str) -> (String, String, String) {
+  let destination = env::temp_dir().join(format!("membrane_{}_test", namespace));
+  let _ = fs::remove_dir_all(&destination);
+  fs::create_dir_all(destination.join("lib/src").join(namespace)).unwrap();
+
+  let mut membrane = Membrane::new();
+  membrane
+    .package_destination_dir(&destination)
+    .write_schema()
+    .write_c_headers()
+    .write_api();
+  assert_eq!(membrane.drain_errors(), Vec::<String>::new());
+
+  let src = destination.join("lib/src");
+  let api = fs::read_to_string(src.join(format!("{}_ffi.dart", namespace))).unwrap();
+  let header = fs::read_to_string(src.join(namespace).join(format!("{}.h", namespace))).unwrap();
+  let schema = fs::read_to_string(
+    destination
+      .join("schema")
+      .join(format!("{}.yaml", namespace)),
+  )
+  .unwrap();
+
+  let _ = fs::remove_dir_all(destination);
+  (api, header, schema)
+}
+
+#[allow(clippy::ptr_arg)]
 pub fn build_lib(path: &PathBuf, additional_args: &mut Vec<&str>) {
   let example_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap())
     .join("..")
@@ -0,0 +1,53 @@
+mod mock;
+mod test_utils;
+use crate::mock::RUNTIME;
+
+mod test {
+  use super::test_utils::{assert_contains_part, generate};
+
+  mod app {
+    use membrane::{async_dart, sync_dart};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize)]
+    pub struct Contact {
+      pub id: i64,
+    }
+
+    #[async_dart(namespace = "tuples")]
+    pub async fn pair(
+      point: (i64, Option<String>),
+      nested: Option<(Contact, (bool,))>,
+    ) -> Result<(i64, Vec<Contact>), String> {
+      let _ = (point, nested);
+      todo!()
+    }
+
+    #[sync_dart(namespace = "tuples")]
+    pub fn single(values: Vec<(u8, f64)>) -> Result<(String,), String> {
+      Ok((values.len().to_string(),))
+    }
+  }
+
+  #[test]
+  fn test_tuples_are_passed_as_dart_records() {
+    let (api, header, _) = generate("tuples");
+
+    assert_contains_part(
+      &api,
+      "Future<(int, List<Contact>)> pair({required (int, String?) point, (Contact, (bool,))? nested})",
+    );
+    assert_contains_part(
+      &api,
+      "(String,) single({required List<(int, double)> values})",
+    );
+
+    // each field is written in order and read back into a record literal
+    assert_contains_part(&api, "final pointField1 = point.$1;");
+    assert_contains_part(&api, "serializer.serializeOptionTag(pointField2 != null);");
+    assert_contains_part(&api, "final nestedField2Field1 = nestedField2.$1;");
+    assert_contains_part(&api, "return (deserializer.deserializeString(),);");
+
+    assert_contains_part(&header, "const uint8_t *point, const uint8_t *nested");
+  }
+}
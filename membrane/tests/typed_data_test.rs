@@ -0,0 +1,62 @@
+mod mock;
+mod test_utils;
+use crate::mock::RUNTIME;
+
+mod test {
+  use super::test_utils::{assert_contains_part, generate};
+
+  mod app {
+    use membrane::{async_dart, sync_dart};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(transparent)]
+    pub struct Sample(f32);
+
+    #[async_dart(namespace = "typed_data")]
+    pub async fn image(pixels: Vec<u8>, kernel: Box<[f64]>) -> Result<Vec<u8>, String> {
+      let _ = kernel;
+      Ok(pixels)
+    }
+
+    #[sync_dart(namespace = "typed_data")]
+    pub fn audio(samples: Vec<Sample>) -> Result<Box<[f32]>, String> {
+      Ok(samples.into_iter().map(|x| x.0).collect())
+    }
+
+    #[async_dart(namespace = "typed_data")]
+    pub async fn counts(ids: Option<Vec<i64>>) -> Result<Vec<u64>, String> {
+      let _ = ids;
+      Ok(vec![])
+    }
+  }
+
+  #[test]
+  fn test_vecs_of_numbers_are_typed_data() {
+    let (api, _, _) = generate("typed_data");
+
+    assert_contains_part(
+      &api,
+      "Future<Uint8List> image({required Uint8List pixels, required Float64List kernel})",
+    );
+    assert_contains_part(&api, "Float32List audio({required Float32List samples})");
+    // an option and a `u64` aren't typed data
+    assert_contains_part(&api, "Future<List<Uint64>> counts({List<int>? ids})");
+
+    // the bytes of an argument are copied as a whole
+    assert_contains_part(
+      &api,
+      "final data = pixels.buffer.asUint8List(pixels.offsetInBytes, pixels.lengthInBytes);",
+    );
+    // a transparent newtype is still serialized
+    assert_contains_part(&api, "serializer.serializeFloat32(");
+
+    // async returns are sent without a copy and sync returns are copied once
+    assert_contains_part(&api, "return (input as List).first as Uint8List;");
+    assert_contains_part(
+      &api,
+      "return data.asTypedList(length + 8).sublist(17).buffer.asFloat32List();",
+    );
+    assert_contains_part(&api, "return Uint8List.fromList(List.generate(");
+  }
+}
@@ -0,0 +1,52 @@
+use membrane::{dart_enum, DartEnum};
+use serde::{Deserialize, Serialize};
+
+#[dart_enum(namespace = "a")]
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Untagged {
+  One(i32),
+  Two(String),
+}
+
+#[dart_enum(namespace = "a")]
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InternallyTagged {
+  One { id: i32 },
+}
+
+#[dart_enum(namespace = "a")]
+#[derive(Serialize, Deserialize)]
+pub enum SkippedField {
+  One {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<i32>,
+  },
+}
+
+#[dart_enum(namespace = "a")]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Success {
+  One,
+  Two,
+}
+
+#[derive(Serialize, Deserialize, DartEnum)]
+#[serde(untagged)]
+pub enum DerivedUntagged {
+  One(i32),
+}
+
+#[derive(Serialize, Deserialize, DartEnum)]
+pub enum DerivedGeneric<T> {
+  One(T),
+}
+
+#[derive(Serialize, Deserialize, DartEnum)]
+pub enum Derived {
+  One { id: i32 },
+}
+
+fn main() {}
@@ -12,17 +12,26 @@ pub async fn os_thread_option_used_in_sync_fn() -> i32 {}
 // return value errors
 
 #[async_dart(namespace = "a")]
-pub async fn no_result() -> i32 {}
+pub async fn alias_result() -> Result<i32> {}
 
-#[async_dart(namespace = "a")]
-pub async fn bare_tuple() -> Result<(i32, i32), String> {}
+#[async_dart(namespace = "a", error = "String")]
+pub async fn error_with_result() -> Result<i32, String> {}
 
-#[async_dart(namespace = "a")]
-pub async fn top_level_option() -> Option<String> {}
+#[async_dart(namespace = "a", error = "String")]
+pub async fn error_without_result() -> Option<i32> {}
+
+#[async_dart(namespace = "a", error_display = true)]
+pub async fn infallible_error_display() -> i32 {}
 
 #[async_dart(namespace = "a")]
 pub async fn return_fn() -> Result<dyn Fn(), String> {}
 
+#[async_dart(namespace = "a")]
+pub async fn char_return() -> Result<char, String> {}
+
+#[sync_dart(namespace = "a")]
+pub fn nested_char_return() -> Result<Vec<(i64, char)>, String> {}
+
 #[async_dart(namespace = "a")]
 pub async fn option_success() -> Result<Option<i32>, String> {
   Ok(Some(1))
@@ -41,10 +50,24 @@ pub async fn failing_arg(self) -> Result<(), String> {
 }
 
 #[async_dart(namespace = "a")]
-pub async fn bad_arg_type(one: i32) -> Result<i32, String> {}
+pub async fn bad_nested_arg_type(one: Vec<char>) -> Result<i32, String> {}
+
+#[async_dart(namespace = "a")]
+pub async fn nested_str_arg(one: Vec<&str>) -> Result<i32, String> {}
+
+#[async_dart(namespace = "a")]
+pub async fn tuple_str_arg(one: (i64, &str)) -> Result<i32, String> {}
+
+#[async_dart(namespace = "a")]
+pub async fn mut_str_arg(one: &mut str) -> Result<i32, String> {}
+
+#[async_dart(namespace = "a")]
+pub async fn vec_map_key_arg(one: std::collections::HashMap<Vec<i64>, i64>) -> Result<i32, String> {}
+
+const KEY_LENGTH: usize = 32;
 
 #[async_dart(namespace = "a")]
-pub async fn bad_nested_arg_type(one: Vec<i32>) -> Result<i32, String> {}
+pub async fn const_array_arg(one: [u8; KEY_LENGTH]) -> Result<i32, String> {}
 
 #[async_dart(namespace = "a")]
 pub async fn failing_arg_two(foo: &[i8]) -> Result<(), String> {
@@ -0,0 +1,38 @@
+use membrane::dart_type;
+use serde::{Deserialize, Serialize};
+
+#[dart_type(namespace = "a")]
+#[derive(Serialize, Deserialize)]
+pub struct Flattened {
+  #[serde(flatten)]
+  inner: Inner,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Inner {
+  id: i32,
+}
+
+#[dart_type(namespace = "a")]
+#[derive(Serialize, Deserialize)]
+pub struct Generic<T> {
+  value: T,
+}
+
+#[dart_type(namespace = "a")]
+pub fn not_a_type() {}
+
+#[dart_type]
+#[derive(Serialize, Deserialize)]
+pub struct MissingNamespace {
+  id: i32,
+}
+
+#[dart_type(namespace = "a")]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Success {
+  One { id: i32 },
+}
+
+fn main() {}
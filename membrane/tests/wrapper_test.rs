@@ -0,0 +1,97 @@
+mod mock;
+mod test_utils;
+use crate::mock::RUNTIME;
+
+mod test {
+  use super::test_utils::{assert_contains_part, generate};
+
+  mod app {
+    use membrane::{async_dart, sync_dart};
+    use serde::{Deserialize, Serialize};
+    use std::borrow::Cow;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[derive(Clone, Deserialize, Serialize)]
+    pub struct Contact {
+      pub id: i64,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(transparent)]
+    pub struct UserId(i64);
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(transparent)]
+    pub struct Nickname(Option<String>);
+
+    #[async_dart(namespace = "wrappers")]
+    pub async fn update_contact(
+      contact: Box<Contact>,
+      name: Arc<str>,
+      note: Option<Box<str>>,
+      tags: Cow<'static, [String]>,
+    ) -> Result<Box<Contact>, String> {
+      let _ = (name, note, tags);
+      Ok(contact)
+    }
+
+    #[sync_dart(namespace = "wrappers")]
+    pub fn shared_contact(
+      contact: Rc<Contact>,
+      id: Option<Rc<i64>>,
+    ) -> Result<Cow<'static, str>, String> {
+      let _ = id;
+      Ok(Cow::Owned(contact.id.to_string()))
+    }
+
+    #[async_dart(namespace = "wrappers")]
+    pub async fn rename(
+      id: UserId,
+      nickname: Nickname,
+      manager: Option<UserId>,
+      reports: Vec<UserId>,
+    ) -> Result<UserId, String> {
+      let _ = (nickname, manager, reports);
+      Ok(id)
+    }
+  }
+
+  #[test]
+  fn test_wrappers_and_transparent_newtypes_are_their_inner_type() {
+    let (api, header, schema) = generate("wrappers");
+
+    assert_contains_part(
+      &api,
+      "Future<Contact> updateContact({required Contact contact, required String name, String? note, required List<String> tags})",
+    );
+    assert_contains_part(
+      &api,
+      "String sharedContact({required Contact contact, int? id})",
+    );
+    assert_contains_part(&api, "return Contact.deserialize(deserializer);");
+
+    // a transparent newtype is passed as the bincode of the type it wraps
+    assert_contains_part(
+      &api,
+      "Future<int> rename({required int id, String? nickname, int? manager, required Int64List reports})",
+    );
+    assert_contains_part(&api, "serializer.serializeOptionTag(nickname != null);");
+    assert_contains_part(&api, "serializer.serializeInt64(manager);");
+    assert_contains_part(&api, "return deserializer.deserializeInt64();");
+
+    assert_contains_part(
+      &header,
+      "const uint8_t *contact, const char *name, const char *note, const uint8_t *tags",
+    );
+    assert_contains_part(&header, "const uint8_t *contact, const int64_t *id");
+    assert_contains_part(
+      &header,
+      "const uint8_t *id, const uint8_t *nickname, const uint8_t *manager, const uint8_t *reports",
+    );
+
+    // the schema has the inner types and no class for a newtype
+    assert_contains_part(&schema, "Contact:");
+    assert!(!schema.contains("UserId"));
+  }
+}
@@ -1,11 +1,16 @@
 extern crate proc_macro;
 use membrane_types::c::CHeaderTypes;
-use membrane_types::dart::{DartArgs, DartParams, DartTransforms};
+use membrane_types::dart::{typed_data, DartArgs, DartParams, DartTransforms};
 use membrane_types::heck::ToLowerCamelCase;
-use membrane_types::rust::{flatten_types, RustArgs, RustExternParams, RustTransforms};
+use membrane_types::rust::{
+  flatten_types, traced_type, RustArgs, RustExternParams, RustTransforms,
+};
 use membrane_types::syn::Attribute;
 use membrane_types::{proc_macro2, quote, syn, Input, OutputStyle};
-use options::{extract_enum_options, extract_function_options, FunctionOptions};
+use options::{
+  extract_enum_options, extract_function_options, extract_type_options, FunctionOptions,
+  TypeOptions,
+};
 use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::quote;
@@ -24,8 +29,7 @@ struct ReprDart {
   fn_name: Ident,
   inputs: Vec<Input>,
   output_style: OutputStyle,
-  output: syn::Type,
-  error: syn::Type,
+  return_type: syn::Type,
   docblock: String,
 }
 
@@ -55,8 +59,10 @@ impl Parse for ReprDart {
     input.parse::<Token![fn]>()?;
     let fn_name = input.parse::<Ident>()?;
     syn::parenthesized!(arg_buffer in input);
-    input.parse::<Token![->]>()?;
-    let (output_style, ret_type, err_type) = if input.peek(Token![impl]) {
+    // a function without a return type returns `()`
+    let (output_style, return_type) = if input.parse::<Option<Token![->]>>()?.is_none() {
+      (OutputStyle::Serialized, syn::parse_quote!(()))
+    } else if input.peek(Token![impl]) {
       parsers::parse_trait_return_type(input)?
     } else {
       parsers::parse_return_type(input)?
@@ -67,8 +73,7 @@ impl Parse for ReprDart {
       fn_name,
       inputs: parsers::parse_args(arg_buffer)?,
       output_style,
-      output: ret_type,
-      error: err_type,
+      return_type,
       docblock,
     })
   }
@@ -79,11 +84,19 @@ impl Parse for ReprDart {
 ///
 /// Valid options:
 ///   * `namespace`, used to name the generated Dart API class and the implementation code directory.
+///     Namespaces may be nested, as in `namespace = "accounts::admin"`.
 ///   * `disable_logging`, turn off logging statements inside generated Dart API code.
 ///   * `timeout`, the milliseconds that Dart should wait for a response on the isolate port before cancelling.
 ///   * `os_thread`, specifies that the function should be ran with `spawn_blocking` which moves the work to a pool of OS threads.
+///   * `error`, the error type of a `Result` alias such as `type Result<T> = std::result::Result<T, AppError>`,
+///     as in `error = "crate::AppError"`.
+///   * `error_display`, send an error type which implements `Display` but not `Serialize` as its message.
 ///
-/// The usual function return type is either `Result<T, E>` or `impl Stream<Item = Result<T, E>>`. However, for
+/// An `anyhow::Error` or `Box<dyn std::error::Error>` error is sent as a `MembraneErrorMessage` holding the
+/// error's message and the messages of its sources, as is any error when `error_display = true`.
+///
+/// The usual function return type is either `Result<T, E>` or `impl Stream<Item = Result<T, E>>`. A function which
+/// returns any other type, or no type, is infallible and its Dart function never throws an `ApiError`. However, for
 /// advanced usage you may want to use either `impl Emitter<Result<T, E>>` or `impl StreamEmitter<Result<T, E>>`.
 /// When the Emitter traits are used the function must be synchronous but the emitter is thread-safe
 /// and may be sent to another thread to send asynchronous messages. See the `example` directory for details.
@@ -103,9 +116,12 @@ pub fn async_dart(attrs: TokenStream, input: TokenStream) -> TokenStream {
 ///
 /// Valid options:
 ///   * `namespace`, used to name the generated Dart API class and the implementation code directory.
+///     Namespaces may be nested, as in `namespace = "accounts::admin"`.
 ///   * `disable_logging`, turn off logging statements inside generated Dart API code.
+///   * `error`, the error type of a `Result` alias such as `type Result<T> = std::result::Result<T, AppError>`.
+///   * `error_display`, send an error type which implements `Display` but not `Serialize` as its message.
 ///
-/// The only supported function return type is `Result<T, E>`.
+/// The function return type is `Result<T, E>`, or any other type for an infallible function.
 ///
 #[proc_macro_attribute]
 pub fn sync_dart(attrs: TokenStream, input: TokenStream) -> TokenStream {
@@ -161,8 +177,7 @@ fn to_token_stream(
   let ReprDart {
     fn_name,
     output_style,
-    output,
-    error,
+    return_type,
     inputs,
     docblock,
     ..
@@ -174,8 +189,40 @@ fn to_token_stream(
     timeout,
     os_thread,
     borrow,
+    error,
+    error_display,
   } = options;
 
+  let (output, error) = parsers::result_types(&return_type, error.as_ref())?;
+  // an infallible function is sent as an `Ok` with an error type that's never used
+  let infallible = error.is_none();
+  if infallible && error_display {
+    return Err(syn::Error::new(
+      span,
+      "`error_display` is only valid for a function which returns a `Result`",
+    ));
+  }
+  // an error which isn't `Serialize` is sent as its message along with the messages of its sources
+  let display_error = match &error {
+    Some(error) if parsers::is_display_error(error) => Some(quote! {
+      ::membrane::MembraneErrorMessage::from_error(&*err)
+    }),
+    Some(_) if error_display => Some(quote! {
+      ::membrane::MembraneErrorMessage::from_display(&err)
+    }),
+    _ => None,
+  };
+  let error = match (&error, &display_error) {
+    (_, Some(_)) => syn::parse_quote!(::membrane::MembraneErrorMessage),
+    (Some(error), None) => error.clone(),
+    (None, None) => syn::parse_quote!(()),
+  };
+  let ok = |result: TokenStream2| match &display_error {
+    _ if infallible => quote! { Ok(#result) },
+    Some(message) => quote! { (#result).map_err(|err| #message) },
+    None => result,
+  };
+
   let mut functions = TokenStream::new();
 
   match output_style {
@@ -198,7 +245,7 @@ fn to_token_stream(
     .concat()
   };
   let rust_transforms: Vec<TokenStream2> = RustTransforms::try_from(&inputs)?.into();
-  let rust_inner_args: Vec<Ident> = RustArgs::from(&inputs).into();
+  let rust_inner_args: Vec<TokenStream2> = RustArgs::from(&inputs).into();
 
   let c_header_types: Vec<String> = CHeaderTypes::try_from(&inputs)?.into();
 
@@ -206,6 +253,34 @@ fn to_token_stream(
   let dart_transforms: Vec<String> = DartTransforms::try_from(&inputs)?.into();
   let dart_inner_args: Vec<String> = DartArgs::from(&inputs).into();
 
+  // a wrapper such as `Box` or `Arc` serializes like its inner type so the inner type is traced
+  let traced_output = traced_type(&output);
+
+  // a `Vec` of bytes or numbers is handed to Dart as typed data, other types which serialize as one such
+  // as a `Box<[u8]>` are copied into a `Vec` first
+  let output_types = flatten_types(&output, vec![])?;
+  let send = if typed_data(
+    &output_types
+      .iter()
+      .map(|x| x.as_str())
+      .collect::<Vec<&str>>(),
+  )
+  .is_some()
+  {
+    let into_vec = if quote!(#output).to_string() == quote!(#traced_output).to_string() {
+      quote! {}
+    } else {
+      quote! { .map(|value| value.to_vec()) }
+    };
+    quote! { ::membrane::utils::send_typed_data::<_, #error>(isolate, result #into_vec) }
+  } else {
+    quote! { ::membrane::utils::send::<#output, #error>(isolate, result) }
+  };
+
+  let stream_result = ok(quote! { result });
+  let call_result = ok(quote! { #fn_name(#(#rust_inner_args),*) });
+  let await_result = ok(quote! { #fn_name(#(#rust_inner_args),*).await });
+
   let return_statement = match output_style {
     OutputStyle::EmitterSerialized | OutputStyle::StreamEmitterSerialized if sync => {
       syn::Error::new(
@@ -214,6 +289,19 @@ fn to_token_stream(
       )
       .into_compile_error()
     }
+    OutputStyle::EmitterSerialized | OutputStyle::StreamEmitterSerialized if infallible => {
+      syn::Error::new(span, "#[async_dart] expected an emitter of `Result<T, E>`")
+        .into_compile_error()
+    }
+    OutputStyle::EmitterSerialized | OutputStyle::StreamEmitterSerialized
+      if display_error.is_some() =>
+    {
+      syn::Error::new(
+        span,
+        "#[async_dart] expected an emitter with a `Serialize` error type, use `membrane::MembraneErrorMessage` to send an error's message",
+      )
+      .into_compile_error()
+    }
     OutputStyle::EmitterSerialized | OutputStyle::StreamEmitterSerialized => quote! {
       let membrane_emitter = #fn_name(membrane_port, #(#rust_inner_args),*);
       let membrane_abort_handle = membrane_emitter.abort_handle();
@@ -229,8 +317,8 @@ fn to_token_stream(
           ::membrane::futures::pin_mut!(stream);
           let isolate = ::membrane::allo_isolate::Isolate::new(membrane_port);
           while let Some(result) = stream.next().await {
-            let result: ::std::result::Result<#output, #error> = result;
-            ::membrane::utils::send::<#output, #error>(isolate, result);
+            let result: ::std::result::Result<#output, #error> = #stream_result;
+            #send;
           }
         },
         ::membrane::runtime::Info { name: #rust_fn_name }
@@ -240,7 +328,7 @@ fn to_token_stream(
       ::std::boxed::Box::into_raw(Box::new(handle))
     },
     OutputStyle::Serialized if sync => quote! {
-      let result: ::std::result::Result<#output, #error> = #fn_name(#(#rust_inner_args),*);
+      let result: ::std::result::Result<#output, #error> = #call_result;
       let ser_result = match result {
         Ok(value) => ::membrane::bincode::serialize(&(::membrane::MembraneMsgKind::Ok as u8, value)),
         Err(err) => ::membrane::bincode::serialize(&(::membrane::MembraneMsgKind::Error as u8, err)),
@@ -268,9 +356,9 @@ fn to_token_stream(
           ::futures::executor::block_on(
             ::futures::future::Abortable::new(
               async move {
-                let result: ::std::result::Result<#output, #error> = #fn_name(#(#rust_inner_args),*).await;
+                let result: ::std::result::Result<#output, #error> = #await_result;
                 let isolate = ::membrane::allo_isolate::Isolate::new(membrane_port);
-                ::membrane::utils::send::<#output, #error>(isolate, result);
+                #send;
               }, membrane_future_registration)
           )
         },
@@ -283,9 +371,9 @@ fn to_token_stream(
     OutputStyle::Serialized => quote! {
       let membrane_join_handle = crate::RUNTIME.get().info_spawn(
         async move {
-          let result: ::std::result::Result<#output, #error> = #fn_name(#(#rust_inner_args),*).await;
+          let result: ::std::result::Result<#output, #error> = #await_result;
           let isolate = ::membrane::allo_isolate::Isolate::new(membrane_port);
-          ::membrane::utils::send::<#output, #error>(isolate, result);
+          #send;
         },
         ::membrane::runtime::Info { name: #rust_fn_name }
       );
@@ -296,12 +384,19 @@ fn to_token_stream(
   };
 
   let extern_c_fn_name = Ident::new(
-    format!("membrane_{}_{}", namespace, fn_name).as_str(),
+    // nested namespaces such as `accounts::admin` become `accounts_admin` in C
+    format!("membrane_{}_{}", namespace.replace("::", "_"), fn_name).as_str(),
     Span::call_site(),
   );
 
+  let symbol = utils::symbol_prefix().map_err(|err| syn::Error::new(span, err))?
+    + &extern_c_fn_name.to_string();
+  let track_symbol_prefix = utils::track_symbol_prefix();
+
   let c_fn = quote! {
-      #[no_mangle]
+      #track_symbol_prefix
+
+      #[export_name = #symbol]
       #[allow(clippy::not_unsafe_ptr_arg_deref)]
       pub extern "C" fn #extern_c_fn_name(#(#rust_outer_params),*) -> ::membrane::MembraneResponse {
         let func = || {
@@ -349,13 +444,27 @@ fn to_token_stream(
   let types = flatten_types(&output, vec![])?;
   let return_type = quote! { &[#(#types),*] };
 
-  let types = flatten_types(&error, vec![])?;
+  // an infallible function has no error type so that Dart doesn't expect one
+  let types = if infallible {
+    vec![]
+  } else {
+    flatten_types(&error, vec![])?
+  };
   let error_type = quote! { &[#(#types),*] };
 
   let rust_arg_types = inputs
     .iter()
-    .map(|Input { ty, .. }| ty)
-    .collect::<Vec<&Type>>();
+    .map(|Input { ty, .. }| traced_type(ty))
+    .collect::<Vec<Type>>();
+  let traced_error = traced_type(&error);
+
+  let args = inputs
+    .iter()
+    .map(|Input { variable, ty, .. }| {
+      let types = flatten_types(ty, vec![])?;
+      Ok(quote! { ::membrane::Argument { name: #variable, ty: &[#(#types),*] } })
+    })
+    .collect::<Result<Vec<TokenStream2>>>()?;
 
   let dart_outer_params = dart_outer_params.join(", ");
   let dart_transforms = dart_transforms.join(";\n    ");
@@ -379,6 +488,7 @@ fn to_token_stream(
                 is_sync: #sync,
                 return_type: #return_type,
                 error_type: #error_type,
+                args: &[#(#args),*],
                 namespace: #namespace,
                 disable_logging: #disable_logging,
                 timeout: #timeout,
@@ -395,10 +505,16 @@ fn to_token_stream(
                 tracer: &mut ::membrane::serde_reflection::Tracer,
                 samples: &mut ::membrane::serde_reflection::Samples
               | {
-                  tracer.trace_type::<#output>(samples).unwrap();
-                  tracer.trace_type::<#error>(samples).unwrap();
-                  // send all argument types over to serde-reflection, the primitives will be dropped
-                  #(tracer.trace_type::<#rust_arg_types>(samples).unwrap();)*
+                  let roots = vec![
+                    ::membrane::encoding::trace_type::<#traced_output>(tracer, samples)?,
+                    ::membrane::encoding::trace_type::<#traced_error>(tracer, samples)?,
+                    // send all argument types over to serde-reflection, the primitives will be dropped
+                    #(::membrane::encoding::trace_type::<#rust_arg_types>(tracer, samples)?,)*
+                  ];
+                  // the values which are sent to Dart must serialize the way they were traced
+                  ::membrane::encoding::check_round_trip::<#traced_output>()?;
+                  ::membrane::encoding::check_round_trip::<#traced_error>()?;
+                  Ok(roots)
               }
           }
       }
@@ -411,6 +527,24 @@ fn to_token_stream(
   ))]
   functions.extend::<TokenStream>(_deferred_trace.into());
 
+  // registered in every profile so that the library can report the hash of its functions to Dart
+  functions.extend::<TokenStream>(
+    quote! {
+      ::membrane::inventory::submit! {
+        ::membrane::Signature {
+          namespace: #namespace,
+          fn_name: #dart_fn_name,
+          is_stream: #is_stream,
+          is_sync: #sync,
+          return_type: #return_type,
+          error_type: #error_type,
+          args: &[#(#args),*],
+        }
+      }
+    }
+    .into(),
+  );
+
   functions = utils::maybe_inject_metadata(functions);
 
   Ok(functions)
@@ -423,16 +557,137 @@ struct ReprDartEnum {
 
 impl Parse for ReprDartEnum {
   fn parse(input: ParseStream) -> Result<Self> {
-    // parse and discard any other macros so that we can get to the enum
-    let _ = input.call(syn::Attribute::parse_outer);
+    // parse any other macros so that we can get to the enum
+    let attrs = input.call(syn::Attribute::parse_outer)?;
     let item_enum = input.parse::<syn::ItemEnum>()?;
 
+    let fields = item_enum
+      .variants
+      .iter()
+      .flat_map(|variant| variant.fields.iter());
+
+    serde_compatibility(
+      attrs.iter().chain(item_enum.attrs.iter()),
+      fields,
+      "#[dart_enum]",
+      "enum",
+    )?;
+
     Ok(ReprDartEnum {
       name: item_enum.ident,
     })
   }
 }
 
+#[derive(Debug)]
+struct ReprDartType {
+  name: Ident,
+}
+
+impl Parse for ReprDartType {
+  fn parse(input: ParseStream) -> Result<Self> {
+    let item = input.parse::<syn::Item>()?;
+
+    let (name, generics, attrs, fields, kind): (_, _, _, Vec<&syn::Field>, _) = match &item {
+      syn::Item::Struct(item_struct) => (
+        &item_struct.ident,
+        &item_struct.generics,
+        &item_struct.attrs,
+        item_struct.fields.iter().collect(),
+        "struct",
+      ),
+      syn::Item::Enum(item_enum) => (
+        &item_enum.ident,
+        &item_enum.generics,
+        &item_enum.attrs,
+        item_enum
+          .variants
+          .iter()
+          .flat_map(|variant| variant.fields.iter())
+          .collect(),
+        "enum",
+      ),
+      _ => {
+        return Err(syn::Error::new_spanned(
+          item,
+          "#[dart_type] can only be applied to a struct or an enum",
+        ))
+      }
+    };
+
+    if !generics.params.is_empty() {
+      return Err(syn::Error::new_spanned(
+        generics,
+        "#[dart_type] can't be applied to a generic type because Dart needs a concrete type to generate",
+      ));
+    }
+
+    serde_compatibility(attrs.iter(), fields.into_iter(), "#[dart_type]", kind)?;
+
+    Ok(ReprDartType { name: name.clone() })
+  }
+}
+
+///
+/// Reject the serde options which make a type's bincode differ from what Dart decodes.
+fn serde_compatibility<'a>(
+  attrs: impl Iterator<Item = &'a Attribute>,
+  fields: impl Iterator<Item = &'a syn::Field>,
+  macro_name: &str,
+  kind: &str,
+) -> Result<()> {
+  let incompatible = attrs
+    .flat_map(|attr| serde_options(attr, &["untagged", "tag"]))
+    .chain(
+      fields
+        .flat_map(|field| field.attrs.iter())
+        .flat_map(|attr| {
+          serde_options(
+            attr,
+            &["flatten", "skip_serializing", "skip_serializing_if"],
+          )
+        }),
+    )
+    .next();
+
+  match incompatible {
+    Some((attr, option)) => Err(syn::Error::new_spanned(
+      attr,
+      format!(
+        "`#[serde({})]` can't be used with {} because the {} is sent to Dart as bincode, which {}",
+        option,
+        macro_name,
+        kind,
+        if option.starts_with("skip") {
+          "would be missing the skipped field"
+        } else {
+          "doesn't encode the structure that this option needs to deserialize"
+        }
+      ),
+    )),
+    None => Ok(()),
+  }
+}
+
+///
+/// The options of a `#[serde(..)]` attribute which are in the given list.
+fn serde_options<'a>(attr: &'a Attribute, options: &[&str]) -> Vec<(&'a Attribute, String)> {
+  match &attr.meta {
+    syn::Meta::List(list) if list.path.is_ident("serde") => list
+      .tokens
+      .clone()
+      .into_iter()
+      .filter_map(|token| match token {
+        proc_macro2::TokenTree::Ident(ident) if options.contains(&ident.to_string().as_str()) => {
+          Some((attr, ident.to_string()))
+        }
+        _ => None,
+      })
+      .collect(),
+    _ => vec![],
+  }
+}
+
 ///
 /// Apply this macro to enums to mark them for Dart code generation.
 ///
@@ -478,9 +733,10 @@ pub fn dart_enum(attrs: TokenStream, input: TokenStream) -> TokenStream {
               },
               namespace: #namespace,
               trace: |
-                tracer: &mut ::membrane::serde_reflection::Tracer
+                tracer: &mut ::membrane::serde_reflection::Tracer,
+                samples: &mut ::membrane::serde_reflection::Samples
               | {
-                  tracer.trace_simple_type::<#name>().unwrap();
+                  ::membrane::encoding::trace_type::<#name>(tracer, samples)
               }
           }
       }
@@ -496,6 +752,196 @@ pub fn dart_enum(attrs: TokenStream, input: TokenStream) -> TokenStream {
   variants
 }
 
+///
+/// Derive this on enums which are nested inside the types of functions so that every variant is traced
+/// in each namespace which uses the enum, without naming the namespaces. Use `#[dart_enum]` instead to
+/// choose the Dart output of an enum or to generate it in a namespace which doesn't use it.
+#[proc_macro_derive(DartEnum)]
+pub fn derive_dart_enum(input: TokenStream) -> TokenStream {
+  let item_enum = parse_macro_input!(input as syn::ItemEnum);
+
+  if !item_enum.generics.params.is_empty() {
+    return syn::Error::new_spanned(
+      &item_enum.generics,
+      "DartEnum can't be derived for a generic enum because Dart needs a concrete type to generate",
+    )
+    .to_compile_error()
+    .into();
+  }
+
+  if let Err(err) = serde_compatibility(
+    item_enum.attrs.iter(),
+    item_enum
+      .variants
+      .iter()
+      .flat_map(|variant| variant.fields.iter()),
+    "#[derive(DartEnum)]",
+    "enum",
+  ) {
+    return err.to_compile_error().into();
+  }
+
+  let name = &item_enum.ident;
+  let enum_name = name.to_string();
+  let debug_location = quote! { concat!(file!(), ":", line!()) };
+
+  let _deferred_trace = quote! {
+      ::membrane::inventory::submit! {
+          ::membrane::DeferredDerivedEnumTrace {
+              name: #enum_name,
+              location: #debug_location,
+              trace: |
+                tracer: &mut ::membrane::serde_reflection::Tracer,
+                samples: &mut ::membrane::serde_reflection::Samples
+              | {
+                  ::membrane::encoding::trace_type::<#name>(tracer, samples)
+              }
+          }
+      }
+  };
+
+  #[allow(unused_mut)]
+  let mut output = TokenStream::new();
+
+  // by default only enable tracing in the dev profile or with an explicit flag
+  #[cfg(all(
+    any(debug_assertions, feature = "generate"),
+    not(feature = "skip-generate")
+  ))]
+  output.extend::<TokenStream>(_deferred_trace.into());
+
+  output
+}
+
+///
+/// Apply this macro to structs and enums which aren't used by any function to generate Dart classes for
+/// them anyway, for example a payload which is sent as opaque bytes.
+///
+/// Valid options:
+///   * `namespace`, used to select the Dart implementation code directory.
+#[proc_macro_attribute]
+pub fn dart_type(attrs: TokenStream, input: TokenStream) -> TokenStream {
+  let TypeOptions { namespace } = match extract_type_options(
+    parse_macro_input!(attrs with Punctuated::<MetaNameValue, Token![,]>::parse_terminated)
+      .into_iter()
+      .collect(),
+    TypeOptions::default(),
+  ) {
+    Ok(options) => options,
+    Err(err) => {
+      return syn::Error::new(Span::call_site(), err)
+        .to_compile_error()
+        .into();
+    }
+  };
+
+  let mut item = TokenStream::new();
+  item.extend(input.clone());
+
+  let ReprDartType { name } = parse_macro_input!(input as ReprDartType);
+  let type_name = name.to_string();
+  let debug_location = quote! { concat!(file!(), ":", line!()) };
+
+  let _deferred_trace = quote! {
+      ::membrane::inventory::submit! {
+          ::membrane::DeferredTypeTrace {
+              name: #type_name,
+              namespace: #namespace,
+              location: #debug_location,
+              trace: |
+                tracer: &mut ::membrane::serde_reflection::Tracer,
+                samples: &mut ::membrane::serde_reflection::Samples
+              | {
+                  ::membrane::encoding::trace_type::<#name>(tracer, samples)
+              }
+          }
+      }
+  };
+
+  // by default only enable tracing in the dev profile or with an explicit flag
+  #[cfg(all(
+    any(debug_assertions, feature = "generate"),
+    not(feature = "skip-generate")
+  ))]
+  item.extend::<TokenStream>(_deferred_trace.into());
+
+  item
+}
+
+///
+/// Apply this macro to a function which returns a valid value of a type whose `Deserialize` impl rejects
+/// the values Membrane traces with, such as a validated newtype or a non-zero integer. The value is
+/// given to the tracer before any types are traced. The function takes no arguments and is only called
+/// while generating code.
+///
+/// Example:
+///
+/// ```ignore
+/// #[dart_sample]
+/// fn email() -> Email {
+///   Email::parse("someone@example.com").unwrap()
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn dart_sample(attrs: TokenStream, input: TokenStream) -> TokenStream {
+  if !attrs.is_empty() {
+    return syn::Error::new(
+      Span::call_site(),
+      "#[dart_sample] does not take any options",
+    )
+    .to_compile_error()
+    .into();
+  }
+
+  let item = parse_macro_input!(input as syn::ItemFn);
+  let name = &item.sig.ident;
+  let ty = match &item.sig.output {
+    syn::ReturnType::Type(_, ty) if item.sig.inputs.is_empty() && item.sig.asyncness.is_none() => {
+      ty
+    }
+    _ => {
+      return syn::Error::new_spanned(
+        &item.sig,
+        "#[dart_sample] must be applied to a function without arguments which returns the sample value",
+      )
+      .to_compile_error()
+      .into();
+    }
+  };
+  let type_name = quote!(#ty).to_string();
+  // the function is only called while tracing, which is compiled out of release builds
+  let mut functions: TokenStream = quote! {
+    #[allow(dead_code)]
+    #item
+  }
+  .into();
+  let debug_location = quote! { concat!(file!(), ":", line!()) };
+
+  let _deferred_sample = quote! {
+      ::membrane::inventory::submit! {
+          ::membrane::DeferredSample {
+              type_name: #type_name,
+              location: #debug_location,
+              trace: |
+                tracer: &mut ::membrane::serde_reflection::Tracer,
+                samples: &mut ::membrane::serde_reflection::Samples
+              | {
+                  ::membrane::encoding::trace_sample::<#ty>(tracer, samples, &#name())
+              }
+          }
+      }
+  };
+
+  // by default only enable tracing in the dev profile or with an explicit flag
+  #[cfg(all(
+    any(debug_assertions, feature = "generate"),
+    not(feature = "skip-generate")
+  ))]
+  functions.extend::<TokenStream>(_deferred_sample.into());
+
+  functions
+}
+
 ///
 /// For use inside `#[async_dart]` functions. Used to create an emitter for use with `impl Emitter<Result<T, E>>` and `impl StreamEmitter<Result<T, E>>`
 /// return types.
@@ -561,3 +1007,28 @@ pub fn export_metadata(token_stream: TokenStream) -> TokenStream {
     utils::maybe_inject_metadata(token_stream)
   }
 }
+
+///
+/// Exports a Membrane runtime function under its name plus the `MEMBRANE_SYMBOL_PREFIX` given at build time.
+#[doc(hidden)]
+#[proc_macro_attribute]
+pub fn prefixed_symbol(_attrs: TokenStream, input: TokenStream) -> TokenStream {
+  let item = parse_macro_input!(input as syn::ItemFn);
+  let symbol = match utils::symbol_prefix() {
+    Ok(prefix) => prefix + &item.sig.ident.to_string(),
+    Err(err) => {
+      return syn::Error::new(Span::call_site(), err)
+        .to_compile_error()
+        .into()
+    }
+  };
+  let track_symbol_prefix = utils::track_symbol_prefix();
+
+  quote! {
+    #track_symbol_prefix
+
+    #[export_name = #symbol]
+    #item
+  }
+  .into()
+}
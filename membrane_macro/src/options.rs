@@ -8,6 +8,8 @@ pub(crate) struct FunctionOptions {
   pub timeout: Option<i32>,
   pub os_thread: bool,
   pub borrow: Vec<String>,
+  pub error: Option<syn::Type>,
+  pub error_display: bool,
 }
 
 #[derive(Debug, Default)]
@@ -16,6 +18,11 @@ pub(crate) struct EnumOptions {
   pub output: Option<String>,
 }
 
+#[derive(Debug, Default)]
+pub(crate) struct TypeOptions {
+  pub namespace: String,
+}
+
 pub(crate) fn extract_function_options(
   mut input: Vec<MetaNameValue>,
   mut options: FunctionOptions,
@@ -37,7 +44,7 @@ pub(crate) fn extract_function_options(
         ..
       }),
     )) if ident == "namespace" => {
-      options.namespace = val.value();
+      options.namespace = valid_namespace(val.value())?;
       options
     }
     Some((
@@ -49,8 +56,9 @@ pub(crate) fn extract_function_options(
     )) if ident == "borrow" => {
       let value = val.value();
       let borrow = value.split("::").collect::<Vec<&str>>();
-      // check the given borrow for correctness, then push the string
-      if let [_namespace, _type] = borrow[..] {
+      // check the given borrow for correctness, then push the string. The last segment is the
+      // type and everything before it is the (possibly nested) namespace
+      if borrow.len() > 1 && borrow.iter().all(|x| !x.trim().is_empty()) {
         options.borrow.push(value);
         options
       } else {
@@ -60,6 +68,27 @@ pub(crate) fn extract_function_options(
         ));
       }
     }
+    Some((
+      ident,
+      Lit(ExprLit {
+        lit: syn::Lit::Str(val),
+        ..
+      }),
+    )) if ident == "error" => {
+      let value = val.value();
+      match syn::parse_str::<syn::Type>(&value) {
+        Ok(error) => {
+          options.error = Some(error);
+          options
+        }
+        Err(_) => {
+          return Err(format!(
+            "`{value}` is not a valid option for `error`, must be of the form `error = \"path::Type\"`",
+            value = value
+          ));
+        }
+      }
+    }
     Some((
       ident,
       Lit(ExprLit {
@@ -70,6 +99,16 @@ pub(crate) fn extract_function_options(
       options.disable_logging = val.value();
       options
     }
+    Some((
+      ident,
+      Lit(ExprLit {
+        lit: syn::Lit::Bool(val),
+        ..
+      }),
+    )) if ident == "error_display" => {
+      options.error_display = val.value();
+      options
+    }
     Some((
       ident,
       Lit(ExprLit {
@@ -110,12 +149,12 @@ pub(crate) fn extract_function_options(
     }
     Some(_) if sync => {
       return Err(
-        r#"only `namespace=""`, `borrow="namespace::Type"`, and `disable_logging=true` are valid options"#.to_string(),
+        r#"only `namespace=""`, `borrow="namespace::Type"`, `error="path::Type"`, `error_display=true`, and `disable_logging=true` are valid options"#.to_string(),
       );
     }
     Some(_) => {
       return Err(
-        r#"only `namespace=""`, `borrow="namespace::Type"`, `disable_logging=true`, `os_thread=true`, and `timeout=1000` are valid options"#.to_string());
+        r#"only `namespace=""`, `borrow="namespace::Type"`, `error="path::Type"`, `error_display=true`, `disable_logging=true`, `os_thread=true`, and `timeout=1000` are valid options"#.to_string());
     }
     None => {
       // we've iterated over all options and didn't find a namespace (required)
@@ -153,7 +192,7 @@ pub(crate) fn extract_enum_options(
         ..
       }),
     )) if ident == "namespace" => {
-      options.namespace = val.value();
+      options.namespace = valid_namespace(val.value())?;
       options
     }
     Some((
@@ -183,6 +222,57 @@ pub(crate) fn extract_enum_options(
 
   extract_enum_options(input, options)
 }
+
+pub(crate) fn extract_type_options(
+  mut input: Vec<MetaNameValue>,
+  mut options: TypeOptions,
+) -> Result<TypeOptions, String> {
+  let option = match input.pop() {
+    Some(syn::MetaNameValue { path, value, .. }) => {
+      let ident = path.get_ident().unwrap().clone();
+      Some((ident, value))
+    }
+    _ => None,
+  };
+
+  let options = match option {
+    Some((
+      ident,
+      Lit(ExprLit {
+        lit: syn::Lit::Str(val),
+        ..
+      }),
+    )) if ident == "namespace" => {
+      options.namespace = valid_namespace(val.value())?;
+      options
+    }
+    Some(_) => {
+      return Err(r#"only `namespace=""` is a valid option"#.to_string());
+    }
+    None => {
+      // we've iterated over all options and didn't find a namespace (required)
+      if options.namespace.is_empty() {
+        return Err("#[dart_type] expects a `namespace` attribute".to_string());
+      }
+
+      return Ok(options);
+    }
+  };
+
+  extract_type_options(input, options)
+}
+
+fn valid_namespace(namespace: String) -> Result<String, String> {
+  if namespace.split("::").all(|x| !x.trim().is_empty()) {
+    Ok(namespace)
+  } else {
+    Err(format!(
+      "`{}` is not a valid namespace, nested namespaces must be of the form `namespace = \"parent::child\"`",
+      namespace
+    ))
+  }
+}
+
 fn invalid_option(macr: &str, opt: &str) -> Result<FunctionOptions, String> {
   Err(format!(
     "`{opt}` is not a valid option for `{m}`",
@@ -7,7 +7,7 @@ use syn::parse::{Parse, ParseBuffer, ParseStream, Result};
 use syn::punctuated::Punctuated;
 use syn::{Error, ExprPath, Ident, Token};
 
-pub fn parse_trait_return_type(input: ParseStream) -> Result<(OutputStyle, syn::Type, syn::Type)> {
+pub fn parse_trait_return_type(input: ParseStream) -> Result<(OutputStyle, syn::Type)> {
   input.parse::<Token![impl]>()?;
   let span = input.span();
   let stream_path = input.parse::<ExprPath>()?;
@@ -22,9 +22,9 @@ pub fn parse_trait_return_type(input: ParseStream) -> Result<(OutputStyle, syn::
       }
 
       input.parse::<Token![=]>()?;
-      let (t, e) = parse_type(input)?;
+      let return_type = parse_type(input)?;
       input.parse::<Token![>]>()?;
-      Ok((OutputStyle::Serialized, t, e))
+      Ok((OutputStyle::Serialized, return_type))
     }
     "Stream" => {
       let item_ident = input.parse::<Ident>()?;
@@ -33,19 +33,19 @@ pub fn parse_trait_return_type(input: ParseStream) -> Result<(OutputStyle, syn::
       }
 
       input.parse::<Token![=]>()?;
-      let (t, e) = parse_type(input)?;
+      let return_type = parse_type(input)?;
       input.parse::<Token![>]>()?;
-      Ok((OutputStyle::StreamSerialized, t, e))
+      Ok((OutputStyle::StreamSerialized, return_type))
     }
     "StreamEmitter" => {
-      let (t, e) = parse_type(input)?;
+      let return_type = parse_type(input)?;
       input.parse::<Token![>]>()?;
-      Ok((OutputStyle::StreamEmitterSerialized, t, e))
+      Ok((OutputStyle::StreamEmitterSerialized, return_type))
     }
     "Emitter" => {
-      let (t, e) = parse_type(input)?;
+      let return_type = parse_type(input)?;
       input.parse::<Token![>]>()?;
-      Ok((OutputStyle::EmitterSerialized, t, e))
+      Ok((OutputStyle::EmitterSerialized, return_type))
     }
     _ => {
       Err(Error::new(span, "trait found, expected `impl Stream<Item = Result>` or `impl StreamEmitter<Result>` or `impl Emitter<Result>`"))
@@ -53,43 +53,113 @@ pub fn parse_trait_return_type(input: ParseStream) -> Result<(OutputStyle, syn::
   }
 }
 
-pub fn parse_return_type(input: ParseStream) -> Result<(OutputStyle, syn::Type, syn::Type)> {
-  let (t, e) = parse_type(input)?;
-  Ok((OutputStyle::Serialized, t, e))
+pub fn parse_return_type(input: ParseStream) -> Result<(OutputStyle, syn::Type)> {
+  Ok((OutputStyle::Serialized, parse_type(input)?))
 }
 
-fn parse_type(input: ParseStream) -> Result<(syn::Type, syn::Type)> {
-  let outer_span = input.span();
-  let type_path = input.parse::<syn::TypePath>()?;
+fn parse_type(input: ParseStream) -> Result<syn::Type> {
+  let return_type = input.parse::<syn::Type>()?;
   // if the formatter inserts a trailing comma on multi-line types then discard it
   let _ = input.parse::<Token![,]>();
-  let return_type = &type_path.path.segments.last().unwrap();
-  if return_type.ident != "Result" {
-    return Err(Error::new(outer_span, "expected enum `Result`"));
-  }
 
-  if let syn::PathArguments::AngleBracketed(args) = &return_type.arguments {
-    let syn::AngleBracketedGenericArguments { args, .. } = args;
-    return Ok((validate_type(&args[0])?, validate_type(&args[1])?));
+  Ok(return_type)
+}
+
+///
+/// Split a return type into the types it returns and errors with, a function which doesn't return a
+/// `Result` is infallible and has no error type. An alias such as `type Result<T> = std::result::Result<T,
+/// AppError>` can't be expanded by a macro so its error type is given by the `error` option.
+pub fn result_types(
+  return_type: &syn::Type,
+  error: Option<&syn::Type>,
+) -> Result<(syn::Type, Option<syn::Type>)> {
+  let segment = match return_type {
+    syn::Type::Path(path) => path.path.segments.last(),
+    _ => None,
+  };
+  let args = match segment.map(|segment| &segment.arguments) {
+    Some(syn::PathArguments::AngleBracketed(args)) => args.args.iter().collect(),
+    _ => vec![],
+  };
+
+  match (segment, &args[..], error) {
+    (Some(segment), [t], Some(error)) if segment.ident == "Result" => {
+      Ok((validate_argument(t)?, Some(validate_type(error)?)))
+    }
+    (_, _, Some(_)) => Err(Error::new(
+      return_type.span(),
+      "`error` is only valid for a `Result<T>` alias",
+    )),
+    (Some(segment), [t, e], None) if segment.ident == "Result" => {
+      Ok((validate_argument(t)?, Some(validate_argument(e)?)))
+    }
+    // `anyhow::Result<T>` is the one alias whose error type is known
+    (Some(segment), [t], None) if segment.ident == "Result" && is_anyhow(return_type) => Ok((
+      validate_argument(t)?,
+      Some(syn::parse_quote!(::anyhow::Error)),
+    )),
+    (Some(segment), [_], None) if segment.ident == "Result" => Err(Error::new(
+      return_type.span(),
+      "expected `Result<T, E>`, the error type of a `Result<T>` alias must be given with `error = \"path::Type\"`, such as `error = \"anyhow::Error\"` for an imported `anyhow::Result`",
+    )),
+    _ => Ok((validate_type(return_type)?, None)),
   }
+}
 
-  Err(Error::new(outer_span, "expected enum `Result`"))
+///
+/// Whether an error type can't be serialized and is sent to Dart by its message instead, which is the case
+/// for `anyhow::Error` and `Box<dyn std::error::Error>`.
+pub fn is_display_error(error: &syn::Type) -> bool {
+  let segment = match error {
+    syn::Type::Path(path) => path.path.segments.last(),
+    _ => None,
+  };
+
+  match segment {
+    Some(segment) if segment.ident == "Error" => is_anyhow(error),
+    Some(segment) if segment.ident == "Box" => matches!(
+      &segment.arguments,
+      syn::PathArguments::AngleBracketed(args) if matches!(
+        args.args.first(),
+        Some(syn::GenericArgument::Type(syn::Type::TraitObject(_)))
+      )
+    ),
+    _ => false,
+  }
 }
 
-fn validate_type(type_: &syn::GenericArgument) -> Result<syn::Type> {
-  if let syn::GenericArgument::Type(type_) = type_ {
-    match type_ {
-      syn::Type::Path(_path) => return Ok(type_.clone()),
-      syn::Type::Tuple(tuple) if !tuple.elems.is_empty() => {
-        return Err(Error::new(
-        type_.span(),
-        "A tuple may not be returned from an `async_dart` function. If a tuple is needed return a struct containing the tuple.",
-      ));
-      }
-      // empty unit () is supported as a return type
-      syn::Type::Tuple(_tuple) => return Ok(type_.clone()),
-      _ => (),
+// a path such as `anyhow::Error` or `::anyhow::Result<T>`
+fn is_anyhow(type_: &syn::Type) -> bool {
+  match type_ {
+    syn::Type::Path(path) => {
+      let segments = path.path.segments.iter().collect::<Vec<_>>();
+      segments.len() == 2 && segments[0].ident == "anyhow"
     }
+    _ => false,
+  }
+}
+
+fn validate_argument(argument: &syn::GenericArgument) -> Result<syn::Type> {
+  match argument {
+    syn::GenericArgument::Type(type_) => validate_type(type_),
+    _ => Err(Error::new(
+      argument.span(),
+      format!(
+        "expected a struct, vec, or scalar type but found `{}`",
+        quote! { #argument }
+      ),
+    )),
+  }
+}
+
+fn validate_type(type_: &syn::Type) -> Result<syn::Type> {
+  reject_char(type_)?;
+
+  match type_ {
+    syn::Type::Path(_) | syn::Type::Array(_) => return Ok(type_.clone()),
+    // a tuple is returned as a Dart record and empty unit () as void
+    syn::Type::Tuple(_tuple) => return Ok(type_.clone()),
+    _ => (),
   }
 
   Err(Error::new(
@@ -101,6 +171,32 @@ fn validate_type(type_: &syn::GenericArgument) -> Result<syn::Type> {
   ))
 }
 
+// bincode encodes a char as UTF-8 which the serde runtime can't decode
+fn reject_char(type_: &syn::Type) -> Result<()> {
+  match type_ {
+    syn::Type::Path(path) => match path.path.segments.last() {
+      Some(segment) if segment.ident == "char" && segment.arguments.is_empty() => Err(Error::new(
+        type_.span(),
+        "`char` is not a supported return type for Dart interop, please use String instead.",
+      )),
+      Some(syn::PathSegment {
+        arguments: syn::PathArguments::AngleBracketed(args),
+        ..
+      }) => args.args.iter().try_for_each(|arg| match arg {
+        syn::GenericArgument::Type(type_) => reject_char(type_),
+        _ => Ok(()),
+      }),
+      _ => Ok(()),
+    },
+    syn::Type::Array(array) => reject_char(&array.elem),
+    syn::Type::Slice(slice) => reject_char(&slice.elem),
+    syn::Type::Reference(reference) => reject_char(&reference.elem),
+    syn::Type::Paren(paren) => reject_char(&paren.elem),
+    syn::Type::Tuple(tuple) => tuple.elems.iter().try_for_each(reject_char),
+    _ => Ok(()),
+  }
+}
+
 pub(crate) fn parse_args(arg_buffer: ParseBuffer) -> Result<Vec<Input>> {
   let args: Punctuated<syn::FnArg, Token![,]> =
     arg_buffer.parse_terminated(syn::FnArg::parse, Token![,])?;
@@ -1,4 +1,5 @@
 use crate::quote::quote;
+use membrane_types::{proc_macro2, syn};
 use once_cell::sync::OnceCell;
 use proc_macro::TokenStream;
 use std::env;
@@ -7,6 +8,35 @@ use toml::Value;
 
 static BOOTSTRAPPED: OnceCell<bool> = OnceCell::new();
 
+//
+// The prefix applied to every exported symbol, set at build time with `MEMBRANE_SYMBOL_PREFIX`
+//
+pub(crate) fn symbol_prefix() -> Result<String, String> {
+  let prefix = env::var("MEMBRANE_SYMBOL_PREFIX").unwrap_or_default();
+  let valid = prefix
+    .chars()
+    .enumerate()
+    .all(|(index, c)| c == '_' || c.is_ascii_alphabetic() || (index > 0 && c.is_ascii_digit()));
+
+  if valid {
+    Ok(prefix)
+  } else {
+    Err(format!(
+      "MEMBRANE_SYMBOL_PREFIX `{}` is not a valid C identifier prefix",
+      prefix
+    ))
+  }
+}
+
+//
+// Emitted alongside prefixed symbols, the `option_env!` lets cargo know to rebuild the crate when the prefix changes
+//
+pub(crate) fn track_symbol_prefix() -> proc_macro2::TokenStream {
+  quote! {
+    const _: ::std::option::Option<&str> = ::std::option_env!("MEMBRANE_SYMBOL_PREFIX");
+  }
+}
+
 //
 // Fetch the crate type from the Cargo.toml of the currently-compiling crate
 //
@@ -38,19 +68,58 @@ pub(crate) fn maybe_inject_metadata(mut token_stream: TokenStream) -> TokenStrea
     // we only add the metadata once and only then when we're a crate that produces a dylib, otherwise
     // we run the risk of generating duplicate functions within shared workspace crates that all use this macro
     if is_cdylib() {
+      let prefix = match symbol_prefix() {
+        Ok(prefix) => prefix,
+        Err(err) => {
+          token_stream.extend::<TokenStream>(
+            syn::Error::new(proc_macro2::Span::call_site(), err)
+              .to_compile_error()
+              .into(),
+          );
+          return token_stream;
+        }
+      };
+      let enums = prefix.clone() + "membrane_metadata_enums";
+      let functions = prefix.clone() + "membrane_metadata_functions";
+      let samples = prefix.clone() + "membrane_metadata_samples";
+      let types = prefix.clone() + "membrane_metadata_types";
+      let derived_enums = prefix.clone() + "membrane_metadata_derived_enums";
+      let version = prefix.clone() + "membrane_metadata_version";
+      let schema_hashes = prefix.clone() + "membrane_metadata_schema_hashes";
+      let signature_hashes = prefix.clone() + "membrane_metadata_signature_hashes";
+      let membrane_version = prefix + "membrane_metadata_membrane_version";
+      let track_symbol_prefix = track_symbol_prefix();
+
       token_stream.extend::<TokenStream>(
           quote! {
-            #[no_mangle]
+            #track_symbol_prefix
+
+            #[export_name = #enums]
             pub fn membrane_metadata_enums() -> Box<Vec<&'static ::membrane::DeferredEnumTrace>> {
               Box::new(::membrane::metadata::enums())
             }
 
-            #[no_mangle]
+            #[export_name = #functions]
             pub fn membrane_metadata_functions() -> Box<Vec<&'static ::membrane::DeferredTrace>> {
               Box::new(::membrane::metadata::functions())
             }
 
-            #[no_mangle]
+            #[export_name = #samples]
+            pub fn membrane_metadata_samples() -> Box<Vec<&'static ::membrane::DeferredSample>> {
+              Box::new(::membrane::metadata::samples())
+            }
+
+            #[export_name = #types]
+            pub fn membrane_metadata_types() -> Box<Vec<&'static ::membrane::DeferredTypeTrace>> {
+              Box::new(::membrane::metadata::types())
+            }
+
+            #[export_name = #derived_enums]
+            pub fn membrane_metadata_derived_enums() -> Box<Vec<&'static ::membrane::DeferredDerivedEnumTrace>> {
+              Box::new(::membrane::metadata::derived_enums())
+            }
+
+            #[export_name = #version]
             pub extern "C" fn membrane_metadata_version() -> *mut std::os::raw::c_char {
               // allow the developer to override the embedded version string with one of their own choosing
               const LIB_VERSION: Option<&str> = option_env!("MEMBRANE_CDYLIB_VERSION");
@@ -67,7 +136,21 @@ pub(crate) fn maybe_inject_metadata(mut token_stream: TokenStream) -> TokenStrea
               }
             }
 
-            #[no_mangle]
+            #[export_name = #schema_hashes]
+            pub extern "C" fn membrane_metadata_schema_hashes() -> *mut std::os::raw::c_char {
+              // written by the generator as `{library}.schema_hashes`, embedded so that release builds are checked too
+              const HASHES: Option<&str> = option_env!("MEMBRANE_SCHEMA_HASHES");
+              let hashes = ::std::ffi::CString::new(HASHES.unwrap_or_default()).expect("Invalid string received");
+              hashes.into_raw()
+            }
+
+            #[export_name = #signature_hashes]
+            pub extern "C" fn membrane_metadata_signature_hashes() -> *mut std::os::raw::c_char {
+              let hashes = ::std::ffi::CString::new(::membrane::metadata::signature_hashes()).expect("Invalid string received");
+              hashes.into_raw()
+            }
+
+            #[export_name = #membrane_version]
             pub extern "C" fn membrane_metadata_membrane_version() -> *mut std::os::raw::c_char {
               let version = ::std::ffi::CString::new(::membrane::metadata::version()).expect("Invalid string received");
               version.into_raw()
@@ -32,27 +32,49 @@ impl From<CHeaderTypes> for Vec<String> {
   }
 }
 
+///
+/// The C type of a scalar which is passed by value, `None` for types which are serialized.
+fn c_scalar(ty: &str) -> Option<&'static str> {
+  let type_ = match ty {
+    "i8" => "int8_t",
+    "i16" => "int16_t",
+    "i32" => "int32_t",
+    "i64" => "int64_t",
+    "u8" => "uint8_t",
+    "u16" => "uint16_t",
+    "u32" => "uint32_t",
+    "u64" => "uint64_t",
+    "f32" => "float",
+    "f64" => "double",
+    "bool" => "uint8_t",
+    // the unicode scalar value
+    "char" => "uint32_t",
+    _ => return None,
+  };
+
+  Some(type_)
+}
+
 fn c_type(ty: &[&str], type_: &syn::Type) -> syn::Result<String> {
   let type_ = match ty[..] {
-    ["String"] => "const char *",
-    ["i64"] => "const int64_t ",
-    ["f64"] => "const double ",
-    ["bool"] => "const uint8_t ",
-    ["Vec", ..] => "const uint8_t *",
-    [serialized, ..] if serialized != "Option" => "const uint8_t *",
-    ["Option", "String"] => "const char *",
-    ["Option", "i64"] => "const int64_t *",
-    ["Option", "f64"] => "const double *",
-    ["Option", "bool"] => "const uint8_t *",
-    ["Option", ..] => "const uint8_t *",
+    ["String"] | ["&str"] => "const char *".to_string(),
+    [scalar] if c_scalar(scalar).is_some() => format!("const {} ", c_scalar(scalar).unwrap()),
+    ["Vec", ..] | ["Map", ..] | ["Set", ..] | ["Array", ..] | ["(", ..] => {
+      "const uint8_t *".to_string()
+    }
+    [serialized, ..] if serialized != "Option" => "const uint8_t *".to_string(),
+    ["Option", "String"] | ["Option", "&str"] => "const char *".to_string(),
+    ["Option", scalar] if c_scalar(scalar).is_some() => {
+      format!("const {} *", c_scalar(scalar).unwrap())
+    }
+    ["Option", ..] => "const uint8_t *".to_string(),
     _ => {
       return Err(syn::Error::new_spanned(
         type_,
         "not a supported argument type for Dart interop",
       ))
     }
-  }
-  .to_string();
+  };
 
   Ok(type_)
 }
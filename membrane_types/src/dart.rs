@@ -1,4 +1,5 @@
-use crate::{rust::flatten_types, Input};
+use crate::rust::{flatten_types, tuple_elements};
+use crate::Input;
 use heck::{ToLowerCamelCase, ToUpperCamelCase};
 use syn::Type;
 
@@ -92,6 +93,8 @@ pub fn dart_type(types: &[&str]) -> String {
   let ty;
   match types[..] {
     ["String"] => "String",
+    ["&str"] => "String",
+    ["char"] => "int",
     ["i8"] => "int",
     ["u8"] => "int",
     ["i16"] => "int",
@@ -118,18 +121,56 @@ pub fn dart_type(types: &[&str]) -> String {
       ty = format!("List<{}>", dart_type(&types[1..]));
       &ty
     }
+    ["Map", key, ..] => {
+      ty = format!("Map<{}, {}>", dart_type(&[key]), dart_type(&types[2..]));
+      &ty
+    }
+    ["Set", ..] => {
+      ty = format!("Set<{}>", dart_type(&types[1..]));
+      &ty
+    }
+    ["Array", _, "u8"] => "Uint8List",
+    ["Array", _, ..] => {
+      ty = format!("List<{}>", dart_type(&types[2..]));
+      &ty
+    }
+    // a tuple is a record with positional fields
+    ["(", ..] => {
+      ty = match &tuple_elements(types)[..] {
+        [element] => format!("({},)", dart_type(element)),
+        elements => format!(
+          "({})",
+          elements
+            .iter()
+            .map(|x| dart_type(x))
+            .collect::<Vec<String>>()
+            .join(", ")
+        ),
+      };
+      &ty
+    }
     _ => types[0],
   }
   .to_string()
 }
 
+// the argument types which are passed as a Dart built-in or a serde runtime class
+const SCALARS: &[&str] = &[
+  "String", "&str", "char", "bool", "i8", "i16", "i32", "i64", "i128", "u8", "u16", "u32", "u64",
+  "u128", "f32", "f64",
+];
+
 fn dart_param_type(types: &[&str], type_: &syn::Type) -> syn::Result<String> {
   let ty;
   let result = match types[..] {
-    ["String"] => "required String",
-    ["i64"] => "required int",
-    ["f64"] => "required double",
-    ["bool"] => "required bool",
+    [scalar] if SCALARS.contains(&scalar) => {
+      ty = format!("required {}", dart_type(types));
+      &ty
+    }
+    ["Vec", _] if typed_data(types).is_some() => {
+      ty = format!("required {}", typed_data(types).unwrap());
+      &ty
+    }
     ["Vec", "Option", ..] => {
       ty = format!("required List<{}?>", dart_type(&types[2..]));
       &ty
@@ -138,14 +179,18 @@ fn dart_param_type(types: &[&str], type_: &syn::Type) -> syn::Result<String> {
       ty = format!("required List<{}>", dart_type(&types[1..]));
       &ty
     }
+    ["Map", ..] | ["Set", ..] | ["Array", ..] | ["(", ..] => {
+      ty = format!("required {}", dart_type(types));
+      &ty
+    }
     [serialized] if serialized != "Option" => {
       ty = format!("required {} ", serialized);
       &ty
     }
-    ["Option", "String"] => "String?",
-    ["Option", "i64"] => "int?",
-    ["Option", "f64"] => "double?",
-    ["Option", "bool"] => "bool?",
+    ["Option", scalar] if SCALARS.contains(&scalar) => {
+      ty = format!("{}?", dart_type(&types[1..]));
+      &ty
+    }
     ["Option", ..] => {
       ty = format!("{}? ", dart_type(&types[1..]));
       &ty
@@ -164,30 +209,21 @@ fn dart_param_type(types: &[&str], type_: &syn::Type) -> syn::Result<String> {
 
 fn cast_dart_type_to_c(types: &[&str], variable: &str, ty: &Type) -> syn::Result<String> {
   match ty {
+    syn::Type::Reference(_) if types.contains(&"&str") => (),
     &syn::Type::Reference(_) => return unsupported_type_error(ty, "a struct"),
-    &syn::Type::Tuple(_) | &syn::Type::Slice(_) | &syn::Type::Array(_) => {
-      return unsupported_type_error(ty, "a struct")
-    }
+    &syn::Type::Slice(_) => return unsupported_type_error(ty, "a struct"),
     _ => (),
   };
 
   let cast = match types[..] {
-    ["&str"] => return unsupported_type_error(ty, "String"),
-    ["char"] => return unsupported_type_error(ty, "String"),
-    ["i8"] => return unsupported_type_error(ty, "i64"),
-    ["i16"] => return unsupported_type_error(ty, "i64"),
-    ["i32"] => return unsupported_type_error(ty, "i64"),
-    ["i128"] => return unsupported_type_error(ty, "i64"),
-    ["u8"] => return unsupported_type_error(ty, "i64"),
-    ["u16"] => return unsupported_type_error(ty, "i64"),
-    ["u32"] => return unsupported_type_error(ty, "i64"),
-    ["u64"] => return unsupported_type_error(ty, "i64"),
-    ["u128"] => return unsupported_type_error(ty, "i64"),
-    ["f32"] => return unsupported_type_error(ty, "f64"),
+    // a `&str` can't outlive the call so it may only be passed on its own
+    _ if types.contains(&"&str") && !matches!(types, ["&str"] | ["Option", "&str"]) => {
+      return unsupported_type_error(ty, "String")
+    }
     //
     // supported types
     //
-    ["String"] => {
+    ["String"] | ["&str"] => {
       format!(
         r#"(){{
           final ptr = {variable}.toNativeUtf8().cast<Char>();
@@ -203,7 +239,27 @@ fn cast_dart_type_to_c(types: &[&str], variable: &str, ty: &Type) -> syn::Result
     ),
     ["i64"] => variable.to_lower_camel_case(),
     ["f64"] => variable.to_lower_camel_case(),
-    ["Vec", ..] => format!(
+    [scalar] if native_type(scalar).is_some() => checked_value(scalar, variable),
+    ["i128"] | ["u128"] => format!(
+      r#"(){{
+      final serializer = BincodeSerializer();
+      {serializer};
+      final data = serializer.bytes;
+      {ser_partial}
+    }}()"#,
+      serializer = serializer(types, &variable.to_lower_camel_case(), ty)?,
+      ser_partial = serialization_partial(),
+    ),
+    // typed data is passed as its bytes rather than being serialized element by element
+    ["Vec", _] if typed_data(types).is_some() => format!(
+      r#"(){{
+      final data = {variable}.buffer.asUint8List({variable}.offsetInBytes, {variable}.lengthInBytes);
+      {ser_partial}
+    }}()"#,
+      variable = variable.to_lower_camel_case(),
+      ser_partial = serialization_partial(),
+    ),
+    ["Vec", ..] | ["Map", ..] | ["Set", ..] | ["Array", ..] | ["(", ..] => format!(
       r#"(){{
       final serializer = BincodeSerializer();
       {serializer}
@@ -221,7 +277,7 @@ fn cast_dart_type_to_c(types: &[&str], variable: &str, ty: &Type) -> syn::Result
       variable = variable.to_lower_camel_case(),
       ser_partial = serialization_partial(),
     ),
-    ["Option", "String"] => {
+    ["Option", "String"] | ["Option", "&str"] => {
       format!(
         r#"(){{
       if ({variable} == null) {{
@@ -246,31 +302,27 @@ fn cast_dart_type_to_c(types: &[&str], variable: &str, ty: &Type) -> syn::Result
     }}()"#,
       variable = variable.to_lower_camel_case()
     ),
-    ["Option", "i64"] => format!(
-      r#"(){{
-      if ({variable} == null) {{
-        return nullptr;
-      }}
-      final ptr = calloc<Int64>();
-      _toFree.add(ptr);
-      ptr.asTypedList(1).setAll(0, [{variable}]);
-      return ptr;
-    }}()"#,
-      variable = variable.to_lower_camel_case()
-    ),
-    ["Option", "f64"] => format!(
+    ["Option", scalar] if native_type(scalar).is_some() => format!(
       r#"(){{
       if ({variable} == null) {{
         return nullptr;
       }}
-      final ptr = calloc<Double>();
+      final ptr = calloc<{native_type}>();
       _toFree.add(ptr);
-      ptr.asTypedList(1).setAll(0, [{variable}]);
+      ptr.asTypedList(1).setAll(0, [{value}]);
       return ptr;
     }}()"#,
-      variable = variable.to_lower_camel_case()
+      variable = variable.to_lower_camel_case(),
+      native_type = native_type(scalar).unwrap(),
+      value = checked_value(scalar, variable),
     ),
-    ["Option", "Vec", ..] => format!(
+    ["Option", "Vec", ..]
+    | ["Option", "Map", ..]
+    | ["Option", "Set", ..]
+    | ["Option", "Array", ..]
+    | ["Option", "(", ..]
+    | ["Option", "i128"]
+    | ["Option", "u128"] => format!(
       r#"(){{
       if ({variable} == null) {{
         return nullptr;
@@ -306,6 +358,138 @@ fn cast_dart_type_to_c(types: &[&str], variable: &str, ty: &Type) -> syn::Result
   Ok(cast)
 }
 
+///
+/// The Dart which passes an argument as the bincode of `types`, for an argument whose type serializes as
+/// another type such as a `#[serde(transparent)]` newtype. The argument may be null when it's `optional`.
+pub fn serialized_dart_type_to_c(
+  types: &[&str],
+  variable: &str,
+  optional: bool,
+  ty: &Type,
+) -> syn::Result<String> {
+  let variable = variable.to_lower_camel_case();
+  // unlike an optional argument the option tag is part of the encoding
+  let serializer = tagged_serializer(types, &variable, ty)?;
+
+  Ok(format!(
+    r#"(){{
+      {null_check}final serializer = BincodeSerializer();
+      {serializer}
+      final data = serializer.bytes;
+      {ser_partial}
+    }}()"#,
+    null_check = if optional {
+      format!(
+        "if ({variable} == null) {{\n        return nullptr;\n      }}\n      ",
+        variable = variable
+      )
+    } else {
+      String::new()
+    },
+    serializer = serializer,
+    ser_partial = serialization_partial(),
+  ))
+}
+
+///
+/// The Dart typed data list of a `Vec` of bytes or numbers, which is passed without serializing each
+/// element. A `u64` isn't typed data since it's returned as the `Uint64` class.
+pub fn typed_data(types: &[&str]) -> Option<&'static str> {
+  let list = match types {
+    ["Vec", "u8"] => "Uint8List",
+    ["Vec", "i8"] => "Int8List",
+    ["Vec", "u16"] => "Uint16List",
+    ["Vec", "i16"] => "Int16List",
+    ["Vec", "u32"] => "Uint32List",
+    ["Vec", "i32"] => "Int32List",
+    ["Vec", "i64"] => "Int64List",
+    ["Vec", "f32"] => "Float32List",
+    ["Vec", "f64"] => "Float64List",
+    _ => return None,
+  };
+
+  Some(list)
+}
+
+///
+/// The `dart:ffi` type of a scalar which is passed by value.
+fn native_type(ty: &str) -> Option<&'static str> {
+  let native = match ty {
+    "i8" => "Int8",
+    "i16" => "Int16",
+    "i32" => "Int32",
+    "i64" => "Int64",
+    "u8" => "Uint8",
+    "u16" => "Uint16",
+    "u32" => "Uint32",
+    "u64" => "Uint64",
+    "f32" => "Float",
+    "f64" => "Double",
+    "char" => "Uint32",
+    _ => return None,
+  };
+
+  Some(native)
+}
+
+///
+/// The Dart expression which passes a scalar on after checking that it fits the Rust type, since
+/// `dart:ffi` silently truncates an integer that doesn't fit.
+fn checked_value(ty: &str, variable: &str) -> String {
+  let variable = variable.to_lower_camel_case();
+  let interval = |min: &str, max: &str| {
+    format!(
+      "RangeError.checkValueInInterval({variable}, {min}, {max}, '{variable}')",
+      variable = variable,
+      min = min,
+      max = max
+    )
+  };
+
+  match ty {
+    "i8" => interval("-128", "127"),
+    "i16" => interval("-32768", "32767"),
+    "i32" => interval("-2147483648", "2147483647"),
+    "u8" => interval("0", "255"),
+    "u16" => interval("0", "65535"),
+    "u32" => interval("0", "4294967295"),
+    // a `Uint64` holds a `BigInt` which is passed as the `int` with the same bits. Only `Uint64.fromBigInt`
+    // truncates to 64 bits, the `const Uint64(...)` constructor keeps a negative or larger `BigInt` as it
+    // is, so that's checked here. `toInt()` alone would clamp every value above `i64::MAX`.
+    "u64" => format!(
+      r#"(){{
+      final value = {variable}.toBigInt();
+      if (value.isNegative || value.bitLength > 64) {{
+        throw RangeError.value(value.toInt(), '{variable}', 'Does not fit in a u64');
+      }}
+      return value.toSigned(64).toInt();
+    }}()"#,
+      variable = variable
+    ),
+    "f32" => format!(
+      r#"(){{
+      if ({variable}.isFinite && {variable}.abs() > 3.4028234663852886e+38) {{
+        throw RangeError.value({variable}, '{variable}', 'Does not fit in an f32');
+      }}
+      return {variable};
+    }}()"#,
+      variable = variable
+    ),
+    "char" => format!(
+      r#"(){{
+      final value = {interval};
+      if (value >= 0xD800 && value <= 0xDFFF) {{
+        throw ArgumentError.value(value, '{variable}', 'Is a surrogate code point which is not a valid char');
+      }}
+      return value;
+    }}()"#,
+      interval = interval("0", "0x10FFFF"),
+      variable = variable
+    ),
+    _ => variable,
+  }
+}
+
 fn unsupported_type_error(ty: &syn::Type, new_ty: &str) -> Result<String, syn::Error> {
   Err(syn::Error::new_spanned(
     ty,
@@ -327,20 +511,43 @@ return ptr;"#
 
 fn serializer(types: &[&str], variable: &str, ty: &Type) -> Result<String, syn::Error> {
   match types[..] {
-    ["i8"] => unsupported_type_error(ty, "i64"),
-    ["i16"] => unsupported_type_error(ty, "i64"),
-    ["i32"] => unsupported_type_error(ty, "i64"),
-    ["u8"] => unsupported_type_error(ty, "i64"),
-    ["u16"] => unsupported_type_error(ty, "i64"),
-    ["u32"] => unsupported_type_error(ty, "i64"),
-    ["f32"] => unsupported_type_error(ty, "f64"),
-    ["String"] => Ok("serializer.serializeString(value)".to_string()),
-    ["bool"] => Ok("serializer.serializeBool(value)".to_string()),
-    ["i64"] => Ok("serializer.serializeInt64(value)".to_string()),
-    ["u64"] => Ok("serializer.serializeUint64(value)".to_string()),
-    ["i128"] => Ok("serializer.serializeInt128(value)".to_string()),
-    ["u128"] => Ok("serializer.serializeUint128(value)".to_string()),
-    ["f64"] => Ok("serializer.serializeFloat64(value)".to_string()),
+    // bincode encodes a char as UTF-8 which the serde runtime doesn't
+    ["char"] => unsupported_type_error(ty, "String"),
+    ["i8"] => Ok(format!(
+      "serializer.serializeInt8({})",
+      checked_value("i8", variable)
+    )),
+    ["i16"] => Ok(format!(
+      "serializer.serializeInt16({})",
+      checked_value("i16", variable)
+    )),
+    ["i32"] => Ok(format!(
+      "serializer.serializeInt32({})",
+      checked_value("i32", variable)
+    )),
+    ["u8"] => Ok(format!(
+      "serializer.serializeUint8({})",
+      checked_value("u8", variable)
+    )),
+    ["u16"] => Ok(format!(
+      "serializer.serializeUint16({})",
+      checked_value("u16", variable)
+    )),
+    ["u32"] => Ok(format!(
+      "serializer.serializeUint32({})",
+      checked_value("u32", variable)
+    )),
+    ["f32"] => Ok(format!(
+      "serializer.serializeFloat32({})",
+      checked_value("f32", variable)
+    )),
+    ["String"] => Ok(format!("serializer.serializeString({})", variable)),
+    ["bool"] => Ok(format!("serializer.serializeBool({})", variable)),
+    ["i64"] => Ok(format!("serializer.serializeInt64({})", variable)),
+    ["u64"] => Ok(format!("serializer.serializeUint64({})", variable)),
+    ["i128"] => Ok(format!("serializer.serializeInt128({})", variable)),
+    ["u128"] => Ok(format!("serializer.serializeUint128({})", variable)),
+    ["f64"] => Ok(format!("serializer.serializeFloat64({})", variable)),
     ["Vec", "Option", ..] => Ok(format!(
       "serializer.serializeLength({variable}.length);
       {variable}.forEach((value) {{
@@ -360,6 +567,58 @@ fn serializer(types: &[&str], variable: &str, ty: &Type) -> Result<String, syn::
       variable = variable,
       serializer = serializer(&types[1..], "value", ty)?,
     )),
+    ["Map", ..] => Ok(format!(
+      "serializer.serializeLength({variable}.length);
+      {variable}.forEach((key, value) {{
+        {key_serializer};
+        {value_serializer}
+      }});",
+      variable = variable,
+      key_serializer = serializer(&types[1..2], "key", ty)?,
+      value_serializer = tagged_serializer(&types[2..], "value", ty)?,
+    )),
+    ["Set", ..] => Ok(format!(
+      "serializer.serializeLength({variable}.length);
+      {variable}.forEach((value) {{
+        {serializer}
+      }});",
+      variable = variable,
+      serializer = tagged_serializer(&types[1..], "value", ty)?,
+    )),
+    // bincode writes an array like a tuple, without its length
+    ["Array", len, ..] => Ok(format!(
+      "if ({variable}.length != {len}) {{
+        throw MembraneArrayLengthException('`{variable}` must have a length of {len} but has a length of ${{{variable}.length}}', {len}, {variable}.length);
+      }}
+      {variable}.forEach((value) {{
+        {serializer}
+      }});",
+      variable = variable,
+      len = len,
+      serializer = tagged_serializer(&types[2..], "value", ty)?,
+    )),
+    // each field is bound to a local so that an optional field can be promoted
+    ["(", ..] => Ok(format!(
+      "{{
+        {fields}
+      }}",
+      fields = tuple_elements(types)
+        .iter()
+        .enumerate()
+        .map(|(i, element)| {
+          let field = format!("{}Field{}", variable, i + 1);
+          Ok(format!(
+            "final {field} = {variable}.${position};
+        {serializer}",
+            field = field,
+            variable = variable,
+            position = i + 1,
+            serializer = tagged_serializer(element, &field, ty)?,
+          ))
+        })
+        .collect::<Result<Vec<String>, syn::Error>>()?
+        .join("\n        "),
+    )),
     ["Option", ..] => {
       Ok(format!(
         // the containing serialization code does an early return if the
@@ -368,6 +627,23 @@ fn serializer(types: &[&str], variable: &str, ty: &Type) -> Result<String, syn::
         serializer = serializer(&types[1..], variable, ty)?,
       ))
     }
-    _ => Ok("value.serialize(serializer)".to_string()),
+    _ => Ok(format!("{}.serialize(serializer)", variable)),
+  }
+}
+
+///
+/// Serialize a value which is written along with its option tag when it's an `Option`, such as the value
+/// of a map.
+fn tagged_serializer(types: &[&str], variable: &str, ty: &Type) -> Result<String, syn::Error> {
+  match types {
+    ["Option", inner @ ..] => Ok(format!(
+      r#"serializer.serializeOptionTag({variable} != null);
+      if ({variable} != null) {{
+        {serializer};
+      }}"#,
+      variable = variable,
+      serializer = serializer(inner, variable, ty)?,
+    )),
+    _ => Ok(format!("{};", serializer(types, variable, ty)?)),
   }
 }
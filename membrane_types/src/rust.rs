@@ -1,4 +1,5 @@
-use crate::utils::extract_type_from_option;
+use crate::dart::typed_data;
+use crate::utils::{extract_type_from_option, extract_wrapper, is_str_reference};
 use crate::Input;
 
 use proc_macro2::{Span, TokenStream as TokenStream2};
@@ -7,7 +8,7 @@ use syn::{Ident, Type};
 
 pub struct RustExternParams(Vec<TokenStream2>);
 pub struct RustTransforms(Vec<TokenStream2>);
-pub struct RustArgs(Vec<Ident>);
+pub struct RustArgs(Vec<TokenStream2>);
 
 impl std::convert::TryFrom<&Vec<Input>> for RustExternParams {
   type Error = syn::Error;
@@ -45,7 +46,7 @@ impl std::convert::TryFrom<&Vec<Input>> for RustTransforms {
           .map(|x| x.as_str())
           .collect::<Vec<&str>>(),
         &input.variable,
-        &input.ty,
+        &traced_type(&input.ty),
       )?;
       stream.push(q!(let #variable = #cast;))
     }
@@ -59,7 +60,22 @@ impl From<&Vec<Input>> for RustArgs {
     let mut stream = vec![];
 
     for input in inputs {
-      stream.push(Ident::new_raw(&input.variable, Span::call_site()))
+      let variable = Ident::new_raw(&input.variable, Span::call_site());
+      // a `&str` is received as a `String` and borrowed for the call
+      if is_str_reference(&input.ty) {
+        stream.push(q!(&#variable))
+      } else if extract_type_from_option(&input.ty).is_some_and(is_str_reference) {
+        stream.push(q!(#variable.as_deref()))
+      } else if extract_wrapper(&input.ty).is_some() {
+        stream.push(wrap(&input.ty, q!(#variable)))
+      } else if let Some(inner) =
+        extract_type_from_option(&input.ty).filter(|x| extract_wrapper(x).is_some())
+      {
+        let value = wrap(inner, q!(value));
+        stream.push(q!(#variable.map(|value| #value)))
+      } else {
+        stream.push(q!(#variable))
+      }
     }
 
     Self(stream)
@@ -78,17 +94,146 @@ impl From<RustTransforms> for Vec<TokenStream2> {
   }
 }
 
-impl From<RustArgs> for Vec<Ident> {
+impl From<RustArgs> for Vec<TokenStream2> {
   fn from(types: RustArgs) -> Self {
     types.0
   }
 }
 
+///
+/// The type which is traced for an argument and received from Dart, `&str` is received as a `String` and
+/// a `Box`, `Arc`, `Rc`, or `Cow` as the owned type it wraps.
+pub fn traced_type(ty: &syn::Type) -> syn::Type {
+  if is_str_reference(ty) {
+    return syn::parse_quote!(::std::string::String);
+  }
+
+  match ty {
+    syn::Type::Path(path) if path.qself.is_none() && path.path.is_ident("str") => {
+      return syn::parse_quote!(::std::string::String)
+    }
+    syn::Type::Slice(slice) => {
+      let elem = &slice.elem;
+      return syn::parse_quote!(::std::vec::Vec<#elem>);
+    }
+    _ => (),
+  }
+
+  if let Some((_, inner)) = extract_wrapper(ty) {
+    return traced_type(inner);
+  }
+
+  match extract_type_from_option(ty) {
+    Some(inner) if is_str_reference(inner) || extract_wrapper(inner).is_some() => {
+      let inner = traced_type(inner);
+      syn::parse_quote!(::std::option::Option<#inner>)
+    }
+    _ => ty.clone(),
+  }
+}
+
+///
+/// Wrap a value of the traced type back up in the `Box`, `Arc`, `Rc`, or `Cow` of the argument.
+fn wrap(ty: &syn::Type, value: TokenStream2) -> TokenStream2 {
+  match extract_wrapper(ty) {
+    Some((wrapper, inner)) => {
+      let value = wrap(inner, value);
+      match wrapper.as_str() {
+        "Box" => q!(::std::boxed::Box::from(#value)),
+        "Arc" => q!(::std::sync::Arc::from(#value)),
+        "Rc" => q!(::std::rc::Rc::from(#value)),
+        _ => q!(::std::borrow::Cow::Owned(#value)),
+      }
+    }
+    None => value,
+  }
+}
+
 pub fn flatten_types(ty: &syn::Type, mut types: Vec<String>) -> syn::Result<Vec<String>> {
+  if let Some((_, inner)) = extract_wrapper(ty) {
+    return flatten_types(inner, types);
+  }
+
   match &ty {
-    syn::Type::Tuple(_expr) => Ok(vec!["()".to_string()]),
+    syn::Type::Tuple(tuple) if tuple.elems.is_empty() => {
+      types.push("()".to_string());
+      Ok(types)
+    }
+    // a tuple is delimited so that each of its elements can be flattened in turn
+    syn::Type::Tuple(tuple) => {
+      types.push("(".to_string());
+      for (i, elem) in tuple.elems.iter().enumerate() {
+        if i > 0 {
+          types.push(",".to_string());
+        }
+        types = flatten_types(elem, types)?;
+      }
+      types.push(")".to_string());
+      Ok(types)
+    }
+    ty if is_str_reference(ty) => {
+      types.push("&str".to_string());
+      Ok(types)
+    }
+    // a `str` or slice is only reached through a wrapper such as `Box<str>` or `Cow<'_, [u8]>`
+    syn::Type::Path(expr) if expr.qself.is_none() && expr.path.is_ident("str") => {
+      types.push("String".to_string());
+      Ok(types)
+    }
+    syn::Type::Slice(slice) => {
+      types.push("Vec".to_string());
+      flatten_types(&slice.elem, types)
+    }
+    // an array is flattened with its length, which has to be known to generate Dart
+    syn::Type::Array(array) => match &array.len {
+      syn::Expr::Lit(syn::ExprLit {
+        lit: syn::Lit::Int(len),
+        ..
+      }) => {
+        types.push("Array".to_string());
+        types.push(len.base10_digits().to_string());
+        flatten_types(&array.elem, types)
+      }
+      len => Err(syn::Error::new_spanned(
+        len,
+        "the length of an array passed to Dart must be an integer literal",
+      )),
+    },
     syn::Type::Path(expr) => {
       let last = expr.path.segments.last().unwrap();
+      let generics = match &last.arguments {
+        syn::PathArguments::AngleBracketed(params) => params
+          .args
+          .iter()
+          .filter_map(|arg| match arg {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+          })
+          .collect::<Vec<&syn::Type>>(),
+        _ => vec![],
+      };
+
+      // a map is flattened as its key followed by its value, so the key must be a single type
+      match (last.ident.to_string().as_str(), &generics[..]) {
+        ("HashMap" | "BTreeMap", [key, value, ..]) => {
+          let key_types = flatten_types(key, vec![])?;
+          if !matches!(&key_types[..], [key] if key != "()" && key != "&str") {
+            return Err(syn::Error::new_spanned(
+              key,
+              "not a supported map key for Dart interop, please use a scalar, a String, or a struct or enum",
+            ));
+          }
+          types.push("Map".to_string());
+          types.extend(key_types);
+          return flatten_types(value, types);
+        }
+        ("HashSet" | "BTreeSet", [elem, ..]) => {
+          types.push("Set".to_string());
+          return flatten_types(elem, types);
+        }
+        _ => (),
+      }
+
       types.push(last.ident.to_string());
 
       if let syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments {
@@ -110,18 +255,65 @@ pub fn flatten_types(ty: &syn::Type, mut types: Vec<String>) -> syn::Result<Vec<
   }
 }
 
+///
+/// The flattened types of each element of a flattened tuple.
+pub fn tuple_elements<'a>(types: &'a [&'a str]) -> Vec<&'a [&'a str]> {
+  let mut elements = vec![];
+  let mut depth = 0;
+  let mut start = 1;
+  for (i, ty) in types.iter().enumerate() {
+    match *ty {
+      "(" => depth += 1,
+      ")" if depth == 1 => {
+        elements.push(&types[start..i]);
+        break;
+      }
+      ")" => depth -= 1,
+      "," if depth == 1 => {
+        elements.push(&types[start..i]);
+        start = i + 1;
+      }
+      _ => (),
+    }
+  }
+
+  elements
+}
+
+///
+/// The C type of a scalar which is passed by value, `None` for types which are serialized.
+pub fn rust_c_scalar(ty: &str) -> Option<TokenStream2> {
+  let result = match ty {
+    "i8" => q!(::std::os::raw::c_schar),
+    "i16" => q!(::std::os::raw::c_short),
+    "i32" => q!(::std::os::raw::c_int),
+    "i64" => q!(::std::os::raw::c_longlong),
+    "u8" => q!(::std::os::raw::c_uchar),
+    "u16" => q!(::std::os::raw::c_ushort),
+    "u32" => q!(::std::os::raw::c_uint),
+    "u64" => q!(::std::os::raw::c_ulonglong),
+    "f32" => q!(::std::os::raw::c_float),
+    "f64" => q!(::std::os::raw::c_double),
+    "bool" => q!(::std::os::raw::c_char), // i8
+    // the unicode scalar value
+    "char" => q!(::std::os::raw::c_uint),
+    _ => return None,
+  };
+
+  Some(result)
+}
+
 fn rust_c_type(ty: &[&str], type_: &syn::Type) -> syn::Result<TokenStream2> {
   let result = match ty[..] {
-    ["String"] => q!(*const ::std::os::raw::c_char),
-    ["i64"] => q!(::std::os::raw::c_longlong),
-    ["f64"] => q!(::std::os::raw::c_double),
-    ["bool"] => q!(::std::os::raw::c_char), // i8
-    ["Vec", ..] => q!(*const u8),
+    ["String"] | ["&str"] => q!(*const ::std::os::raw::c_char),
+    [scalar] if rust_c_scalar(scalar).is_some() => rust_c_scalar(scalar).unwrap(),
+    ["Vec", ..] | ["Map", ..] | ["Set", ..] | ["Array", ..] | ["(", ..] => q!(*const u8),
     [serialized] if serialized != "Option" => q!(*const u8),
-    ["Option", "String"] => q!(*const ::std::os::raw::c_char),
-    ["Option", "i64"] => q!(*const ::std::os::raw::c_longlong),
-    ["Option", "f64"] => q!(*const ::std::os::raw::c_double),
-    ["Option", "bool"] => q!(*const ::std::os::raw::c_char), // i8
+    ["Option", "String"] | ["Option", "&str"] => q!(*const ::std::os::raw::c_char),
+    ["Option", scalar] if rust_c_scalar(scalar).is_some() => {
+      let c_type = rust_c_scalar(scalar).unwrap();
+      q!(*const #c_type)
+    }
     ["Option", ..] => q!(*const u8),
     _ => {
       return Err(syn::Error::new_spanned(
@@ -136,21 +328,38 @@ fn rust_c_type(ty: &[&str], type_: &syn::Type) -> syn::Result<TokenStream2> {
 
 fn cast_c_type_to_rust(types: &[&str], variable: &str, ty: &Type) -> syn::Result<TokenStream2> {
   let result = match types[..] {
-    ["String"] => {
+    ["String"] | ["&str"] => {
       let variable = Ident::new_raw(variable, Span::call_site());
       q!(cstr!(#variable, panic!("invalid C string")).to_string())
     }
-    ["i64"] => {
+    ["bool"] => {
       let variable = Ident::new_raw(variable, Span::call_site());
-      q!(#variable)
+      q!(#variable != 0)
     }
-    ["f64"] => {
+    ["char"] => {
+      let variable_name = variable;
+      let variable = Ident::new_raw(variable, Span::call_site());
+      q! {
+        ::std::char::from_u32(#variable).unwrap_or_else(|| {
+          panic!("An invalid char {} was received for {}", #variable, #variable_name)
+        })
+      }
+    }
+    [scalar] if rust_c_scalar(scalar).is_some() => {
       let variable = Ident::new_raw(variable, Span::call_site());
       q!(#variable)
     }
-    ["bool"] => {
+    ["Vec", elem] if typed_data(types).is_some() => {
+      let variable_name = variable;
       let variable = Ident::new_raw(variable, Span::call_site());
-      q!(#variable != 0)
+      let payload = payload(variable, variable_name, types[0]);
+      let elem = Ident::new(elem, Span::call_site());
+      q! {
+        {
+          #payload
+          ::membrane::utils::typed_data::<#elem>(&data[8..])
+        }
+      }
     }
     // this also handles Vec
     [serialized, ..] if serialized != "Option" => {
@@ -163,7 +372,7 @@ fn cast_c_type_to_rust(types: &[&str], variable: &str, ty: &Type) -> syn::Result
         }
       }
     }
-    ["Option", "String"] => {
+    ["Option", "String"] | ["Option", "&str"] => {
       let variable_name = variable;
       let variable = Ident::new_raw(variable, Span::call_site());
       q! {
@@ -180,29 +389,32 @@ fn cast_c_type_to_rust(types: &[&str], variable: &str, ty: &Type) -> syn::Result
         }
       }
     }
-    ["Option", "i64"] => {
+    ["Option", "bool"] => {
       let variable = Ident::new_raw(variable, Span::call_site());
       q! {
         match unsafe { #variable.as_ref() } {
-          Some(val) => Some(*val),
+          Some(val) => Some(*val != 0),
           None => None
         }
       }
     }
-    ["Option", "f64"] => {
+    ["Option", "char"] => {
+      let variable_name = variable;
       let variable = Ident::new_raw(variable, Span::call_site());
       q! {
         match unsafe { #variable.as_ref() } {
-          Some(val) => Some(*val),
+          Some(val) => Some(::std::char::from_u32(*val).unwrap_or_else(|| {
+            panic!("An invalid char {} was received for {}", val, #variable_name)
+          })),
           None => None
         }
       }
     }
-    ["Option", "bool"] => {
+    ["Option", scalar] if rust_c_scalar(scalar).is_some() => {
       let variable = Ident::new_raw(variable, Span::call_site());
       q! {
         match unsafe { #variable.as_ref() } {
-          Some(val) => Some(*val != 0),
+          Some(val) => Some(*val),
           None => None
         }
       }
@@ -241,6 +453,17 @@ fn cast_c_type_to_rust(types: &[&str], variable: &str, ty: &Type) -> syn::Result
 }
 
 fn deserialize(variable: Ident, variable_name: &str, ty: &Type, str_ty: &str) -> TokenStream2 {
+  let payload = payload(variable, variable_name, str_ty);
+  q! {
+    #payload
+    // deserialize, skipping the known 8 byte length field
+    ::membrane::bincode::deserialize::<#ty>(&data[8..]).expect(
+      format!("Deserialization error at variable '{}' of type '{}'", #variable_name, #str_ty).as_str()
+    )
+  }
+}
+
+fn payload(variable: Ident, variable_name: &str, str_ty: &str) -> TokenStream2 {
   q! {
     let data = unsafe {
       use std::convert::TryInto;
@@ -254,9 +477,5 @@ fn deserialize(variable: Ident, variable_name: &str, ty: &Type, str_ty: &str) ->
       // return the rest of the bytes for deserialization
       ::std::slice::from_raw_parts(#variable, elements)
     };
-    // deserialize, skipping the known 8 byte length field
-    ::membrane::bincode::deserialize::<#ty>(&data[8..]).expect(
-      format!("Deserialization error at variable '{}' of type '{}'", #variable_name, #str_ty).as_str()
-    )
   }
 }
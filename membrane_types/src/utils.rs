@@ -35,3 +35,39 @@ pub fn extract_type_from_option(ty: &syn::Type) -> Option<&syn::Type> {
       _ => None,
     })
 }
+
+///
+/// Whether the type is `&str`, the only reference which can be passed from Dart.
+pub fn is_str_reference(ty: &syn::Type) -> bool {
+  match ty {
+    syn::Type::Reference(syn::TypeReference {
+      elem, mutability, ..
+    }) if mutability.is_none() => {
+      matches!(&**elem, syn::Type::Path(path) if path.qself.is_none() && path.path.is_ident("str"))
+    }
+    _ => false,
+  }
+}
+
+///
+/// The name and inner type of a `Box`, `Arc`, `Rc`, or `Cow`, which serialize exactly like the type they wrap.
+pub fn extract_wrapper(ty: &syn::Type) -> Option<(String, &syn::Type)> {
+  let last = match ty {
+    syn::Type::Path(path) if path.qself.is_none() => path.path.segments.last()?,
+    _ => return None,
+  };
+
+  let name = last.ident.to_string();
+  if !["Box", "Arc", "Rc", "Cow"].contains(&name.as_str()) {
+    return None;
+  }
+
+  match &last.arguments {
+    // a `Cow` also has a lifetime so the wrapped type is the last argument
+    PathArguments::AngleBracketed(params) => params.args.iter().rev().find_map(|arg| match arg {
+      GenericArgument::Type(ty) => Some((name.clone(), ty)),
+      _ => None,
+    }),
+    _ => None,
+  }
+}